@@ -1,30 +1,27 @@
 use cache::KgCache;
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use embedding::Embedder;
 use grc20_core::neo4rs;
 use std::sync::Arc;
 
-const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
-
 #[derive(Clone)]
 pub struct KnowledgeGraph {
     pub neo4j: Arc<neo4rs::Graph>,
     pub cache: Option<Arc<KgCache>>,
-    pub embedding_model: Arc<TextEmbedding>,
+    pub embedder: Arc<dyn Embedder>,
 }
 
 impl juniper::Context for KnowledgeGraph {}
 
 impl KnowledgeGraph {
-    pub fn new(neo4j: Arc<neo4rs::Graph>, cache: Option<Arc<KgCache>>) -> Self {
+    pub fn new(
+        neo4j: Arc<neo4rs::Graph>,
+        cache: Option<Arc<KgCache>>,
+        embedder: Arc<dyn Embedder>,
+    ) -> Self {
         Self {
             neo4j,
             cache,
-            embedding_model: Arc::new(
-                TextEmbedding::try_new(
-                    InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
-                )
-                .expect("Failed to initialize embedding model"),
-            ),
+            embedder,
         }
     }
 }
@@ -10,7 +10,9 @@ use axum::{
 };
 use cache::{CacheConfig, KgCache};
 use clap::{Args, Parser};
+use embedding::{Embedder, FastEmbedder};
 use grc20_core::neo4rs;
+use grc20_sdk::models::EmbeddingConfig;
 use juniper::{EmptyMutation, EmptySubscription, RootNode};
 use juniper_axum::{extract::JuniperRequest, graphiql, playground, response::JuniperResponse};
 use std::time::Duration;
@@ -45,6 +47,15 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
+    let embedder = FastEmbedder::new(&args.embedding_model)?;
+    // Search results would be meaningless with another model than the index's
+    EmbeddingConfig::check(&neo4j, embedder.model(), embedder.dim()).await?;
+    tracing::info!(
+        "Using embedding model {} (dim = {})",
+        embedder.model(),
+        embedder.dim()
+    );
+
     let schema = Schema::new(
         RootQuery,
         EmptyMutation::<KnowledgeGraph>::new(),
@@ -72,7 +83,11 @@ async fn main() -> anyhow::Result<()> {
         .route("/playground", get(playground("/graphql", "/subscriptions")))
         .route("/", get(homepage))
         .layer(Extension(Arc::new(schema)))
-        .layer(Extension(KnowledgeGraph::new(Arc::new(neo4j), cache)))
+        .layer(Extension(KnowledgeGraph::new(
+            Arc::new(neo4j),
+            cache,
+            Arc::new(embedder),
+        )))
         .layer(cors);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
@@ -95,6 +110,10 @@ struct AppArgs {
 
     #[clap(flatten)]
     cache_args: CacheArgs,
+
+    /// Embedding model used for search. Must match the model the index was built with.
+    #[arg(long, env = "EMBEDDING_MODEL", default_value = embedding::DEFAULT_MODEL)]
+    embedding_model: String,
 }
 
 #[derive(Debug, Args)]
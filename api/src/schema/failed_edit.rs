@@ -0,0 +1,46 @@
+use juniper::{graphql_object, ScalarValue};
+
+use grc20_core::mapping::Entity;
+use grc20_sdk::models::FailedEdit as SdkFailedEdit;
+
+use crate::context::KnowledgeGraph;
+
+/// Edit (or op of an edit) that was rejected by the indexer
+pub struct FailedEdit {
+    entity: Entity<SdkFailedEdit>,
+}
+
+impl FailedEdit {
+    pub fn new(entity: Entity<SdkFailedEdit>) -> Self {
+        Self { entity }
+    }
+}
+
+#[graphql_object]
+#[graphql(context = KnowledgeGraph, scalar = S: ScalarValue)]
+impl FailedEdit {
+    /// Failed edit ID
+    fn id(&self) -> &str {
+        self.entity.id()
+    }
+
+    /// IPFS URI of the edit content
+    fn content_uri(&self) -> &str {
+        &self.entity.attributes.content_uri
+    }
+
+    /// ID of the space the edit was published to
+    fn space_id(&self) -> &str {
+        &self.entity.attributes.space
+    }
+
+    /// Block at which the edit was published
+    fn block_number(&self) -> String {
+        self.entity.attributes.block_number.to_string()
+    }
+
+    /// Reason why the edit was rejected
+    fn reason(&self) -> &str {
+        &self.entity.attributes.reason
+    }
+}
@@ -5,6 +5,7 @@ pub mod entity;
 pub mod entity_filter;
 pub mod entity_order_by;
 pub mod entity_version;
+pub mod failed_edit;
 pub mod property;
 pub mod query;
 pub mod relation;
@@ -21,6 +22,7 @@ pub use attribute_filter::EntityAttributeFilter;
 pub use entity::Entity;
 pub use entity_filter::{AttributeFilter, EntityFilter, EntityRelationFilter};
 pub use entity_version::EntityVersion;
+pub use failed_edit::FailedEdit;
 pub use property::Property;
 pub use query::RootQuery;
 pub use relation::Relation;
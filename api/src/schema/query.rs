@@ -10,11 +10,13 @@ use grc20_core::{
         relation, RelationEdge,
     },
 };
-use grc20_sdk::models::{account, property, space};
+use grc20_sdk::models::{account, failed_edit, property, space};
 
 use crate::{
     context::KnowledgeGraph,
-    schema::{Account, AccountFilter, Entity, Relation, RelationFilter, Space, SpaceFilter},
+    schema::{
+        Account, AccountFilter, Entity, FailedEdit, Relation, RelationFilter, Space, SpaceFilter,
+    },
 };
 
 use super::{entity_order_by::OrderDirection, EntityFilter, Triple};
@@ -35,7 +37,8 @@ impl RootQuery {
         Ok(Space::load(&executor.context().neo4j, id, version).await?)
     }
 
-    /// Returns multiple spaces according to the provided filter
+    /// Returns multiple spaces according to the provided filter. Archived spaces
+    /// are excluded unless `include_archived` is set.
     #[allow(clippy::too_many_arguments)]
     async fn spaces<'a, S: ScalarValue>(
         &'a self,
@@ -44,8 +47,10 @@ impl RootQuery {
         version: Option<String>,
         #[graphql(default = 100)] first: i32,
         #[graphql(default = 0)] skip: i32,
+        #[graphql(default = false)] include_archived: bool,
     ) -> FieldResult<Vec<Space>> {
-        let mut query = space::find_many(&executor.context().neo4j, indexer_ids::INDEXER_SPACE_ID);
+        let mut query = space::find_many(&executor.context().neo4j, indexer_ids::INDEXER_SPACE_ID)
+            .include_archived(include_archived);
 
         // Apply filters if provided
         if let Some(where_) = &r#where {
@@ -159,6 +164,36 @@ impl RootQuery {
             .try_collect::<Vec<_>>()
             .await?)
     }
+
+    /// Returns the edits (or ops) that were rejected by the indexer, optionally
+    /// filtered by space
+    async fn failed_edits<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+        space_id: Option<String>,
+        #[graphql(default = 100)] first: i32,
+        #[graphql(default = 0)] skip: i32,
+    ) -> FieldResult<Vec<FailedEdit>> {
+        let mut query =
+            failed_edit::find_many(&executor.context().neo4j, indexer_ids::INDEXER_SPACE_ID);
+
+        if let Some(space_id) = space_id {
+            query = query.space(prop_filter::value(space_id));
+        }
+
+        if first > 1000 {
+            return Err("Cannot query more than 1000 failed edits at once".into());
+        }
+
+        Ok(query
+            .limit(first as usize)
+            .skip(skip as usize)
+            .send()
+            .await?
+            .map_ok(FailedEdit::new)
+            .try_collect::<Vec<_>>()
+            .await?)
+    }
     /// Returns a single entity identified by its ID and space ID
     async fn entity<'a, S: ScalarValue>(
         &'a self,
@@ -325,16 +360,7 @@ impl RootQuery {
         #[graphql(default = 100)] first: i32,
         #[graphql(default = 0)] skip: i32,
     ) -> FieldResult<Vec<Triple>> {
-        let embedding = executor
-            .context()
-            .embedding_model
-            .embed(vec![&query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let embedding = executor.context().embedder.embed_one(&query)?;
 
         let query = mapping::triple::search(&executor.context().neo4j, embedding)
             .limit(first as usize)
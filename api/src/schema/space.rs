@@ -185,6 +185,11 @@ impl Space {
             .as_deref()
     }
 
+    /// Whether the space was archived
+    fn archived(&self) -> bool {
+        self.entity.attributes.archived.unwrap_or(false)
+    }
+
     // fn updated_at(&self) -> &str {
     //     &self.entity.updated_at
     // }
@@ -297,6 +302,48 @@ impl Space {
             .await?)
     }
 
+    /// Space this space was upgraded from
+    async fn predecessor<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+    ) -> FieldResult<Option<Space>> {
+        let mut predecessors =
+            models::space::predecessors(&executor.context().neo4j, self.entity.id())
+                .limit(1)
+                .send()
+                .await?
+                .and_then(|ranking| Space::load(&executor.context().neo4j, ranking.space_id, None))
+                .filter_map(|space| async move { space.transpose() })
+                .try_collect::<Vec<_>>()
+                .await?;
+
+        Ok(predecessors.pop())
+    }
+
+    /// Spaces this space was upgraded to
+    async fn successors<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+        #[graphql(default = 100)] first: i32,
+        #[graphql(default = 0)] skip: i32,
+    ) -> FieldResult<Vec<Space>> {
+        let query = models::space::successors(&executor.context().neo4j, self.entity.id());
+
+        if first > 1000 {
+            return Err("Cannot query more than 1000 relations at once".into());
+        }
+
+        Ok(query
+            .limit(first as usize)
+            .skip(skip as usize)
+            .send()
+            .await?
+            .and_then(|ranking| Space::load(&executor.context().neo4j, ranking.space_id, None))
+            .filter_map(|space| async move { space.transpose() })
+            .try_collect::<Vec<_>>()
+            .await?)
+    }
+
     async fn types<'a, S: ScalarValue>(
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
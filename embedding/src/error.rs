@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EmbeddingError {
+    #[error("Unsupported embedding model: {0}")]
+    UnsupportedModel(String),
+
+    #[error("Model initialization error: {0}")]
+    Initialization(String),
+
+    #[error("Embedding error: {0}")]
+    Embedding(String),
+
+    #[error("Expected {expected} embeddings, got {actual}")]
+    Count { expected: usize, actual: usize },
+}
@@ -0,0 +1,114 @@
+use fastembed::{EmbeddingModel, InitOptions, ModelInfo, TextEmbedding};
+
+use crate::{Embedder, EmbeddingError};
+
+/// Default number of texts embedded at once
+const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// [`Embedder`] backed by a local [fastembed](https://github.com/Anush008/fastembed-rs) model
+pub struct FastEmbedder {
+    model: TextEmbedding,
+    model_code: String,
+    dim: usize,
+    batch_size: usize,
+}
+
+impl FastEmbedder {
+    /// Loads the fastembed model identified by `model_code` (e.g.: `Qdrant/all-MiniLM-L6-v2-onnx`),
+    /// downloading it if needed. The lookup is case-insensitive.
+    pub fn new(model_code: &str) -> Result<Self, EmbeddingError> {
+        let info = Self::model_info(model_code)?;
+
+        let model = TextEmbedding::try_new(
+            InitOptions::new(info.model.clone()).with_show_download_progress(true),
+        )
+        .map_err(|e| EmbeddingError::Initialization(format!("{e:?}")))?;
+
+        Ok(Self {
+            model,
+            model_code: info.model_code,
+            dim: info.dim,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    /// Sets the number of texts embedded at once
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Returns the codes of the models supported by fastembed
+    pub fn supported_models() -> Vec<String> {
+        TextEmbedding::list_supported_models()
+            .into_iter()
+            .map(|info| info.model_code)
+            .collect()
+    }
+
+    /// Returns the dimension of the embeddings of the model `model_code`, without
+    /// loading it
+    pub fn model_dim(model_code: &str) -> Result<usize, EmbeddingError> {
+        Ok(Self::model_info(model_code)?.dim)
+    }
+
+    fn model_info(model_code: &str) -> Result<ModelInfo<EmbeddingModel>, EmbeddingError> {
+        TextEmbedding::list_supported_models()
+            .into_iter()
+            .find(|info| info.model_code.eq_ignore_ascii_case(model_code))
+            .ok_or_else(|| EmbeddingError::UnsupportedModel(model_code.to_string()))
+    }
+}
+
+impl Embedder for FastEmbedder {
+    fn model(&self) -> &str {
+        &self.model_code
+    }
+
+    fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>, EmbeddingError> {
+        let expected = texts.len();
+
+        let embeddings = self
+            .model
+            .embed(texts, Some(self.batch_size))
+            .map_err(|e| EmbeddingError::Embedding(format!("{e:?}")))?;
+
+        if embeddings.len() != expected {
+            return Err(EmbeddingError::Count {
+                expected,
+                actual: embeddings.len(),
+            });
+        }
+
+        Ok(embeddings
+            .into_iter()
+            .map(|embedding| embedding.into_iter().map(|v| v as f64).collect())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_model_is_supported() {
+        let info = FastEmbedder::model_info(crate::DEFAULT_MODEL).unwrap();
+        assert_eq!(info.dim, 384);
+
+        let info = FastEmbedder::model_info(&crate::DEFAULT_MODEL.to_lowercase()).unwrap();
+        assert_eq!(info.model_code, crate::DEFAULT_MODEL);
+    }
+
+    #[test]
+    fn test_unsupported_model() {
+        assert!(matches!(
+            FastEmbedder::new("not-a-model"),
+            Err(EmbeddingError::UnsupportedModel(_))
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+mod error;
+mod fast_embedder;
+
+pub use error::EmbeddingError;
+pub use fast_embedder::FastEmbedder;
+
+/// Model used when none is configured. The sink, the API and the MCP server must all
+/// use the same model for vector search to work.
+pub const DEFAULT_MODEL: &str = "Qdrant/all-MiniLM-L6-v2-onnx";
+
+/// Text embedding provider shared by the sink and the query services
+pub trait Embedder: Send + Sync {
+    /// Identifier of the model (e.g.: `Qdrant/all-MiniLM-L6-v2-onnx`)
+    fn model(&self) -> &str;
+
+    /// Dimension of the embeddings produced by the model
+    fn dim(&self) -> usize;
+
+    /// Computes the embeddings of `texts`, returned in the same order as the input.
+    /// This is CPU-bound and blocking.
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>, EmbeddingError>;
+
+    /// Computes the embedding of a single text
+    fn embed_one(&self, text: &str) -> Result<Vec<f64>, EmbeddingError> {
+        self.embed(vec![text.to_string()])?
+            .pop()
+            .ok_or(EmbeddingError::Count {
+                expected: 1,
+                actual: 0,
+            })
+    }
+}
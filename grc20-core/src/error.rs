@@ -16,4 +16,8 @@ pub enum DatabaseError {
     Infaillible(#[from] std::convert::Infallible),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Transaction error: {0}")]
+    TransactionError(String),
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 }
@@ -15,6 +15,12 @@ pub const SPACE_PLUGIN_ADDRESS: &str = "8WQFgjEoqNYHPS9niQGcfC";
 pub const SPACE_VOTING_PLUGIN_ADDRESS: &str = "GMuFbsoSsVmiMcCxc34zZA";
 pub const SPACE_MEMBER_PLUGIN_ADDRESS: &str = "AGaTTZWAbEaSrmZYTinQuc";
 pub const SPACE_PERSONAL_PLUGIN_ADDRESS: &str = "F75rm9StiixRKWTRiHGgvS";
+/// Whether the space was archived
+pub const SPACE_ARCHIVED: &str = "PY8EJkcfBW29uo54DC7rgR";
+/// Support threshold of the space's voting plugin, in parts per million
+pub const SPACE_SUPPORT_THRESHOLD: &str = "8Ka9n6m9ZFS4nGHh2cFrH6";
+/// Minimum participation of the space's voting plugin, in parts per million
+pub const SPACE_MIN_PARTICIPATION: &str = "NkP1mw3pNitzBdUvb7z99g";
 
 /// GEO_ACCOUNT > MEMBER_RELATION > INDEXED_SPACE
 pub const MEMBER_RELATION: &str = "2oGooh2PEUo8pbdMPqcBrQ";
@@ -25,6 +31,9 @@ pub const EDITOR_RELATION: &str = "24TNsjdHUhuC5qtsN13stv";
 /// SPACE > PARENT_SPACE > SPACE
 pub const PARENT_SPACE: &str = "4jLdUCbpRzdjnpjhViDm2d";
 
+/// SPACE > SUCCESSOR_SPACE > SPACE
+pub const SUCCESSOR_SPACE: &str = "PEQwyiy7XpiMrv78eHWaeF";
+
 // Voting
 /// GEO_ACCOUNT > VOTE_CAST > PROPOSAL
 pub const VOTE_CAST_TYPE: &str = "PfgzdxPYwDUTBCzkXCT9ga";
@@ -32,7 +41,16 @@ pub const VOTE_TYPE_ATTRIBUTE: &str = "UrTNUMukLGV2y4yWEnBKQv";
 
 // Proposal
 pub const PROPOSAL_TYPE: &str = "9No6qfEutiKg1WLeXDv73x";
-pub const PROPOSAL_STATUS_ATTRIBUTE: &str = "status";
+/// Status of a proposal (see `grc20_sdk::models::proposal::ProposalStatus`)
+pub const PROPOSAL_STATUS_ATTRIBUTE: &str = "DepVN4q5m2wk5uE5ATNUdZ";
+/// Number of current accept votes on a proposal
+pub const PROPOSAL_ACCEPT_VOTES_ATTRIBUTE: &str = "5FK5cDM2XUD8joBEpJT47J";
+/// Number of current reject votes on a proposal
+pub const PROPOSAL_REJECT_VOTES_ATTRIBUTE: &str = "ug7HrJNu2J5gk2SkWnfRGV";
+/// Number of current abstain votes on a proposal
+pub const PROPOSAL_ABSTAIN_VOTES_ATTRIBUTE: &str = "mHqf7LjdDaxBgTb9Gzg6Gz";
+/// Number of accounts which voted on a proposal
+pub const PROPOSAL_VOTER_COUNT_ATTRIBUTE: &str = "DuiTqM9y3xwdetREFQDAsA";
 pub const ADD_MEMBER_PROPOSAL: &str = "6dJ23LRTHRdwqoWhtivRrM";
 pub const REMOVE_MEMBER_PROPOSAL: &str = "8dJ23LRTHRdwqoWhtivRrM";
 pub const ADD_EDITOR_PROPOSAL: &str = "7W7SE2UTj5YTsQvqSmCfLN";
@@ -40,6 +58,8 @@ pub const REMOVE_EDITOR_PROPOSAL: &str = "9W7SE2UTj5YTsQvqSmCfLN";
 pub const ADD_SUBSPACE_PROPOSAL: &str = "DcEZrRpmAuwxfw7C5G7gjC";
 pub const REMOVE_SUBSPACE_PROPOSAL: &str = "FcEZrRpmAuwxfw7C5G7gjC";
 pub const EDIT_PROPOSAL: &str = "GcEZrRpmAuwxfw7C5G7gjC";
+/// Proposal created by the indexer for an edit published without an onchain proposal
+pub const SYNTHETIC_PROPOSAL: &str = "d3EBYyBpViKmXtv6a3e3M5";
 
 /// MEMBERSHIP_PROPOSAL_TYPE > PROPOSED_ACCOUNT > GEO_ACCOUNT
 /// EDITORSHIP_PROPOSAL_TYPE > PROPOSED_ACCOUNT > GEO_ACCOUNT
@@ -59,18 +79,55 @@ pub const PROPOSALS: &str = "3gmeTonVCB6B11p3YF8mj5";
 pub const EDIT_TYPE: &str = "Q2dfub76oMdzJpyR8z4GZv";
 pub const EDIT_INDEX_ATTRIBUTE: &str = "edit_index";
 pub const EDIT_CONTENT_URI_ATTRIBUTE: &str = "content_uri";
+pub const EDIT_SET_TRIPLE_COUNT_ATTRIBUTE: &str = "SRvDkuTmCGFJNfN7PriShP";
+pub const EDIT_DELETE_TRIPLE_COUNT_ATTRIBUTE: &str = "aNDqYEMKzrbwKXnDs3oUZm";
+pub const EDIT_CREATE_RELATION_COUNT_ATTRIBUTE: &str = "VJCaugtLJ6WnGE5wsSBh4D";
+pub const EDIT_DELETE_RELATION_COUNT_ATTRIBUTE: &str = "cc5mJTCJZTLaSUaD25bwx1";
+pub const EDIT_DELETE_ENTITY_COUNT_ATTRIBUTE: &str = "wEkWNuQ2TMygnG9CZE8s8M";
+
+/// Edit > EDIT_AUTHOR > Account
+pub const EDIT_AUTHOR: &str = "t5PqSYdhi5vXfQdtt63U5b";
 
 /// Proposal > PROPOSED_EDIT > Edit
 pub const PROPOSED_EDIT: &str = "8NPzSYo8fXBeFHs7WvGtif";
 /// Space > EDITS > Edit
 pub const EDITS: &str = "QRkn8QWyKjo1sKmpVKsoUJ";
 
+// Failed edits
+/// Failed edit type ID (edits or ops rejected by the indexer)
+pub const FAILED_EDIT_TYPE: &str = "4VY8V1W6v6tU3yHUXus5kh";
+pub const FAILED_EDIT_SPACE_ATTRIBUTE: &str = "Bku5Zi8MZM27Z6kDVYpymi";
+pub const FAILED_EDIT_REASON_ATTRIBUTE: &str = "KYvAEGCVZigawX2J6KEf32";
+
+// Skipped edits
+/// Skipped edit type ID (edits of blacklisted spaces, authors or DAOs)
+pub const SKIPPED_EDIT_TYPE: &str = "RiqypqizX2qxKFBZbu5FRf";
+pub const SKIPPED_EDIT_SPACE_ATTRIBUTE: &str = "iXFTKAFj2xm5s6wWykmP49";
+pub const SKIPPED_EDIT_AUTHOR_ATTRIBUTE: &str = "h5b2dRorLFwaAdYAsqbvoV";
+pub const SKIPPED_EDIT_DAO_ATTRIBUTE: &str = "5ENDidShuq8LPfSe8y3QCF";
+pub const SKIPPED_EDIT_REASON_ATTRIBUTE: &str = "3bayQE8nLQKG1dYyt6VaKg";
+
 // Cursor
 /// Cursor type ID
 pub const CURSOR_TYPE: &str = "CURSOR_TYPE"; // TODO: Replace by GRC20 ID
 /// Cursor ID
 pub const CURSOR_ID: &str = "grc20-cursor"; // TODO: Replace by GRC20 ID
+/// Cursor used when re-indexing spaces or blocks
+pub const REINDEX_CURSOR_ID: &str = "grc20-reindex-cursor"; // TODO: Replace by GRC20 ID
 pub const CURSOR_ATTRIBUTE: &str = "cursor-attribute";
 pub const BLOCK_NUMBER_ATTRIBUTE: &str = "block-number-attribute";
 pub const BLOCK_TIMESTAMP_ATTRIBUTE: &str = "block-timestamp-attribute";
 pub const VERSION_ATTRIBUTE: &str = "version-attribute";
+
+// Embedding model
+/// Embedding config type ID
+pub const EMBEDDING_CONFIG_TYPE: &str = "EMBEDDING_CONFIG_TYPE"; // TODO: Replace by GRC20 ID
+/// Embedding config ID
+pub const EMBEDDING_CONFIG_ID: &str = "grc20-embedding-config"; // TODO: Replace by GRC20 ID
+pub const EMBEDDING_MODEL_ATTRIBUTE: &str = "embedding-model-attribute";
+pub const EMBEDDING_DIM_ATTRIBUTE: &str = "embedding-dim-attribute";
+
+// Migrations
+/// Migration type ID
+pub const MIGRATION_TYPE: &str = "MIGRATION_TYPE"; // TODO: Replace by GRC20 ID
+pub const MIGRATION_ID_ATTRIBUTE: &str = "migration-id-attribute";
@@ -3,6 +3,7 @@ pub mod error;
 pub mod graph_uri;
 pub mod ids;
 pub mod mapping;
+pub mod metrics;
 pub mod neo4j_utils;
 pub mod pb;
 
@@ -11,7 +11,7 @@ use super::{
         query_builder::{MatchQuery, QueryBuilder, Subquery},
         Query, QueryStream, VersionFilter,
     },
-    AttributeFilter, AttributeNode, PropFilter, Triple, TriplesConversionError, Value,
+    transaction, AttributeFilter, AttributeNode, PropFilter, Triple, TriplesConversionError, Value,
 };
 
 /// Group of attributes belonging to the same entity.
@@ -240,11 +240,21 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<T> {
             UNWIND $attributes AS attribute
             CALL (e, attribute) {{
                 MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: attribute.id}})
-                WHERE r.max_version IS null AND r.min_version <> $space_version
+                WHERE r.max_version IS null AND (r.min_version <> $space_version OR coalesce(r.`{CREATED_AT_BLOCK}`, "") <> $block_number)
                 SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
             }}
             CALL (e, attribute) {{
-                MERGE (e) -[:ATTRIBUTE {{space_id: $space_id, min_version: $space_version}}]-> (m:Attribute {{id: attribute.id}})
+                MERGE (e) -[r:ATTRIBUTE {{space_id: $space_id, min_version: $space_version, `{CREATED_AT_BLOCK}`: $block_number}}]-> (m:Attribute {{id: attribute.id}})
+                ON CREATE SET r += {{
+                    `{CREATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+                SET r.max_version = null
                 SET m += attribute
             }}
             "#,
@@ -266,7 +276,7 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<T> {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "attributes::insert_one", query).await?;
 
         Ok(())
     }
@@ -324,11 +334,21 @@ impl Query<()> for InsertManyQuery {
             UNWIND attributes.attributes AS attribute
             CALL (e, attribute) {{
                 MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: attribute.id}})
-                WHERE r.max_version IS null AND r.min_version <> $space_version
+                WHERE r.max_version IS null AND (r.min_version <> $space_version OR coalesce(r.`{CREATED_AT_BLOCK}`, "") <> $block_number)
                 SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
             }}
             CALL (e, attribute) {{
-                MERGE (e) -[:ATTRIBUTE {{space_id: $space_id, min_version: $space_version}}]-> (m:Attribute {{id: attribute.id}})
+                MERGE (e) -[r:ATTRIBUTE {{space_id: $space_id, min_version: $space_version, `{CREATED_AT_BLOCK}`: $block_number}}]-> (m:Attribute {{id: attribute.id}})
+                ON CREATE SET r += {{
+                    `{CREATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+                SET r.max_version = null
                 SET m += attribute
             }}
             "#,
@@ -368,7 +388,7 @@ impl Query<()> for InsertManyQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "attributes::insert_many", query).await?;
 
         Ok(())
     }
@@ -429,8 +449,7 @@ where
             attrs: Vec<AttributeNode>,
         }
 
-        let result = neo4j
-            .execute(query.build())
+        let result = transaction::execute(&neo4j, "attributes::find_one", query.build())
             .await?
             .next()
             .await?
@@ -533,8 +552,7 @@ where
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = neo4j
-            .execute(query)
+        let stream = transaction::execute(&neo4j, "attributes::find_many", query)
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -0,0 +1,349 @@
+use std::sync::{Arc, RwLock};
+
+use futures::{future::BoxFuture, stream, FutureExt, StreamExt};
+
+use crate::{block::BlockMetadata, error::DatabaseError, mapping::Triple};
+
+use super::{Backend, TripleFilter, TripleStream};
+
+/// In-memory [`Backend`], mostly meant for tests. Triples are stored as versioned
+/// `ATTRIBUTE` edges, like in Neo4j, so that versions and spaces behave the same way.
+///
+/// Clones of a [`MemoryBackend`] share the same graph.
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    edges: Arc<RwLock<Vec<AttributeEdge>>>,
+}
+
+#[derive(Clone, Debug)]
+struct AttributeEdge {
+    space_id: String,
+    min_version: String,
+    max_version: Option<String>,
+    created_at_block: String,
+    updated_at_block: String,
+    triple: Triple,
+}
+
+impl AttributeEdge {
+    fn is(&self, entity_id: &str, attribute_id: &str, space_id: &str) -> bool {
+        self.triple.entity == entity_id
+            && self.triple.attribute == attribute_id
+            && self.space_id == space_id
+    }
+
+    fn matches(&self, filter: &TripleFilter) -> bool {
+        filter
+            .entity_id
+            .as_ref()
+            .is_none_or(|f| f.matches(&self.triple.entity))
+            && filter
+                .attribute_id
+                .as_ref()
+                .is_none_or(|f| f.matches(&self.triple.attribute))
+            && filter
+                .value
+                .as_ref()
+                .is_none_or(|f| f.matches(&self.triple.value.value))
+            && filter
+                .value_type
+                .as_ref()
+                .is_none_or(|f| f.matches(&self.triple.value.value_type.to_string()))
+            && filter
+                .space_id
+                .as_ref()
+                .is_none_or(|f| f.matches(&self.space_id))
+            && filter
+                .version
+                .matches(&self.min_version, self.max_version.as_deref())
+    }
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn insert_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<Triple>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>> {
+        let block_number = block.block_number.to_string();
+        let mut edges = self.edges.write().expect("Memory backend lock poisoned");
+
+        for triple in triples {
+            let mut current = None;
+
+            for (idx, edge) in edges.iter_mut().enumerate() {
+                if !edge.is(&triple.entity, &triple.attribute, space_id) {
+                    continue;
+                }
+
+                if edge.min_version == space_version && edge.created_at_block == block_number {
+                    current = Some(idx);
+                } else if edge.max_version.is_none() {
+                    edge.max_version = Some(space_version.to_string());
+                    edge.updated_at_block = block_number.clone();
+                }
+            }
+
+            match current {
+                // Reopen the edge if it was deleted earlier in the same block and version
+                Some(idx) => {
+                    edges[idx].max_version = None;
+                    edges[idx].updated_at_block = block_number.clone();
+                    edges[idx].triple = triple;
+                }
+                None => edges.push(AttributeEdge {
+                    space_id: space_id.to_string(),
+                    min_version: space_version.to_string(),
+                    max_version: None,
+                    created_at_block: block_number.clone(),
+                    updated_at_block: block_number.clone(),
+                    triple,
+                }),
+            }
+        }
+
+        futures::future::ok(()).boxed()
+    }
+
+    fn delete_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<(String, String)>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>> {
+        let block_number = block.block_number.to_string();
+        let mut edges = self.edges.write().expect("Memory backend lock poisoned");
+
+        for (entity_id, attribute_id) in triples {
+            edges
+                .iter_mut()
+                .filter(|edge| edge.is(&entity_id, &attribute_id, space_id))
+                .filter(|edge| edge.max_version.is_none())
+                .for_each(|edge| {
+                    edge.max_version = Some(space_version.to_string());
+                    edge.updated_at_block = block_number.clone();
+                });
+        }
+
+        futures::future::ok(()).boxed()
+    }
+
+    fn find_triples(
+        &self,
+        filter: TripleFilter,
+    ) -> BoxFuture<'_, Result<TripleStream, DatabaseError>> {
+        let edges = self.edges.read().expect("Memory backend lock poisoned");
+
+        let triples = edges
+            .iter()
+            .filter(|edge| edge.matches(&filter))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|edge| Ok(edge.triple.clone()))
+            .collect::<Vec<_>>();
+
+        futures::future::ok(stream::iter(triples).boxed()).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use crate::mapping::{prop_filter, triple, Query, QueryStream};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_versioning() {
+        let backend = MemoryBackend::new();
+
+        let triple_v1 = Triple::new("abc", "name", "Alice");
+        triple_v1
+            .clone()
+            .insert(&backend, &BlockMetadata::default(), "ROOT", "0")
+            .send()
+            .await
+            .expect("Failed to insert triple");
+
+        let triple_v2 = Triple::new("abc", "name", "NotAlice");
+        triple_v2
+            .clone()
+            .insert(&backend, &BlockMetadata::default(), "ROOT", "1")
+            .send()
+            .await
+            .expect("Failed to insert triple");
+
+        let latest = triple::find_one(&backend, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(latest, Some(triple_v2));
+
+        let found_v1 = triple::find_one(&backend, "name", "abc", "ROOT", Some("0".into()))
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(found_v1, Some(triple_v1));
+    }
+
+    #[tokio::test]
+    async fn test_update_no_versioning() {
+        let backend = MemoryBackend::new();
+
+        triple::insert_many(&backend, &BlockMetadata::default(), "ROOT", "0")
+            .triple(Triple::new("abc", "name", "Alice"))
+            .triple(Triple::new("abc", "name", "NotAlice"))
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        let found = triple::find_one(&backend, "name", "abc", "ROOT", Some("0".into()))
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(found, Some(Triple::new("abc", "name", "NotAlice")));
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let backend = MemoryBackend::new();
+
+        let triple = Triple::new("abc", "name", "Alice");
+        triple
+            .clone()
+            .insert(&backend, &BlockMetadata::default(), "ROOT", "0")
+            .send()
+            .await
+            .expect("Failed to insert triple");
+
+        triple::delete_one(
+            &backend,
+            &BlockMetadata::default(),
+            "name",
+            "abc",
+            "ROOT",
+            "1",
+        )
+        .send()
+        .await
+        .expect("Failed to delete triple");
+
+        let latest = triple::find_one(&backend, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(latest, None);
+
+        let found_v1 = triple::find_one(&backend, "name", "abc", "ROOT", Some("0".into()))
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(found_v1, Some(triple));
+    }
+
+    #[tokio::test]
+    async fn test_delete_records_block() {
+        let backend = MemoryBackend::new();
+        let block = |block_number| BlockMetadata {
+            block_number,
+            ..Default::default()
+        };
+
+        triple::insert_one(
+            &backend,
+            &block(1),
+            "ROOT",
+            "0",
+            Triple::new("abc", "name", "Alice"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert triple");
+
+        triple::delete_one(&backend, &block(2), "name", "abc", "ROOT", "1")
+            .send()
+            .await
+            .expect("Failed to delete triple");
+
+        let edges = backend.edges.read().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].created_at_block, "1");
+        assert_eq!(edges[0].updated_at_block, "2");
+        assert_eq!(edges[0].max_version.as_deref(), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_find_many_spaces() {
+        let backend = MemoryBackend::new();
+
+        triple::insert_many(&backend, &BlockMetadata::default(), "ROOT", "0")
+            .triples(vec![
+                Triple::new("abc", "name", "Alice"),
+                Triple::new("def", "name", "Bob"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        triple::insert_one(
+            &backend,
+            &BlockMetadata::default(),
+            "other_space",
+            "0",
+            Triple::new("abc", "name", "Alicia"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert triple");
+
+        let found = triple::find_many(&backend)
+            .entity_id(prop_filter::value("abc"))
+            .space_id(prop_filter::value_in(vec!["ROOT".to_string()]))
+            .send()
+            .await
+            .expect("Failed to find triples")
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect triples");
+        assert_eq!(found, vec![Triple::new("abc", "name", "Alice")]);
+
+        let found = triple::find_many(&backend)
+            .attribute_id(prop_filter::value("name"))
+            .value(prop_filter::value_not("Bob"))
+            .send()
+            .await
+            .expect("Failed to find triples")
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect triples");
+        assert_eq!(
+            found,
+            vec![
+                Triple::new("abc", "name", "Alice"),
+                Triple::new("abc", "name", "Alicia")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_direction_pluralism_unsupported() {
+        let backend = MemoryBackend::new();
+
+        let result = triple::find_one(&backend, "name", "abc", "ROOT", None)
+            .pluralism(crate::mapping::Pluralism::Direction(
+                crate::mapping::AggregationDirection::Up,
+            ))
+            .send()
+            .await;
+        assert!(matches!(result, Err(DatabaseError::Unsupported(_))));
+    }
+}
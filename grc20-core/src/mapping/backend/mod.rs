@@ -0,0 +1,124 @@
+//! Storage backends of the mapping layer.
+//!
+//! Triple queries (see [`crate::mapping::triple`]) do not talk to Neo4j directly but to a
+//! [`Backend`] through a [`Storage`] handle. Two backends are provided:
+//! - [`Neo4jBackend`]: the production backend, which runs Cypher queries on a
+//!   [`neo4rs::Graph`] (and takes part in [`crate::mapping::transaction`] scopes).
+//! - [`MemoryBackend`]: an in-memory graph with the same versioning and space semantics,
+//!   which allows testing code that reads and writes triples without a Neo4j instance.
+//!
+//! Since a [`Storage`] can be created from a `&neo4rs::Graph`, code that passes a Neo4j
+//! client to the triple queries does not need to change.
+//!
+//! Note: Only the triple queries go through a [`Backend`]. The other queries of the
+//! mapping layer (attribute, entity and relation queries, triple queries with a
+//! [`crate::mapping::Pluralism::Direction`], semantic search and rollbacks) and the
+//! sink's event handlers run Cypher on a [`neo4rs::Graph`] and still require Neo4j.
+use std::{ops::Deref, sync::Arc};
+
+use futures::{future::BoxFuture, stream::BoxStream};
+
+use crate::{block::BlockMetadata, error::DatabaseError};
+
+use super::{
+    query_utils::{PropFilter, VersionFilter},
+    Triple,
+};
+
+pub mod memory;
+pub mod neo4j;
+
+pub use memory::MemoryBackend;
+pub use neo4j::Neo4jBackend;
+
+pub type TripleStream = BoxStream<'static, Result<Triple, DatabaseError>>;
+
+pub trait Backend: Send + Sync {
+    /// Sets the value of the `triples` in the space `space_id` at version `space_version`.
+    /// The previous value of each triple (if any) stops being current at `space_version`,
+    /// unless it was set by the same version in the same block, in which case it is
+    /// overwritten.
+    fn insert_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<Triple>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>>;
+
+    /// Deletes the `(entity_id, attribute_id)` triples from the space `space_id` at
+    /// version `space_version`. Previous versions of the triples are kept.
+    fn delete_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<(String, String)>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>>;
+
+    /// Returns the triples matching `filter`
+    fn find_triples(
+        &self,
+        filter: TripleFilter,
+    ) -> BoxFuture<'_, Result<TripleStream, DatabaseError>>;
+
+    /// Returns the Neo4j client of the backend, if it is backed by Neo4j. Used by the
+    /// queries that are only supported by the Neo4j backend.
+    fn neo4j(&self) -> Option<&neo4rs::Graph> {
+        None
+    }
+}
+
+/// Filter of the triples returned by [`Backend::find_triples`]
+#[derive(Clone, Debug, Default)]
+pub struct TripleFilter {
+    pub entity_id: Option<PropFilter<String>>,
+    pub attribute_id: Option<PropFilter<String>>,
+    pub value: Option<PropFilter<String>>,
+    pub value_type: Option<PropFilter<String>>,
+    pub space_id: Option<PropFilter<String>>,
+    pub version: VersionFilter,
+    pub limit: Option<usize>,
+}
+
+/// Shared handle to the [`Backend`] used by the queries
+#[derive(Clone)]
+pub struct Storage(Arc<dyn Backend>);
+
+impl Storage {
+    pub fn new(backend: impl Backend + 'static) -> Self {
+        Self(Arc::new(backend))
+    }
+}
+
+impl Deref for Storage {
+    type Target = dyn Backend;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl From<&Storage> for Storage {
+    fn from(storage: &Storage) -> Self {
+        storage.clone()
+    }
+}
+
+impl From<neo4rs::Graph> for Storage {
+    fn from(neo4j: neo4rs::Graph) -> Self {
+        Self::new(Neo4jBackend::new(neo4j))
+    }
+}
+
+impl From<&neo4rs::Graph> for Storage {
+    fn from(neo4j: &neo4rs::Graph) -> Self {
+        Self::new(Neo4jBackend::new(neo4j.clone()))
+    }
+}
+
+impl From<&MemoryBackend> for Storage {
+    fn from(backend: &MemoryBackend) -> Self {
+        Self::new(backend.clone())
+    }
+}
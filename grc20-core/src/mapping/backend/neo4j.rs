@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use futures::{future::BoxFuture, FutureExt, StreamExt, TryStreamExt};
+use neo4rs::{BoltMap, BoltType};
+
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{
+        query_utils::query_builder::{MatchQuery, QueryBuilder, Subquery},
+        transaction, Triple,
+    },
+};
+
+use super::{Backend, TripleFilter, TripleStream};
+
+/// [`Backend`] storing the knowledge graph in Neo4j
+#[derive(Clone)]
+pub struct Neo4jBackend {
+    neo4j: neo4rs::Graph,
+}
+
+impl Neo4jBackend {
+    pub fn new(neo4j: neo4rs::Graph) -> Self {
+        Self { neo4j }
+    }
+}
+
+impl Backend for Neo4jBackend {
+    fn insert_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<Triple>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>> {
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+            UNWIND $triples as triple
+            MERGE (e:Entity {{id: triple.entity}})
+            ON CREATE SET e += {{
+                `{CREATED_AT}`: datetime($block_timestamp),
+                `{CREATED_AT_BLOCK}`: $block_number
+            }}
+            SET e += {{
+                `{UPDATED_AT}`: datetime($block_timestamp),
+                `{UPDATED_AT_BLOCK}`: $block_number
+            }}
+            WITH e, triple
+            CALL (e, triple) {{
+                MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:$(triple.attr_labels) {{id: triple.attribute}})
+                WHERE r.max_version IS null AND (r.min_version <> $space_version OR coalesce(r.`{CREATED_AT_BLOCK}`, "") <> $block_number)
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            }}
+            CALL (e, triple) {{
+                MERGE (e) -[r:ATTRIBUTE {{space_id: $space_id, min_version: $space_version, `{CREATED_AT_BLOCK}`: $block_number}}]-> (m:$(triple.attr_labels) {{id: triple.attribute}})
+                ON CREATE SET r += {{
+                    `{CREATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+                SET r.max_version = null
+                SET m += triple.value
+                SET m.embedding = triple.embedding
+            }}
+            "#,
+            CREATED_AT = indexer_ids::CREATED_AT_TIMESTAMP,
+            CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
+            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
+            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+        );
+
+        let query = neo4rs::query(QUERY)
+            .param("space_id", space_id)
+            .param("space_version", space_version)
+            .param("triples", triples)
+            .param("block_number", block.block_number.to_string())
+            .param("block_timestamp", block.timestamp.to_rfc3339());
+
+        async move {
+            transaction::run(&self.neo4j, "triple::insert_many", query).await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn delete_triples<'a>(
+        &'a self,
+        block: &'a BlockMetadata,
+        space_id: &'a str,
+        space_version: &'a str,
+        triples: Vec<(String, String)>,
+    ) -> BoxFuture<'a, Result<(), DatabaseError>> {
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+            UNWIND $triples as triple
+            MATCH (e:Entity {{id: triple.entity_id}}) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: triple.attribute_id}})
+            WHERE r.max_version IS null
+            SET r.max_version = $space_version
+            SET r += {{
+                `{UPDATED_AT}`: datetime($block_timestamp),
+                `{UPDATED_AT_BLOCK}`: $block_number
+            }}
+            SET e += {{
+                `{UPDATED_AT}`: datetime($block_timestamp),
+                `{UPDATED_AT_BLOCK}`: $block_number
+            }}
+            "#,
+            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
+            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+        );
+
+        let query = neo4rs::query(QUERY)
+            .param("space_id", space_id)
+            .param("space_version", space_version)
+            .param(
+                "triples",
+                triples
+                    .into_iter()
+                    .map(|(entity_id, attribute_id)| {
+                        BoltType::Map(BoltMap {
+                            value: HashMap::from([
+                                (
+                                    neo4rs::BoltString {
+                                        value: "entity_id".into(),
+                                    },
+                                    entity_id.into(),
+                                ),
+                                (
+                                    neo4rs::BoltString {
+                                        value: "attribute_id".into(),
+                                    },
+                                    attribute_id.into(),
+                                ),
+                            ]),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .param("block_number", block.block_number.to_string())
+            .param("block_timestamp", block.timestamp.to_rfc3339());
+
+        async move {
+            transaction::run(&self.neo4j, "triple::delete_many", query).await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn find_triples(
+        &self,
+        filter: TripleFilter,
+    ) -> BoxFuture<'_, Result<TripleStream, DatabaseError>> {
+        let query = QueryBuilder::default()
+            .subquery(
+                MatchQuery::new("(e:Entity) -[r:ATTRIBUTE]-> (n:Attribute)")
+                    .where_opt(
+                        filter
+                            .entity_id
+                            .as_ref()
+                            .map(|s| s.subquery("e", "id", None)),
+                    )
+                    .where_opt(
+                        filter
+                            .attribute_id
+                            .as_ref()
+                            .map(|s| s.subquery("n", "id", None)),
+                    )
+                    .where_opt(
+                        filter
+                            .value
+                            .as_ref()
+                            .map(|s| s.subquery("n", "value", None)),
+                    )
+                    .where_opt(
+                        filter
+                            .value_type
+                            .as_ref()
+                            .map(|s| s.subquery("n", "value_type", None)),
+                    )
+                    .where_opt(
+                        filter
+                            .space_id
+                            .as_ref()
+                            .map(|s| s.subquery("r", "space_id", None)),
+                    )
+                    .r#where(filter.version.subquery("r")),
+            )
+            .subquery("RETURN n{.*, entity: e.id}");
+        let query = match filter.limit {
+            Some(limit) => query.limit(limit),
+            None => query,
+        };
+
+        async move {
+            Ok(
+                transaction::execute(&self.neo4j, "triple::find_many", query.build())
+                    .await?
+                    .into_stream_as::<Triple>()
+                    .map_err(DatabaseError::from)
+                    .boxed(),
+            )
+        }
+        .boxed()
+    }
+
+    fn neo4j(&self) -> Option<&neo4rs::Graph> {
+        Some(&self.neo4j)
+    }
+}
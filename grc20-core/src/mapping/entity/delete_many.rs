@@ -1 +1,99 @@
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{transaction, Query},
+};
 
+pub struct DeleteManyQuery {
+    neo4j: neo4rs::Graph,
+    block: BlockMetadata,
+    space_id: String,
+    space_version: String,
+    entities: Vec<String>,
+}
+
+impl DeleteManyQuery {
+    pub fn new(
+        neo4j: &neo4rs::Graph,
+        block: &BlockMetadata,
+        space_id: String,
+        space_version: String,
+    ) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            block: block.clone(),
+            space_id,
+            space_version,
+            entities: vec![],
+        }
+    }
+
+    pub fn entity(mut self, entity_id: impl Into<String>) -> Self {
+        self.entities.push(entity_id.into());
+        self
+    }
+
+    pub fn entity_mut(&mut self, entity_id: impl Into<String>) {
+        self.entities.push(entity_id.into());
+    }
+
+    pub fn entities(mut self, entity_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.entities.extend(entity_ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn entities_mut(&mut self, entity_ids: impl IntoIterator<Item = impl Into<String>>) {
+        self.entities.extend(entity_ids.into_iter().map(Into::into));
+    }
+}
+
+impl Query<()> for DeleteManyQuery {
+    async fn send(self) -> Result<(), DatabaseError> {
+        if self.entities.is_empty() {
+            return Ok(());
+        }
+
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+            UNWIND $entity_ids AS entity_id
+            MATCH (e:Entity {{id: entity_id}})
+            CALL (e) {{
+                MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute)
+                WHERE r.max_version IS null
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            }}
+            CALL (e) {{
+                MATCH (e) -[r:RELATION {{space_id: $space_id}}]- ()
+                WHERE r.max_version IS null
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            }}
+            SET e += {{
+                `{UPDATED_AT}`: datetime($block_timestamp),
+                `{UPDATED_AT_BLOCK}`: $block_number
+            }}
+            "#,
+            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
+            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+        );
+
+        let query = neo4rs::query(QUERY)
+            .param("entity_ids", self.entities)
+            .param("space_id", self.space_id)
+            .param("space_version", self.space_version)
+            .param("block_timestamp", self.block.timestamp.to_rfc3339())
+            .param("block_number", self.block.block_number.to_string());
+
+        transaction::run(&self.neo4j, "entity::delete_many", query).await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::Query};
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{transaction, Query},
+};
 
 pub struct DeleteOneQuery {
     neo4j: neo4rs::Graph,
@@ -30,8 +35,25 @@ impl Query<()> for DeleteOneQuery {
     async fn send(self) -> Result<(), DatabaseError> {
         const QUERY: &str = const_format::formatcp!(
             r#"
-            MATCH (e:Entity {{id: $entity_id}}) -[r:ATTRIBUTE {{space_id: $space_id, max_version: null}}]-> (:Attribute)
-            SET r.max_version = $space_version
+            MATCH (e:Entity {{id: $entity_id}})
+            CALL (e) {{
+                MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute)
+                WHERE r.max_version IS null
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            }}
+            CALL (e) {{
+                MATCH (e) -[r:RELATION {{space_id: $space_id}}]- ()
+                WHERE r.max_version IS null
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            }}
             SET e += {{
                 `{UPDATED_AT}`: datetime($block_timestamp),
                 `{UPDATED_AT_BLOCK}`: $block_number
@@ -48,7 +70,7 @@ impl Query<()> for DeleteOneQuery {
             .param("block_timestamp", self.block.timestamp.to_rfc3339())
             .param("block_number", self.block.block_number.to_string());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "entity::delete_one", query).await?;
 
         Ok(())
     }
@@ -9,7 +9,8 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeFilter, AttributeNode, EntityFilter, FromAttributes, PropFilter, QueryStream,
+        transaction, AttributeFilter, AttributeNode, EntityFilter, FromAttributes, PropFilter,
+        QueryStream,
     },
 };
 
@@ -65,6 +66,16 @@ impl<T> FindManyQuery<T> {
         self.filter.attributes.extend(attributes);
     }
 
+    /// Exclude entities matching the `attribute` filter.
+    pub fn exclude_attribute(mut self, attribute: AttributeFilter) -> Self {
+        self.filter.excluded_attributes.push(attribute);
+        self
+    }
+
+    pub fn exclude_attribute_mut(&mut self, attribute: AttributeFilter) {
+        self.filter.excluded_attributes.push(attribute);
+    }
+
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
@@ -136,12 +147,13 @@ impl QueryStream<EntityNode> for FindManyQuery<EntityNode> {
             e: EntityNode,
         }
 
-        Ok(neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RowResult>()
-            .map_err(DatabaseError::from)
-            .and_then(|row| async move { Ok(row.e) }))
+        Ok(
+            transaction::execute(&neo4j, "entity::find_many", query.build())
+                .await?
+                .into_stream_as::<RowResult>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move { Ok(row.e) }),
+        )
     }
 }
 
@@ -178,9 +190,7 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = transaction::execute(&self.neo4j, "entity::find_many", query.build())
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
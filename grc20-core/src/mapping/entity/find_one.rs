@@ -7,7 +7,7 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeNode, FromAttributes, Query,
+        transaction, AttributeNode, FromAttributes, Query,
     },
 };
 
@@ -62,8 +62,7 @@ impl Query<Option<EntityNode>> for FindOneQuery<EntityNode> {
             e: EntityNode,
         }
 
-        self.neo4j
-            .execute(query)
+        transaction::execute(&self.neo4j, "entity::find_one", query)
             .await?
             .next()
             .await?
@@ -110,8 +109,7 @@ impl<T: FromAttributes> Query<Option<Entity<T>>> for FindOneQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "entity::find_one", query.build())
             .await?
             .next()
             .await?
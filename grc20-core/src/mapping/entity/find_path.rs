@@ -9,7 +9,7 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeFilter, PropFilter, Query,
+        transaction, AttributeFilter, PropFilter, Query,
     },
     system_ids::SCHEMA_TYPE,
 };
@@ -132,7 +132,8 @@ impl Query<Vec<Relation>> for FindPathQuery {
             );
         }
 
-        let mut result = self.neo4j.execute(query.build()).await?;
+        let mut result =
+            transaction::execute(&self.neo4j, "entity::find_path", query.build()).await?;
         let mut all_relationship_data = Vec::new();
 
         // Process each row
@@ -9,6 +9,7 @@ pub mod models;
 pub mod semantic_search;
 pub mod utils;
 
+pub use delete_many::DeleteManyQuery;
 pub use delete_one::DeleteOneQuery;
 pub use find_many::FindManyQuery;
 pub use find_one::FindOneQuery;
@@ -36,6 +37,17 @@ pub fn delete_one(
     )
 }
 
+/// Creates a query to delete multiple entities in a space version. Deleting an entity
+/// closes all of its attributes and relations (incoming and outgoing) in the space.
+pub fn delete_many(
+    neo4j: &neo4rs::Graph,
+    block: &BlockMetadata,
+    space_id: impl Into<String>,
+    space_version: impl Into<String>,
+) -> DeleteManyQuery {
+    DeleteManyQuery::new(neo4j, block, space_id.into(), space_version.into())
+}
+
 /// Creates a query to find a single entity by its ID if it exists. Supports optional
 /// filtering by space ID and version.
 /// ```rust
@@ -4,8 +4,8 @@ use crate::{
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
-        query_utils::VersionFilter, AttributeNode, FromAttributes, PropFilter, QueryBuilder,
-        QueryStream, Subquery,
+        query_utils::VersionFilter, transaction, AttributeNode, FromAttributes, PropFilter,
+        QueryBuilder, QueryStream, Subquery,
     },
 };
 
@@ -138,18 +138,18 @@ impl QueryStream<SemanticSearchResult<EntityNode>> for SemanticSearchQuery<Entit
             score: f64,
         }
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RowResult>()
-            .map_err(DatabaseError::from)
-            .and_then(|row| async move {
-                Ok(SemanticSearchResult {
-                    entity: row.e,
-                    score: row.score,
-                })
-            }))
+        Ok(
+            transaction::execute(&self.neo4j, "entity::semantic_search", query.build())
+                .await?
+                .into_stream_as::<RowResult>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move {
+                    Ok(SemanticSearchResult {
+                        entity: row.e,
+                        score: row.score,
+                    })
+                }),
+        )
     }
 }
 
@@ -192,9 +192,7 @@ impl<T: FromAttributes> QueryStream<SemanticSearchResult<Entity<T>>>
             score: f64,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = transaction::execute(&self.neo4j, "entity::semantic_search", query.build())
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
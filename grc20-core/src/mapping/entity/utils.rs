@@ -16,6 +16,8 @@ use crate::{
 pub struct EntityFilter {
     pub(crate) id: Option<PropFilter<String>>,
     pub(crate) attributes: Vec<AttributeFilter>,
+    /// Entities matching any of these filters are excluded.
+    pub(crate) excluded_attributes: Vec<AttributeFilter>,
     pub(crate) relations: Option<EntityRelationFilter>,
     /// Used to check if the entity exists in the space (i.e.: the entity
     /// has at least one attribute in the space).
@@ -46,6 +48,16 @@ impl EntityFilter {
         self.attributes.extend(attributes);
     }
 
+    /// Exclude entities matching the `attribute` filter.
+    pub fn exclude_attribute(mut self, attribute: AttributeFilter) -> Self {
+        self.excluded_attributes.push(attribute);
+        self
+    }
+
+    pub fn exclude_attribute_mut(&mut self, attribute: AttributeFilter) {
+        self.excluded_attributes.push(attribute);
+    }
+
     pub fn relations(mut self, relations: impl Into<EntityRelationFilter>) -> Self {
         self.relations = Some(relations.into());
         self
@@ -68,6 +80,13 @@ impl EntityFilter {
                     .map(|attribute| attribute.subquery(&node_var))
                     .collect(),
             )
+            // Apply attribute exclusions
+            .subqueries(
+                self.excluded_attributes
+                    .iter()
+                    .map(|attribute| attribute.exclude_subquery(&node_var))
+                    .collect(),
+            )
             // Apply the space_id filter
             .subquery_opt(self.space_id.as_ref().map(|space_id| {
                 MatchQuery::new(format!("({node_var}) -[a:ATTRIBUTE]- (:Attribute)"))
@@ -5,7 +5,7 @@ use crate::{error::DatabaseError, indexer_ids};
 
 use super::{
     query_utils::query_builder::{MatchQuery, QueryBuilder, Subquery},
-    PropFilter, Query,
+    transaction, PropFilter, Query,
 };
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -78,8 +78,7 @@ impl Query<Vec<EntityVersion>> for FindManyQuery {
             );
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "entity_version::find_many", query.build())
             .await?
             .into_stream_as::<EntityVersion>()
             .map_err(DatabaseError::from)
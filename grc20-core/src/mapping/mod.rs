@@ -1,18 +1,22 @@
 pub mod aggregation;
 pub mod attribute_node;
 pub mod attributes;
+pub mod backend;
 pub mod entity;
 pub mod entity_version;
 pub mod error;
 pub mod pluralism;
 pub mod query_utils;
 pub mod relation;
+pub mod rollback;
+pub mod transaction;
 pub mod triple;
 pub mod value;
 
 pub use aggregation::AggregationDirection;
 pub use attribute_node::AttributeNode;
 pub use attributes::{Attributes, FromAttributes, IntoAttributes};
+pub use backend::{Backend, MemoryBackend, Storage};
 pub use entity::{Entity, EntityFilter, EntityNode, EntityNodeRef, EntityRelationFilter};
 pub use entity_version::EntityVersion;
 pub use error::TriplesConversionError;
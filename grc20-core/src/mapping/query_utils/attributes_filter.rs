@@ -1,4 +1,8 @@
-use super::{prop_filter::PropFilter, query_builder::MatchQuery, version_filter::VersionFilter};
+use super::{
+    prop_filter::PropFilter,
+    query_builder::{MatchQuery, QueryBuilder, Subquery},
+    version_filter::VersionFilter,
+};
 
 /// Struct representing an attribute filter subquery for an entity's attributes.
 ///
@@ -121,4 +125,27 @@ impl AttributeFilter {
             )
             .params(attr_id_var, self.attribute.clone())
     }
+
+    /// Compiles the attribute filter into a Neo4j subquery that will filter out the nodes
+    /// identified by `node_var` for which the attribute filter matches.
+    ///
+    /// The subquery will have the following form:
+    /// ```cypher
+    /// WITH * WHERE NOT EXISTS {
+    /// MATCH ({node_var}) -[r_{node_var}_attribute:ATTRIBUTE]-> ({node_var}_attribute:Attribute {id: $attribute})
+    /// WHERE {VERSION_FILTER}
+    /// ...
+    /// }
+    /// ```
+    pub fn exclude_subquery(&self, node_var: &str) -> QueryBuilder {
+        let subquery = self.subquery(node_var);
+
+        QueryBuilder {
+            statements: vec![format!(
+                "WITH * WHERE NOT EXISTS {{\n{}\n}}",
+                subquery.compile()
+            )],
+            params: Subquery::params(&subquery),
+        }
+    }
 }
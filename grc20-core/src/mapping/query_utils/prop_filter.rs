@@ -259,6 +259,24 @@ impl<T: Clone + Into<BoltType>> PropFilter<T> {
     }
 }
 
+impl<T: PartialOrd> PropFilter<T> {
+    /// Returns whether `value` passes the filter. Used by backends that filter values
+    /// in memory instead of compiling the filter to a query (see [`PropFilter::subquery`]).
+    pub fn matches(&self, value: &T) -> bool {
+        self.value.as_ref().is_none_or(|v| value == v)
+            && self.value_gt.as_ref().is_none_or(|v| value > v)
+            && self.value_gte.as_ref().is_none_or(|v| value >= v)
+            && self.value_lt.as_ref().is_none_or(|v| value < v)
+            && self.value_lte.as_ref().is_none_or(|v| value <= v)
+            && self.value_not.as_ref().is_none_or(|v| value != v)
+            && self.value_in.as_ref().is_none_or(|vs| vs.contains(value))
+            && self
+                .value_not_in
+                .as_ref()
+                .is_none_or(|vs| !vs.contains(value))
+    }
+}
+
 impl<T: Into<Value>> PropFilter<T> {
     pub fn as_string(self) -> PropFilter<String> {
         PropFilter {
@@ -33,4 +33,16 @@ impl VersionFilter {
             WhereClause::new(format!("{var}.max_version IS NULL"))
         }
     }
+
+    /// Returns whether an edge valid from `min_version` until `max_version` (excluded, or
+    /// still current if `None`) passes the filter. In-memory equivalent of [`VersionFilter::subquery`].
+    pub fn matches(&self, min_version: &str, max_version: Option<&str>) -> bool {
+        match &self.version {
+            Some(version) => {
+                min_version <= version.as_str()
+                    && max_version.is_none_or(|max_version| max_version > version.as_str())
+            }
+            None => max_version.is_none(),
+        }
+    }
 }
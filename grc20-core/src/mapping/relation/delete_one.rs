@@ -1,4 +1,9 @@
-use crate::{block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::Query};
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{transaction, Query},
+};
 
 pub struct DeleteOneQuery {
     neo4j: neo4rs::Graph,
@@ -51,6 +56,6 @@ impl Query<()> for DeleteOneQuery {
             .param("block_timestamp", self.block.timestamp.to_rfc3339())
             .param("block_number", self.block.block_number.to_string());
 
-        Ok(self.neo4j.run(query).await?)
+        Ok(transaction::run(&self.neo4j, "relation::delete_one", query).await?)
     }
 }
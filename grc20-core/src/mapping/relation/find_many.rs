@@ -7,7 +7,8 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeNode, EntityNode, EntityNodeRef, FromAttributes, PropFilter, QueryStream,
+        transaction, AttributeNode, EntityNode, EntityNodeRef, FromAttributes, PropFilter,
+        QueryStream,
     },
 };
 
@@ -165,11 +166,12 @@ impl QueryStream<RelationEdge<EntityNodeRef>> for FindManyQuery<RelationEdge<Ent
             );
         };
 
-        Ok(neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RelationEdge<EntityNodeRef>>()
-            .map_err(DatabaseError::from))
+        Ok(
+            transaction::execute(&neo4j, "relation::find_many", query.build())
+                .await?
+                .into_stream_as::<RelationEdge<EntityNodeRef>>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
@@ -190,11 +192,12 @@ impl QueryStream<RelationEdge<EntityNode>> for FindManyQuery<RelationEdge<Entity
             );
         };
 
-        Ok(neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RelationEdge<EntityNode>>()
-            .map_err(DatabaseError::from))
+        Ok(
+            transaction::execute(&neo4j, "relation::find_many", query.build())
+                .await?
+                .into_stream_as::<RelationEdge<EntityNode>>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
@@ -230,9 +233,7 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNodeRef>>
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = transaction::execute(&self.neo4j, "relation::find_many", query.build())
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -283,9 +284,7 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNode>>
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = transaction::execute(&self.neo4j, "relation::find_many", query.build())
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -8,7 +8,7 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeNode, Entity, EntityNode, FromAttributes, PropFilter, QueryStream,
+        transaction, AttributeNode, Entity, EntityNode, FromAttributes, PropFilter, QueryStream,
     },
 };
 
@@ -128,12 +128,12 @@ impl QueryStream<EntityNode> for FindManyToQuery<EntityNode> {
             println!("relation_node::FindManyToQuery:\n{}", query.compile());
         };
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<EntityNode>()
-            .map_err(DatabaseError::from))
+        Ok(
+            transaction::execute(&self.neo4j, "relation::find_many_to", query.build())
+                .await?
+                .into_stream_as::<EntityNode>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
@@ -166,9 +166,7 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyToQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = transaction::execute(&self.neo4j, "relation::find_many_to", query.build())
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -5,7 +5,7 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeNode, EntityNode, EntityNodeRef, FromAttributes, Query,
+        transaction, AttributeNode, EntityNode, EntityNodeRef, FromAttributes, Query,
     },
 };
 
@@ -61,8 +61,7 @@ impl Query<Option<RelationEdge<EntityNodeRef>>> for FindOneQuery<RelationEdge<En
             .subquery("ORDER BY r.index")
             .r#return("r{.*, from: from.id, to: to.id} as r");
 
-        neo4j
-            .execute(query.build())
+        transaction::execute(&neo4j, "relation::find_one", query.build())
             .await?
             .next()
             .await?
@@ -86,8 +85,7 @@ impl Query<Option<RelationEdge<EntityNode>>> for FindOneQuery<RelationEdge<Entit
             .subquery("ORDER BY r.index")
             .r#return("r{.*, from: from, to: to} as r");
 
-        neo4j
-            .execute(query.build())
+        transaction::execute(&neo4j, "relation::find_one", query.build())
             .await?
             .next()
             .await?
@@ -139,8 +137,7 @@ impl<T: FromAttributes> Query<Option<Relation<T, EntityNodeRef>>>
             attributes: Vec<AttributeNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "relation::find_one", query.build())
             .await?
             .next()
             .await?
@@ -198,8 +195,7 @@ impl<T: FromAttributes> Query<Option<Relation<T, EntityNode>>>
             attributes: Vec<AttributeNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "relation::find_one", query.build())
             .await?
             .next()
             .await?
@@ -7,7 +7,7 @@ use crate::{
             query_builder::{MatchQuery, QueryBuilder, Subquery},
             VersionFilter,
         },
-        AttributeNode, Entity, EntityNode, FromAttributes, Query,
+        transaction, AttributeNode, Entity, EntityNode, FromAttributes, Query,
     },
     relation::utils::MatchOneRelation,
 };
@@ -55,8 +55,7 @@ impl Query<Option<EntityNode>> for FindOneToQuery<EntityNode> {
             to: EntityNode,
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "relation::find_one_to", query.build())
             .await?
             .next()
             .await?
@@ -97,8 +96,7 @@ impl<T: FromAttributes> Query<Option<Entity<T>>> for FindOneToQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(&self.neo4j, "relation::find_one_to", query.build())
             .await?
             .next()
             .await?
@@ -2,7 +2,7 @@ use crate::{
     block::BlockMetadata,
     error::DatabaseError,
     indexer_ids,
-    mapping::{EntityNodeRef, Query},
+    mapping::{transaction, EntityNodeRef, Query},
 };
 
 use super::RelationEdge;
@@ -92,7 +92,7 @@ impl Query<()> for InsertManyQuery<RelationEdge<EntityNodeRef>> {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "relation::insert_many", query).await?;
 
         Ok(())
     }
@@ -2,7 +2,7 @@ use crate::{
     block::BlockMetadata,
     error::DatabaseError,
     indexer_ids,
-    mapping::{EntityNodeRef, IntoAttributes, Query},
+    mapping::{transaction, EntityNodeRef, IntoAttributes, Query},
 };
 
 use super::{Relation, RelationEdge};
@@ -66,7 +66,7 @@ impl Query<()> for InsertOneQuery<RelationEdge<EntityNodeRef>> {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "relation::insert_one", query).await?;
 
         Ok(())
     }
@@ -93,15 +93,33 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<Relation<T, EntityNodeRef>>
                 `{UPDATED_AT_BLOCK}`: $block_number
             }}
             MERGE (e:Entity {{id: $relation.id}})
+            ON CREATE SET e += {{
+                `{CREATED_AT}`: datetime($block_timestamp),
+                `{CREATED_AT_BLOCK}`: $block_number
+            }}
+            SET e += {{
+                `{UPDATED_AT}`: datetime($block_timestamp),
+                `{UPDATED_AT_BLOCK}`: $block_number
+            }}
             WITH e
             UNWIND $attributes AS attribute
             CALL (e, attribute) {{
                 MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: attribute.id}})
-                WHERE r.max_version IS null AND r.min_version <> $space_version
+                WHERE r.max_version IS null AND (r.min_version <> $space_version OR coalesce(r.`{CREATED_AT_BLOCK}`, "") <> $block_number)
                 SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
             }}
             CALL (e, attribute) {{
-                MERGE (e) -[:ATTRIBUTE {{space_id: $space_id, min_version: $space_version}}]-> (m:Attribute {{id: attribute.id}})
+                MERGE (e) -[r:ATTRIBUTE {{space_id: $space_id, min_version: $space_version, `{CREATED_AT_BLOCK}`: $block_number}}]-> (m:Attribute {{id: attribute.id}})
+                ON CREATE SET r += {{
+                    `{CREATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+                SET r.max_version = null
                 SET m += attribute
             }}
             "#,
@@ -119,7 +137,7 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<Relation<T, EntityNodeRef>>
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        transaction::run(&self.neo4j, "relation::insert_one", query).await?;
 
         Ok(())
     }
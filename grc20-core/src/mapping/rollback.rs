@@ -0,0 +1,349 @@
+//! Chain reorganization support.
+//!
+//! Every write made by the mapping layer stamps the block number on the nodes and
+//! edges it touches (`CREATED_AT_BLOCK` on creation, `UPDATED_AT_BLOCK` on every
+//! update, including when an ATTRIBUTE or RELATION edge is closed by setting its
+//! `max_version`). Values are never overwritten across blocks: a new ATTRIBUTE edge
+//! is created instead. This makes it possible to restore the graph to its state at
+//! the end of any given block.
+use crate::{error::DatabaseError, indexer_ids};
+
+use super::{transaction, Query};
+
+/// Rolls back all writes made after `block_number` (exclusive), i.e.: after
+/// the query completes, the graph is in the same state as it was right after
+/// `block_number` was processed.
+pub fn rollback(neo4j: &neo4rs::Graph, block_number: u64) -> RollbackQuery {
+    RollbackQuery::new(neo4j, block_number)
+}
+
+/// Maximum number of blocks rolled back using the block number indexes (see
+/// [`RollbackQuery::head`]). Longer ranges scan the whole graph.
+pub const MAX_INDEXED_ROLLBACK_BLOCKS: u64 = 10_000;
+
+pub struct RollbackQuery {
+    neo4j: neo4rs::Graph,
+    block_number: u64,
+    head: Option<u64>,
+    space_ids: Option<Vec<String>>,
+}
+
+impl RollbackQuery {
+    pub fn new(neo4j: &neo4rs::Graph, block_number: u64) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            block_number,
+            head: None,
+            space_ids: None,
+        }
+    }
+
+    /// Sets the last block written to the graph. Block numbers are stored as strings,
+    /// so knowing the rolled back blocks lets the queries look them up in the block
+    /// number indexes instead of scanning the whole graph (e.g.: when handling block
+    /// undo signals, which only roll back a few blocks).
+    pub fn head(mut self, head: u64) -> Self {
+        self.head = Some(head);
+        self
+    }
+
+    /// Only roll back the attributes and relations of the given spaces. Entities
+    /// created after the block are only deleted if they have no attributes or
+    /// relations left in other spaces.
+    pub fn spaces(mut self, space_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.space_ids = Some(space_ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Block numbers (as stored) rolled back by the query, if they can be listed
+    fn rolled_back_blocks(&self) -> Option<Vec<String>> {
+        self.head
+            .filter(|head| head.saturating_sub(self.block_number) <= MAX_INDEXED_ROLLBACK_BLOCKS)
+            .map(|head| {
+                (self.block_number + 1..=head)
+                    .map(|block| block.to_string())
+                    .collect()
+            })
+    }
+}
+
+/// Condition matching the nodes or edges `var` whose block number `property` is after
+/// the rolled back block. With a list of blocks, the condition can use the block
+/// number indexes.
+fn after_block(var: &str, property: &str, indexed: bool) -> String {
+    if indexed {
+        format!("{var}.`{property}` IN $blocks")
+    } else {
+        format!("toInteger({var}.`{property}`) > $block_number")
+    }
+}
+
+fn rollback_queries(indexed: bool) -> [String; 6] {
+    let created_at = indexer_ids::CREATED_AT_TIMESTAMP;
+    let created_at_block = indexer_ids::CREATED_AT_BLOCK;
+    let updated_at = indexer_ids::UPDATED_AT_TIMESTAMP;
+    let updated_at_block = indexer_ids::UPDATED_AT_BLOCK;
+
+    [
+        // Delete attributes created after the block
+        format!(
+            r#"
+            MATCH (:Entity) -[r:ATTRIBUTE]-> (a)
+            WHERE {created}
+                AND ($space_ids IS NULL OR r.space_id IN $space_ids)
+            DETACH DELETE a
+            "#,
+            created = after_block("r", created_at_block, indexed),
+        ),
+        // Reopen attributes closed after the block
+        format!(
+            r#"
+            MATCH (:Entity) -[r:ATTRIBUTE]-> ()
+            WHERE {updated} AND r.max_version IS NOT NULL
+                AND ($space_ids IS NULL OR r.space_id IN $space_ids)
+            SET r.max_version = null
+            SET r += {{
+                `{updated_at}`: r.`{created_at}`,
+                `{updated_at_block}`: r.`{created_at_block}`
+            }}
+            "#,
+            updated = after_block("r", updated_at_block, indexed),
+        ),
+        // Delete relations created after the block
+        format!(
+            r#"
+            MATCH () -[r:RELATION]-> ()
+            WHERE {created}
+                AND ($space_ids IS NULL OR r.space_id IN $space_ids)
+            DELETE r
+            "#,
+            created = after_block("r", created_at_block, indexed),
+        ),
+        // Reopen relations closed after the block
+        format!(
+            r#"
+            MATCH () -[r:RELATION]-> ()
+            WHERE {updated} AND r.max_version IS NOT NULL
+                AND ($space_ids IS NULL OR r.space_id IN $space_ids)
+            SET r.max_version = null
+            SET r += {{
+                `{updated_at}`: r.`{created_at}`,
+                `{updated_at_block}`: r.`{created_at_block}`
+            }}
+            "#,
+            updated = after_block("r", updated_at_block, indexed),
+        ),
+        // Delete entities created after the block (when rolling back specific spaces,
+        // only those that are not used by other spaces)
+        format!(
+            r#"
+            MATCH (e:Entity)
+            WHERE {created}
+                AND ($space_ids IS NULL OR NOT (e) -[:ATTRIBUTE|RELATION]- ())
+            DETACH DELETE e
+            "#,
+            created = after_block("e", created_at_block, indexed),
+        ),
+        // Restore the update timestamps of entities updated after the block from
+        // their most recently written attribute.
+        format!(
+            r#"
+            MATCH (e:Entity)
+            WHERE {updated}
+            OPTIONAL MATCH (e) -[r:ATTRIBUTE]-> ()
+            WITH e, r
+            ORDER BY toInteger(r.`{updated_at_block}`) DESC
+            WITH e, head(collect(r)) AS latest
+            SET e += {{
+                `{updated_at}`: coalesce(latest.`{updated_at}`, e.`{created_at}`),
+                `{updated_at_block}`: coalesce(latest.`{updated_at_block}`, e.`{created_at_block}`)
+            }}
+            "#,
+            updated = after_block("e", updated_at_block, indexed),
+        ),
+    ]
+}
+
+impl Query<()> for RollbackQuery {
+    async fn send(self) -> Result<(), DatabaseError> {
+        let blocks = self.rolled_back_blocks();
+        let queries = rollback_queries(blocks.is_some());
+
+        transaction::transaction(&self.neo4j, async {
+            for query in &queries {
+                transaction::run(
+                    &self.neo4j,
+                    "rollback",
+                    neo4rs::query(query)
+                        .param("block_number", self.block_number as i64)
+                        .param("blocks", blocks.clone().unwrap_or_default())
+                        .param("space_ids", self.space_ids.clone()),
+                )
+                .await?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::BlockMetadata, mapping::triple};
+
+    #[tokio::test]
+    async fn test_rollback() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let block = |block_number| BlockMetadata {
+            block_number,
+            ..Default::default()
+        };
+
+        triple::insert_one(
+            &neo4j,
+            &block(1),
+            "ROOT",
+            "0",
+            triple::Triple::new("abc", "name", "Alice"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert triple");
+
+        triple::insert_one(
+            &neo4j,
+            &block(2),
+            "ROOT",
+            "0",
+            triple::Triple::new("abc", "name", "Bob"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert triple");
+
+        triple::insert_one(
+            &neo4j,
+            &block(2),
+            "ROOT",
+            "0",
+            triple::Triple::new("def", "name", "Charlie"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert triple");
+
+        rollback(&neo4j, 1)
+            .send()
+            .await
+            .expect("Failed to rollback");
+
+        let found_triple = triple::find_one(&neo4j, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+
+        assert_eq!(
+            found_triple,
+            Some(triple::Triple::new("abc", "name", "Alice"))
+        );
+
+        let found_triple = triple::find_one(&neo4j, "name", "def", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+
+        assert_eq!(found_triple, None);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_head() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let block = |block_number| BlockMetadata {
+            block_number,
+            ..Default::default()
+        };
+
+        // Block numbers with different lengths, which do not compare as strings
+        for (block_number, name) in [(9, "Alice"), (10, "Bob"), (11, "Charlie")] {
+            triple::insert_one(
+                &neo4j,
+                &block(block_number),
+                "ROOT",
+                "0",
+                triple::Triple::new("abc", "name", name),
+            )
+            .send()
+            .await
+            .expect("Failed to insert triple");
+        }
+
+        rollback(&neo4j, 9)
+            .head(11)
+            .send()
+            .await
+            .expect("Failed to rollback");
+
+        let found_triple = triple::find_one(&neo4j, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+
+        assert_eq!(
+            found_triple,
+            Some(triple::Triple::new("abc", "name", "Alice"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_spaces() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let block = |block_number| BlockMetadata {
+            block_number,
+            ..Default::default()
+        };
+
+        for space_id in ["ROOT", "OTHER"] {
+            triple::insert_one(
+                &neo4j,
+                &block(2),
+                space_id,
+                "0",
+                triple::Triple::new("abc", "name", "Alice"),
+            )
+            .send()
+            .await
+            .expect("Failed to insert triple");
+        }
+
+        rollback(&neo4j, 1)
+            .spaces(["ROOT"])
+            .send()
+            .await
+            .expect("Failed to rollback");
+
+        let found_triple = triple::find_one(&neo4j, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+
+        assert_eq!(found_triple, None);
+
+        // Writes of other spaces are kept
+        let found_triple = triple::find_one(&neo4j, "name", "abc", "OTHER", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+
+        assert_eq!(
+            found_triple,
+            Some(triple::Triple::new("abc", "name", "Alice"))
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Transaction support for the mapping layer.
+//!
+//! Queries hold a [`neo4rs::Graph`] and are by default executed in auto-commit mode.
+//! When a query is sent from within [`transaction`], it is instead executed as part
+//! of the transaction opened by the scope, which is committed once the scope completes
+//! successfully (or rolled back if it fails). This allows running any combination of
+//! queries atomically without having to thread a [`neo4rs::Txn`] through every query.
+//!
+//! Note: The transaction is bound to the current task. Queries sent from other tasks
+//! (e.g.: spawned with `tokio::spawn`) are not part of the transaction.
+use std::{future::Future, sync::Arc};
+
+use futures::{
+    stream::{self, BoxStream},
+    Stream, StreamExt, TryStreamExt,
+};
+use prometheus::HistogramTimer;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+use crate::{error::DatabaseError, metrics};
+
+tokio::task_local! {
+    static CURRENT_TXN: Arc<Mutex<neo4rs::Txn>>;
+}
+
+/// Runs `f` in a Neo4j transaction. All queries sent by `f` are committed together
+/// if `f` succeeds and rolled back if it fails. If a transaction is already active,
+/// `f` simply becomes part of it.
+pub async fn transaction<F, T, E>(neo4j: &neo4rs::Graph, f: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<DatabaseError>,
+{
+    if in_transaction() {
+        return f.await;
+    }
+
+    let txn = Arc::new(Mutex::new(
+        neo4j.start_txn().await.map_err(DatabaseError::from)?,
+    ));
+
+    let result = CURRENT_TXN.scope(txn.clone(), f).await;
+
+    let txn = Arc::into_inner(txn)
+        .ok_or_else(|| {
+            DatabaseError::TransactionError(
+                "Transaction still in use after the end of its scope".to_string(),
+            )
+        })?
+        .into_inner();
+
+    match result {
+        Ok(value) => {
+            txn.commit().await.map_err(DatabaseError::from)?;
+            Ok(value)
+        }
+        Err(err) => {
+            if let Err(rollback_err) = txn.rollback().await {
+                tracing::error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Returns whether the current task is running within a [`transaction`] scope.
+pub fn in_transaction() -> bool {
+    CURRENT_TXN.try_with(|_| ()).is_ok()
+}
+
+/// Runs `query` in the current transaction if there is one, otherwise runs it on `neo4j`.
+/// `query_name` identifies the type of query in the metrics (e.g.: `triple::insert_many`).
+pub async fn run(
+    neo4j: &neo4rs::Graph,
+    query_name: &str,
+    query: neo4rs::Query,
+) -> Result<(), neo4rs::Error> {
+    match CURRENT_TXN.try_with(|txn| txn.clone()) {
+        Ok(txn) => {
+            let mut txn = txn.lock().await;
+            // Time the query only, not the wait for the transaction
+            let _timer = query_timer(query_name);
+            txn.run(query).await
+        }
+        Err(_) => {
+            let _timer = query_timer(query_name);
+            neo4j.run(query).await
+        }
+    }
+}
+
+/// Executes `query` in the current transaction if there is one, otherwise executes it on `neo4j`.
+/// `query_name` identifies the type of query in the metrics (e.g.: `triple::find_many`).
+///
+/// Rows of queries executed in a transaction are buffered so that the transaction is
+/// available to other queries while the results are consumed.
+pub async fn execute(
+    neo4j: &neo4rs::Graph,
+    query_name: &str,
+    query: neo4rs::Query,
+) -> Result<RowStream, neo4rs::Error> {
+    match CURRENT_TXN.try_with(|txn| txn.clone()) {
+        Ok(txn) => {
+            let mut txn = txn.lock().await;
+            let _timer = query_timer(query_name);
+            let mut stream = txn.execute(query).await?;
+
+            let mut rows = Vec::new();
+            while let Some(row) = stream.next(&mut *txn).await? {
+                rows.push(Ok(row));
+            }
+
+            Ok(RowStream {
+                rows: stream::iter(rows).boxed(),
+            })
+        }
+        Err(_) => {
+            let _timer = query_timer(query_name);
+            Ok(RowStream {
+                rows: neo4j
+                    .execute(query)
+                    .await?
+                    .into_stream()
+                    .into_stream()
+                    .boxed(),
+            })
+        }
+    }
+}
+
+fn query_timer(query_name: &str) -> HistogramTimer {
+    metrics::NEO4J_QUERY_DURATION
+        .with_label_values(&[query_name])
+        .start_timer()
+}
+
+/// Result rows of a query sent with [`execute`].
+pub struct RowStream {
+    rows: BoxStream<'static, Result<neo4rs::Row, neo4rs::Error>>,
+}
+
+impl RowStream {
+    pub async fn next(&mut self) -> Result<Option<neo4rs::Row>, neo4rs::Error> {
+        self.rows.next().await.transpose()
+    }
+
+    pub fn into_stream_as<T: DeserializeOwned>(
+        self,
+    ) -> impl Stream<Item = Result<T, neo4rs::Error>> {
+        self.rows.map(|row| {
+            row.and_then(|row| row.to::<T>().map_err(neo4rs::Error::DeserializationError))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::BlockMetadata,
+        mapping::{triple, Query},
+    };
+
+    #[tokio::test]
+    async fn test_transaction() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        // Committed transaction: queries see the writes of the transaction
+        transaction(&neo4j, async {
+            triple::insert_one(
+                &neo4j,
+                &BlockMetadata::default(),
+                "ROOT",
+                "0",
+                triple::Triple::new("abc", "name", "Alice"),
+            )
+            .send()
+            .await?;
+
+            let found_triple = triple::find_one(&neo4j, "name", "abc", "ROOT", None)
+                .send()
+                .await?;
+            assert_eq!(
+                found_triple,
+                Some(triple::Triple::new("abc", "name", "Alice"))
+            );
+
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .expect("Failed to run transaction");
+
+        // Failed transaction: writes are rolled back
+        let result = transaction(&neo4j, async {
+            triple::insert_one(
+                &neo4j,
+                &BlockMetadata::default(),
+                "ROOT",
+                "0",
+                triple::Triple::new("def", "name", "Bob"),
+            )
+            .send()
+            .await?;
+
+            Err::<(), _>(DatabaseError::NotFound("test".to_string()))
+        })
+        .await;
+        assert!(result.is_err());
+
+        let found_triple = triple::find_one(&neo4j, "name", "abc", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(
+            found_triple,
+            Some(triple::Triple::new("abc", "name", "Alice"))
+        );
+
+        let found_triple = triple::find_one(&neo4j, "name", "def", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple");
+        assert_eq!(found_triple, None);
+    }
+}
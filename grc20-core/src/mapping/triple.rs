@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
-use futures::{Stream, TryStreamExt};
-use neo4rs::{BoltMap, BoltType};
+use futures::{Stream, StreamExt, TryStreamExt};
+use neo4rs::BoltType;
 use serde::Deserialize;
 
 use crate::{
@@ -11,11 +11,12 @@ use crate::{
 
 use super::{
     aggregation::AggregationDirection,
+    backend::{Storage, TripleFilter},
     query_utils::{
         query_builder::{MatchQuery, QueryBuilder},
         PropFilter, Query, QueryStream, VersionFilter,
     },
-    Pluralism, Value,
+    transaction, Pluralism, Value,
 };
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
@@ -61,17 +62,17 @@ impl Triple {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
     ) -> InsertOneQuery {
-        InsertOneQuery::new(neo4j, block, space_id.into(), space_version.into(), self)
+        InsertOneQuery::new(storage, block, space_id.into(), space_version.into(), self)
     }
 }
 
 pub fn delete_one(
-    neo4j: &neo4rs::Graph,
+    storage: impl Into<Storage>,
     block: &BlockMetadata,
     attribute_id: impl Into<String>,
     entity_id: impl Into<String>,
@@ -79,7 +80,7 @@ pub fn delete_one(
     space_version: impl Into<String>,
 ) -> DeleteOneQuery {
     DeleteOneQuery::new(
-        neo4j,
+        storage,
         block,
         attribute_id.into(),
         entity_id.into(),
@@ -89,42 +90,48 @@ pub fn delete_one(
 }
 
 pub fn delete_many(
-    neo4j: &neo4rs::Graph,
+    storage: impl Into<Storage>,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
 ) -> DeleteManyQuery {
-    DeleteManyQuery::new(neo4j, block, space_id.into(), space_version.into())
+    DeleteManyQuery::new(storage, block, space_id.into(), space_version.into())
 }
 
 pub fn insert_one(
-    neo4j: &neo4rs::Graph,
+    storage: impl Into<Storage>,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
     triple: Triple,
 ) -> InsertOneQuery {
-    InsertOneQuery::new(neo4j, block, space_id.into(), space_version.into(), triple)
+    InsertOneQuery::new(
+        storage,
+        block,
+        space_id.into(),
+        space_version.into(),
+        triple,
+    )
 }
 
 pub fn insert_many(
-    neo4j: &neo4rs::Graph,
+    storage: impl Into<Storage>,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
 ) -> InsertManyQuery {
-    InsertManyQuery::new(neo4j, block, space_id.into(), space_version.into())
+    InsertManyQuery::new(storage, block, space_id.into(), space_version.into())
 }
 
 pub fn find_one(
-    neo4j: &neo4rs::Graph,
+    storage: impl Into<Storage>,
     attribute_id: impl Into<String>,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
 ) -> FindOneQuery {
     FindOneQuery::new(
-        neo4j,
+        storage,
         attribute_id.into(),
         entity_id.into(),
         space_id.into(),
@@ -132,8 +139,8 @@ pub fn find_one(
     )
 }
 
-pub fn find_many(neo4j: &neo4rs::Graph) -> FindManyQuery {
-    FindManyQuery::new(neo4j)
+pub fn find_many(storage: impl Into<Storage>) -> FindManyQuery {
+    FindManyQuery::new(storage)
 }
 
 pub fn search(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> SemanticSearchQuery {
@@ -223,7 +230,7 @@ impl From<Triple> for BoltType {
 }
 
 pub struct InsertOneQuery {
-    neo4j: neo4rs::Graph,
+    storage: Storage,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -232,14 +239,14 @@ pub struct InsertOneQuery {
 
 impl InsertOneQuery {
     pub(crate) fn new(
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
         triple: Triple,
     ) -> Self {
         Self {
-            neo4j: neo4j.clone(),
+            storage: storage.into(),
             block: block.clone(),
             space_id,
             space_version,
@@ -250,50 +257,19 @@ impl InsertOneQuery {
 
 impl Query<()> for InsertOneQuery {
     async fn send(self) -> Result<(), DatabaseError> {
-        const QUERY: &str = const_format::formatcp!(
-            r#"
-            MERGE (e:Entity {{id: $triple.entity}})
-            ON CREATE SET e += {{
-                `{CREATED_AT}`: datetime($block_timestamp),
-                `{CREATED_AT_BLOCK}`: $block_number
-            }}
-            SET e += {{
-                `{UPDATED_AT}`: datetime($block_timestamp),
-                `{UPDATED_AT_BLOCK}`: $block_number
-            }}
-            WITH e
-            CALL (e) {{
-                MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:$($triple.attr_labels) {{id: $triple.attribute}})
-                WHERE r.max_version IS null AND r.min_version <> $space_version
-                SET r.max_version = $space_version
-            }}
-            CALL (e) {{
-                MERGE (e) -[r:ATTRIBUTE {{space_id: $space_id, min_version: $space_version}}]-> (m:$($triple.attr_labels) {{id: $triple.attribute}})
-                SET m += $triple.value
-                SET m.embedding = $triple.embedding
-            }}
-            "#,
-            CREATED_AT = indexer_ids::CREATED_AT_TIMESTAMP,
-            CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
-            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
-            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
-        );
-
-        let query = neo4rs::query(QUERY)
-            .param("space_id", self.space_id)
-            .param("space_version", self.space_version)
-            .param("triple", self.triple)
-            .param("block_number", self.block.block_number.to_string())
-            .param("block_timestamp", self.block.timestamp.to_rfc3339());
-
-        self.neo4j.run(query).await?;
-
-        Ok(())
+        self.storage
+            .insert_triples(
+                &self.block,
+                &self.space_id,
+                &self.space_version,
+                vec![self.triple],
+            )
+            .await
     }
 }
 
 pub struct InsertManyQuery {
-    neo4j: neo4rs::Graph,
+    storage: Storage,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -302,13 +278,13 @@ pub struct InsertManyQuery {
 
 impl InsertManyQuery {
     fn new(
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
     ) -> Self {
         Self {
-            neo4j: neo4j.clone(),
+            storage: storage.into(),
             block: block.clone(),
             space_id,
             space_version,
@@ -337,51 +313,19 @@ impl InsertManyQuery {
 
 impl Query<()> for InsertManyQuery {
     async fn send(self) -> Result<(), DatabaseError> {
-        const QUERY: &str = const_format::formatcp!(
-            r#"
-            UNWIND $triples as triple
-            MERGE (e:Entity {{id: triple.entity}})
-            ON CREATE SET e += {{
-                `{CREATED_AT}`: datetime($block_timestamp),
-                `{CREATED_AT_BLOCK}`: $block_number
-            }}
-            SET e += {{
-                `{UPDATED_AT}`: datetime($block_timestamp),
-                `{UPDATED_AT_BLOCK}`: $block_number
-            }}
-            WITH e, triple
-            CALL (e, triple) {{
-                MATCH (e) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:$(triple.attr_labels) {{id: triple.attribute}})
-                WHERE r.max_version IS null AND r.min_version <> $space_version
-                SET r.max_version = $space_version
-            }}
-            CALL (e, triple) {{
-                MERGE (e) -[:ATTRIBUTE {{space_id: $space_id, min_version: $space_version}}]-> (m:$(triple.attr_labels) {{id: triple.attribute}})
-                SET m += triple.value
-                SET m.embedding = triple.embedding
-            }}
-            "#,
-            CREATED_AT = indexer_ids::CREATED_AT_TIMESTAMP,
-            CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
-            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
-            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
-        );
-
-        let query = neo4rs::query(QUERY)
-            .param("space_id", self.space_id)
-            .param("space_version", self.space_version)
-            .param("triples", self.triples)
-            .param("block_number", self.block.block_number.to_string())
-            .param("block_timestamp", self.block.timestamp.to_rfc3339());
-
-        self.neo4j.run(query).await?;
-
-        Ok(())
+        self.storage
+            .insert_triples(
+                &self.block,
+                &self.space_id,
+                &self.space_version,
+                self.triples,
+            )
+            .await
     }
 }
 
 pub struct FindOneQuery {
-    neo4j: neo4rs::Graph,
+    storage: Storage,
     attribute_id: String,
     entity_id: String,
     space_id: String,
@@ -391,14 +335,14 @@ pub struct FindOneQuery {
 
 impl FindOneQuery {
     fn new(
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         attribute_id: String,
         entity_id: String,
         space_id: String,
         version: Option<String>,
     ) -> Self {
         Self {
-            neo4j: neo4j.clone(),
+            storage: storage.into(),
             attribute_id,
             entity_id,
             space_id,
@@ -493,14 +437,17 @@ impl FindOneQuery {
 
 impl Query<Option<Triple>> for FindOneQuery {
     async fn send(self) -> Result<Option<Triple>, DatabaseError> {
+        let Some(neo4j) = self.storage.neo4j() else {
+            return self.send_filtered().await;
+        };
+
         let query = self.subquery();
 
         if cfg!(debug_assertions) || cfg!(test) {
             println!("triple::FindOneQuery:\n{}", query.compile());
         }
 
-        self.neo4j
-            .execute(query.build())
+        transaction::execute(neo4j, "triple::find_one", query.build())
             .await?
             .next()
             .await?
@@ -516,102 +463,94 @@ impl Query<Option<Triple>> for FindOneQuery {
     }
 }
 
+impl FindOneQuery {
+    /// Finds the triple with [`crate::mapping::backend::Backend::find_triples`], for
+    /// backends that are not backed by Neo4j
+    async fn send_filtered(&self) -> Result<Option<Triple>, DatabaseError> {
+        let spaces = match &self.pluralism {
+            Pluralism::None | Pluralism::Direction(AggregationDirection::Bidirectional) => {
+                vec![self.space_id.clone()]
+            }
+            Pluralism::Hierarchy(spaces) => {
+                let mut spaces = spaces.clone();
+                spaces.sort_by_key(|space| space.depth);
+                spaces.into_iter().map(|space| space.space_id).collect()
+            }
+            Pluralism::Direction(_) => {
+                return Err(DatabaseError::Unsupported(
+                    "Aggregation directions require the Neo4j backend".to_string(),
+                ))
+            }
+        };
+
+        for space_id in spaces {
+            let filter = TripleFilter {
+                entity_id: Some(PropFilter::default().value(self.entity_id.clone())),
+                attribute_id: Some(PropFilter::default().value(self.attribute_id.clone())),
+                space_id: Some(PropFilter::default().value(space_id)),
+                version: self.version.clone(),
+                limit: Some(1),
+                ..Default::default()
+            };
+
+            if let Some(triple) = self.storage.find_triples(filter).await?.next().await {
+                return triple.map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
 pub struct FindManyQuery {
-    neo4j: neo4rs::Graph,
-    attribute_id: Option<PropFilter<String>>,
-    value: Option<PropFilter<String>>,
-    value_type: Option<PropFilter<String>>,
-    entity_id: Option<PropFilter<String>>,
-    space_id: Option<PropFilter<String>>,
-    space_version: VersionFilter,
+    storage: Storage,
+    filter: TripleFilter,
 }
 
 impl FindManyQuery {
-    pub fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub fn new(storage: impl Into<Storage>) -> Self {
         Self {
-            neo4j: neo4j.clone(),
-            attribute_id: None,
-            value: None,
-            value_type: None,
-            entity_id: None,
-            space_id: None,
-            space_version: VersionFilter::default(),
+            storage: storage.into(),
+            filter: TripleFilter::default(),
         }
     }
 
     pub fn attribute_id(mut self, filter: PropFilter<String>) -> Self {
-        self.attribute_id = Some(filter);
+        self.filter.attribute_id = Some(filter);
         self
     }
 
     pub fn value(mut self, filter: PropFilter<String>) -> Self {
-        self.value = Some(filter);
+        self.filter.value = Some(filter);
         self
     }
 
     pub fn value_type(mut self, filter: PropFilter<String>) -> Self {
-        self.value_type = Some(filter);
+        self.filter.value_type = Some(filter);
         self
     }
 
     pub fn entity_id(mut self, filter: PropFilter<String>) -> Self {
-        self.entity_id = Some(filter);
+        self.filter.entity_id = Some(filter);
         self
     }
 
     pub fn space_id(mut self, filter: PropFilter<String>) -> Self {
-        self.space_id = Some(filter);
+        self.filter.space_id = Some(filter);
         self
     }
 
     pub fn space_version(mut self, space_version: impl Into<String>) -> Self {
-        self.space_version.version_mut(space_version.into());
+        self.filter.version.version_mut(space_version.into());
         self
     }
-
-    fn subquery(&self) -> QueryBuilder {
-        QueryBuilder::default()
-            .subquery(
-                MatchQuery::new("(e:Entity) -[r:ATTRIBUTE]-> (n:Attribute)")
-                    .where_opt(self.entity_id.as_ref().map(|s| s.subquery("e", "id", None)))
-                    .where_opt(
-                        self.attribute_id
-                            .as_ref()
-                            .map(|s| s.subquery("n", "id", None)),
-                    )
-                    .where_opt(self.value.as_ref().map(|s| s.subquery("n", "value", None)))
-                    .where_opt(
-                        self.value_type
-                            .as_ref()
-                            .map(|s| s.subquery("n", "value_type", None)),
-                    )
-                    .where_opt(
-                        self.space_id
-                            .as_ref()
-                            .map(|s| s.subquery("r", "space_id", None)),
-                    )
-                    .r#where(self.space_version.subquery("r")),
-            )
-            .subquery("RETURN n{.*, entity: e.id}")
-    }
 }
 
 impl QueryStream<Triple> for FindManyQuery {
     async fn send(
         self,
     ) -> Result<impl Stream<Item = Result<Triple, DatabaseError>>, DatabaseError> {
-        let query = self.subquery();
-
-        if cfg!(debug_assertions) || cfg!(test) {
-            println!("triple::FindManyQuery:\n{}", query.compile());
-        }
-
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<Triple>()
-            .map_err(DatabaseError::from))
+        self.storage.find_triples(self.filter).await
     }
 }
 
@@ -713,17 +652,17 @@ impl QueryStream<SemanticSearchResult> for SemanticSearchQuery {
             .param("limit", self.limit as i64)
             .param("effective_search_ratio", EFFECTIVE_SEARCH_RATIO);
 
-        Ok(self
-            .neo4j
-            .execute(query)
-            .await?
-            .into_stream_as::<SemanticSearchResult>()
-            .map_err(DatabaseError::from))
+        Ok(
+            transaction::execute(&self.neo4j, "triple::semantic_search", query)
+                .await?
+                .into_stream_as::<SemanticSearchResult>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
 pub struct DeleteOneQuery {
-    neo4j: neo4rs::Graph,
+    storage: Storage,
     block: BlockMetadata,
     attribute_id: String,
     entity_id: String,
@@ -733,7 +672,7 @@ pub struct DeleteOneQuery {
 
 impl DeleteOneQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         block: &BlockMetadata,
         attribute_id: String,
         entity_id: String,
@@ -741,7 +680,7 @@ impl DeleteOneQuery {
         space_version: String,
     ) -> Self {
         Self {
-            neo4j: neo4j.clone(),
+            storage: storage.into(),
             block: block.clone(),
             attribute_id,
             entity_id,
@@ -753,36 +692,19 @@ impl DeleteOneQuery {
 
 impl Query<()> for DeleteOneQuery {
     async fn send(self) -> Result<(), DatabaseError> {
-        const QUERY: &str = const_format::formatcp!(
-            r#"
-            MATCH (e:Entity {{id: $entity_id}}) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: $attribute_id}})
-            WHERE r.max_version IS null
-            SET r.max_version = $space_version
-            SET e += {{
-                `{UPDATED_AT}`: datetime($block_timestamp),
-                `{UPDATED_AT_BLOCK}`: $block_number
-            }}
-            "#,
-            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
-            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
-        );
-
-        let query = neo4rs::query(QUERY)
-            .param("attribute_id", self.attribute_id)
-            .param("entity_id", self.entity_id)
-            .param("space_id", self.space_id)
-            .param("space_version", self.space_version)
-            .param("block_number", self.block.block_number.to_string())
-            .param("block_timestamp", self.block.timestamp.to_rfc3339());
-
-        self.neo4j.run(query).await?;
-
-        Ok(())
+        self.storage
+            .delete_triples(
+                &self.block,
+                &self.space_id,
+                &self.space_version,
+                vec![(self.entity_id, self.attribute_id)],
+            )
+            .await
     }
 }
 
 pub struct DeleteManyQuery {
-    neo4j: neo4rs::Graph,
+    storage: Storage,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -791,13 +713,13 @@ pub struct DeleteManyQuery {
 
 impl DeleteManyQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        storage: impl Into<Storage>,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
     ) -> Self {
         Self {
-            neo4j: neo4j.clone(),
+            storage: storage.into(),
             block: block.clone(),
             space_id,
             space_version,
@@ -826,54 +748,14 @@ impl DeleteManyQuery {
 
 impl Query<()> for DeleteManyQuery {
     async fn send(self) -> Result<(), DatabaseError> {
-        const QUERY: &str = const_format::formatcp!(
-            r#"
-            UNWIND $triples as triple
-            MATCH (e:Entity {{id: triple.entity_id}}) -[r:ATTRIBUTE {{space_id: $space_id}}]-> (:Attribute {{id: triple.attribute_id}})
-            WHERE r.max_version IS null
-            SET r.max_version = $space_version
-            SET e += {{
-                `{UPDATED_AT}`: datetime($block_timestamp),
-                `{UPDATED_AT_BLOCK}`: $block_number
-            }}
-            "#,
-            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
-            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
-        );
-
-        let query = neo4rs::query(QUERY)
-            .param("space_id", self.space_id)
-            .param("space_version", self.space_version)
-            .param(
-                "triples",
-                self.triples
-                    .into_iter()
-                    .map(|(entity_id, attribute_id)| {
-                        BoltType::Map(BoltMap {
-                            value: HashMap::from([
-                                (
-                                    neo4rs::BoltString {
-                                        value: "entity_id".into(),
-                                    },
-                                    entity_id.into(),
-                                ),
-                                (
-                                    neo4rs::BoltString {
-                                        value: "attribute_id".into(),
-                                    },
-                                    attribute_id.into(),
-                                ),
-                            ]),
-                        })
-                    })
-                    .collect::<Vec<_>>(),
+        self.storage
+            .delete_triples(
+                &self.block,
+                &self.space_id,
+                &self.space_version,
+                self.triples,
             )
-            .param("block_number", self.block.block_number.to_string())
-            .param("block_timestamp", self.block.timestamp.to_rfc3339());
-
-        self.neo4j.run(query).await?;
-
-        Ok(())
+            .await
     }
 }
 
@@ -0,0 +1,13 @@
+use lazy_static::lazy_static;
+use prometheus::{register_histogram_vec, HistogramVec};
+
+lazy_static! {
+    /// Time spent executing Neo4j queries, labelled by mapping query type (e.g.: `triple::insert_many`).
+    /// For streamed results, only the time until the first response is measured.
+    pub static ref NEO4J_QUERY_DURATION: HistogramVec = register_histogram_vec!(
+        "neo4j_query_duration_seconds",
+        "Time spent executing Neo4j queries",
+        &["query"]
+    )
+    .expect("Failed to create neo4j_query_duration_seconds histogram");
+}
@@ -70,6 +70,10 @@ pub(crate) struct EntityFieldOpts {
     ty: Type,
     #[darling(default)]
     attribute: Option<StringOrPath>,
+    /// Flag fields only: entities for which the flag is set are excluded from
+    /// `find_many` results unless explicitly included.
+    #[darling(default)]
+    exclude_by_default: bool,
 }
 
 pub(crate) fn generate_from_attributes_impl(opts: &EntityOpts) -> TokenStream2 {
@@ -405,6 +409,73 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
         })
         .collect::<Vec<_>>();
 
+    // Generate the default exclusion of entities for which a flag is set
+    let excluded_fields = fields
+        .iter()
+        .filter(|field| field.exclude_by_default)
+        .collect::<Vec<_>>();
+
+    let include_fields = excluded_fields
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let include_name = Ident::new(&format!("include_{}", field_name), Span::call_site());
+            quote! {
+                #include_name: bool,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let include_field_names = excluded_fields
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            Ident::new(&format!("include_{}", field_name), Span::call_site())
+        })
+        .collect::<Vec<_>>();
+
+    let include_methods = excluded_fields
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let include_name = Ident::new(&format!("include_{}", field_name), Span::call_site());
+            let doc_comment = format!(
+                "Include entities for which {} is set (excluded by default)",
+                field_name
+            );
+
+            quote! {
+                #[doc = #doc_comment]
+                pub fn #include_name(mut self, #include_name: bool) -> Self {
+                    self.#include_name = #include_name;
+                    self
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let exclude_filters = excluded_fields
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let include_name = Ident::new(&format!("include_{}", field_name), Span::call_site());
+            let attribute_name = field
+                .attribute
+                .as_ref()
+                .map(|s| quote!(#s))
+                .unwrap_or_else(|| quote!(#field_name.to_string()));
+
+            quote! {
+                if !self.#include_name {
+                    query = query.exclude_attribute(
+                        grc20_core::mapping::query_utils::AttributeFilter::new(#attribute_name)
+                            .value(grc20_core::mapping::query_utils::PropFilter::<bool>::default().value(true).as_string())
+                    );
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
     let schema_type = opts.schema_type.as_ref().map(|s| quote!(#s));
     let type_filter = if let Some(schema_type) = schema_type {
         quote! {
@@ -420,6 +491,7 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
             neo4j: grc20_core::neo4rs::Graph,
             id: Option<grc20_core::mapping::query_utils::PropFilter<String>>,
             #(#find_many_fields)*
+            #(#include_fields)*
             space_id: String,
             version: Option<String>,
             limit: usize,
@@ -434,6 +506,9 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
                     #(
                         #field_names: None,
                     )*
+                    #(
+                        #include_field_names: false,
+                    )*
                     space_id,
                     version: None,
                     limit: 100,
@@ -450,6 +525,8 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
             #(#find_many_methods)*
 
+            #(#include_methods)*
+
             pub fn version(mut self, version: impl Into<String>) -> Self {
                 self.version = Some(version.into());
                 self
@@ -490,6 +567,8 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
                 #(#find_many_filters)*
 
+                #(#exclude_filters)*
+
                 if let Some(skip) = self.skip {
                     query = query.skip(skip);
                 }
@@ -13,6 +13,20 @@ pub struct Edit {
     pub content_uri: String,
     #[grc20(attribute = indexer_ids::EDIT_INDEX_ATTRIBUTE)]
     pub index: Option<String>,
+    /// Block in which the edit was published. The block and op counts are not
+    /// set for edits indexed by previous versions of the sink.
+    #[grc20(attribute = indexer_ids::BLOCK_NUMBER_ATTRIBUTE)]
+    pub block_number: Option<u64>,
+    #[grc20(attribute = indexer_ids::EDIT_SET_TRIPLE_COUNT_ATTRIBUTE)]
+    pub set_triple_count: Option<u64>,
+    #[grc20(attribute = indexer_ids::EDIT_DELETE_TRIPLE_COUNT_ATTRIBUTE)]
+    pub delete_triple_count: Option<u64>,
+    #[grc20(attribute = indexer_ids::EDIT_CREATE_RELATION_COUNT_ATTRIBUTE)]
+    pub create_relation_count: Option<u64>,
+    #[grc20(attribute = indexer_ids::EDIT_DELETE_RELATION_COUNT_ATTRIBUTE)]
+    pub delete_relation_count: Option<u64>,
+    #[grc20(attribute = indexer_ids::EDIT_DELETE_ENTITY_COUNT_ATTRIBUTE)]
+    pub delete_entity_count: Option<u64>,
 }
 
 impl Edit {
@@ -27,6 +41,12 @@ impl Edit {
                 name,
                 content_uri,
                 index,
+                block_number: None,
+                set_triple_count: None,
+                delete_triple_count: None,
+                create_relation_count: None,
+                delete_relation_count: None,
+                delete_entity_count: None,
             },
         )
         .with_type(indexer_ids::EDIT_TYPE)
@@ -90,3 +110,34 @@ impl ProposedEdit {
         )
     }
 }
+
+/// Edit > EDIT_AUTHOR > Account
+#[derive(Clone)]
+#[grc20_core::relation]
+#[grc20(relation_type = indexer_ids::EDIT_AUTHOR)]
+pub struct EditAuthor;
+
+impl EditAuthor {
+    pub fn gen_id(edit_id: &str, account_id: &str) -> String {
+        ids::create_id_from_unique_string(format!("EDIT_AUTHOR:{edit_id}:{account_id}"))
+    }
+
+    /// Relation from the edit to its `index`-th author
+    pub fn new(
+        edit_id: impl Into<String>,
+        account_id: impl Into<String>,
+        index: usize,
+    ) -> Relation<Self, EntityNodeRef> {
+        let edit_id = edit_id.into();
+        let account_id = account_id.into();
+
+        Relation::new(
+            Self::gen_id(&edit_id, &account_id),
+            edit_id,
+            account_id,
+            indexer_ids::EDIT_AUTHOR,
+            index.to_string(),
+            Self {},
+        )
+    }
+}
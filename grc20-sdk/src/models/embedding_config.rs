@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use grc20_core::{
+    entity,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{Entity, Query},
+    neo4rs,
+};
+
+/// Embedding model the index was built with. Embeddings computed with a different
+/// model (or dimension) cannot be compared with the indexed ones.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[grc20_core::entity]
+pub struct EmbeddingConfig {
+    #[grc20(attribute = indexer_ids::EMBEDDING_MODEL_ATTRIBUTE)]
+    pub model: String,
+    #[grc20(attribute = indexer_ids::EMBEDDING_DIM_ATTRIBUTE)]
+    pub dim: u64,
+}
+
+impl EmbeddingConfig {
+    pub fn new(model: &str, dim: usize) -> Entity<Self> {
+        Entity::new(
+            indexer_ids::EMBEDDING_CONFIG_ID,
+            Self {
+                model: model.to_string(),
+                dim: dim as u64,
+            },
+        )
+        .with_type(indexer_ids::EMBEDDING_CONFIG_TYPE)
+    }
+
+    pub async fn load(neo4j: &neo4rs::Graph) -> Result<Option<Entity<Self>>, DatabaseError> {
+        entity::find_one::<Entity<Self>>(neo4j, indexer_ids::EMBEDDING_CONFIG_ID)
+            .space_id(indexer_ids::INDEXER_SPACE_ID)
+            .send()
+            .await
+    }
+
+    /// Returns true if the config matches the given model and dimension
+    pub fn matches(&self, model: &str, dim: usize) -> bool {
+        self.model.eq_ignore_ascii_case(model) && self.dim == dim as u64
+    }
+
+    /// Checks that the index was built with the embedding model `model` of dimension
+    /// `dim`, since search results would be meaningless otherwise. Fails if the index
+    /// was built with another model or if no model is recorded.
+    pub async fn check(
+        neo4j: &neo4rs::Graph,
+        model: &str,
+        dim: usize,
+    ) -> Result<(), EmbeddingConfigError> {
+        match Self::load(neo4j).await? {
+            Some(config) if config.attributes.matches(model, dim) => Ok(()),
+            Some(config) => Err(EmbeddingConfigError::Mismatch {
+                indexed_model: config.attributes.model,
+                indexed_dim: config.attributes.dim,
+                model: model.to_string(),
+                dim,
+            }),
+            None => Err(EmbeddingConfigError::Missing),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmbeddingConfigError {
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+    #[error("Embedding model mismatch: index was built with {indexed_model} (dim = {indexed_dim}) but {model} (dim = {dim}) is configured")]
+    Mismatch {
+        indexed_model: String,
+        indexed_dim: u64,
+        model: String,
+        dim: usize,
+    },
+    #[error("No embedding model recorded in the index (the sink records it on start)")]
+    Missing,
+}
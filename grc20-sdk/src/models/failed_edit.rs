@@ -0,0 +1,44 @@
+use grc20_core::{ids, indexer_ids, mapping::Entity};
+
+/// Edit (or op of an edit) that was rejected by the indexer, e.g.: because its
+/// content could not be decoded.
+#[derive(Clone)]
+#[grc20_core::entity]
+#[grc20(schema_type = indexer_ids::FAILED_EDIT_TYPE)]
+pub struct FailedEdit {
+    #[grc20(attribute = indexer_ids::EDIT_CONTENT_URI_ATTRIBUTE)]
+    pub content_uri: String,
+    /// ID of the space the edit was published to
+    #[grc20(attribute = indexer_ids::FAILED_EDIT_SPACE_ATTRIBUTE)]
+    pub space: String,
+    #[grc20(attribute = indexer_ids::BLOCK_NUMBER_ATTRIBUTE)]
+    pub block_number: u64,
+    #[grc20(attribute = indexer_ids::FAILED_EDIT_REASON_ATTRIBUTE)]
+    pub reason: String,
+}
+
+pub fn new_id(space_id: &str, content_uri: &str, reason: &str) -> String {
+    ids::create_id_from_unique_string(format!("{space_id}:{content_uri}:{reason}"))
+}
+
+pub fn new(
+    space_id: impl Into<String>,
+    content_uri: impl Into<String>,
+    block_number: u64,
+    reason: impl Into<String>,
+) -> Entity<FailedEdit> {
+    let space_id = space_id.into();
+    let content_uri = content_uri.into();
+    let reason = reason.into();
+
+    Entity::new(
+        new_id(&space_id, &content_uri, &reason),
+        FailedEdit {
+            content_uri,
+            space: space_id,
+            block_number,
+            reason,
+        },
+    )
+    .with_type(indexer_ids::FAILED_EDIT_TYPE)
+}
@@ -3,9 +3,12 @@ pub mod base_entity;
 pub mod cursor;
 pub mod edit;
 pub mod editor;
+pub mod embedding_config;
+pub mod failed_edit;
 pub mod member;
 pub mod property;
 pub mod proposal;
+pub mod skipped_edit;
 pub mod space;
 pub mod vote;
 
@@ -14,11 +17,14 @@ pub use base_entity::BaseEntity;
 pub use cursor::Cursor;
 pub use edit::Edit;
 pub use editor::SpaceEditor;
+pub use embedding_config::{EmbeddingConfig, EmbeddingConfigError};
+pub use failed_edit::FailedEdit;
 pub use member::SpaceMember;
 pub use property::Property;
 pub use proposal::{
     AddEditorProposal, AddMemberProposal, AddSubspaceProposal, EditProposal, Proposal,
     ProposalCreator, Proposals, RemoveEditorProposal, RemoveMemberProposal, RemoveSubspaceProposal,
 };
+pub use skipped_edit::SkippedEdit;
 pub use space::{Space, SpaceBuilder, SpaceGovernanceType};
 pub use vote::{VoteCast, VoteType};
@@ -10,9 +10,7 @@ use grc20_core::{
     neo4rs, relation, system_ids,
 };
 
-use crate::models::space::ParentSpacesQuery;
-
-use super::{base_entity, space::SubspacesQuery, BaseEntity};
+use super::{base_entity, space, BaseEntity};
 
 #[grc20_core::entity]
 #[grc20(schema_type = system_ids::ATTRIBUTE)]
@@ -130,7 +128,7 @@ async fn attribute_aggregation_direction(
         depth: 0,
     }];
 
-    let parent_spaces = ParentSpacesQuery::new(neo4j.clone(), space_id.to_string())
+    let parent_spaces = space::parent_spaces(neo4j, space_id)
         .max_depth(None)
         .send()
         .await?
@@ -278,7 +276,7 @@ async fn spaces_for_property(
 
     match attribute_aggregation_direction(neo4j, &space_id, &property_id).await? {
         Some(AggregationDirection::Up) => {
-            let subspaces = SubspacesQuery::new(neo4j.clone(), space_id.clone())
+            let subspaces = space::subspaces(neo4j, &space_id)
                 .max_depth(None)
                 .send()
                 .await?
@@ -289,7 +287,7 @@ async fn spaces_for_property(
             Ok(spaces)
         }
         Some(AggregationDirection::Down) => {
-            let parent_spaces = ParentSpacesQuery::new(neo4j.clone(), space_id.clone())
+            let parent_spaces = space::parent_spaces(neo4j, &space_id)
                 .max_depth(None)
                 .send()
                 .await?
@@ -300,14 +298,14 @@ async fn spaces_for_property(
             Ok(spaces)
         }
         Some(AggregationDirection::Bidirectional) => {
-            let subspaces = SubspacesQuery::new(neo4j.clone(), space_id.clone())
+            let subspaces = space::subspaces(neo4j, &space_id)
                 .max_depth(None)
                 .send()
                 .await?
                 .try_collect::<Vec<_>>()
                 .await?;
 
-            let parent_spaces = ParentSpacesQuery::new(neo4j.clone(), space_id.clone())
+            let parent_spaces = space::parent_spaces(neo4j, &space_id)
                 .max_depth(None)
                 .send()
                 .await?
@@ -1,19 +1,23 @@
 use std::fmt::Display;
 
-use futures::{pin_mut, StreamExt};
+use chrono::{DateTime, Utc};
+use futures::{pin_mut, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use web3_utils::checksum_address;
 
+use super::vote::VoteType;
+
 use grc20_core::{
-    entity,
+    block::BlockMetadata,
+    entity::{self, EntityFilter, TypesFilter},
     error::DatabaseError,
     ids, indexer_ids,
     mapping::{
         self,
-        attributes::{FromAttributes, IntoAttributes},
+        attributes::{self, FromAttributes, IntoAttributes},
         entity::EntityNodeRef,
-        query_utils::{AttributeFilter, PropFilter, QueryStream},
-        Entity, Relation, TriplesConversionError, Value,
+        query_utils::{AttributeFilter, PropFilter, Query, QueryStream},
+        transaction, Entity, Relation, Triple, TriplesConversionError, Value,
     },
     neo4rs, pb,
 };
@@ -26,6 +30,7 @@ pub struct Proposal {
     pub plugin_address: String,
     pub start_time: String,
     pub end_time: String,
+    pub votes: VoteTally,
 }
 
 impl Proposal {
@@ -61,47 +66,274 @@ impl Proposal {
         stream.next().await.transpose()
     }
 
-    // /// Returns a query to set the status of a proposal given its ID
-    // pub async fn set_status(
-    //     neo4j: &neo4rs::Graph,
-    //     block: &BlockMetadata,
-    //     proposal_id: &str,
-    //     status: ProposalStatus,
-    // ) -> Result<(), DatabaseError> {
-    //     const QUERY: &str = const_format::formatcp!(
-    //         r#"
-    //         MATCH (n:`{PROPOSAL_TYPE}` {{onchain_proposal_id: $proposal_id}})
-    //         SET n.status = $status
-    //         SET n += {{
-    //             `{UPDATED_AT}`: datetime($updated_at),
-    //             `{UPDATED_AT_BLOCK}`: $updated_at_block
-    //         }}
-    //         "#,
-    //         PROPOSAL_TYPE = indexer_ids::PROPOSAL_TYPE,
-    //         UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
-    //         UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
-    //     );
-
-    //     let query = neo4rs::query(QUERY)
-    //         .param("proposal_id", proposal_id)
-    //         .param("status", status.to_string())
-    //         .param("updated_at", block.timestamp.to_rfc3339())
-    //         .param("updated_at_block", block.block_number.to_string());
-
-    //     Ok(neo4j.run(query).await?)
-    // }
+    /// Sets the status of the proposal `proposal_id`
+    pub async fn set_status(
+        neo4j: &neo4rs::Graph,
+        block: &BlockMetadata,
+        proposal_id: &str,
+        status: ProposalStatus,
+    ) -> Result<(), DatabaseError> {
+        Triple::new(proposal_id, indexer_ids::PROPOSAL_STATUS_ATTRIBUTE, status)
+            .insert(neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await
+    }
+
+    /// Returns the proposals which are still open (i.e.: with the status
+    /// [`ProposalStatus::Proposed`]) although their voting period ended after `since`
+    /// (if set) and at or before `timestamp`. Passing the timestamp of the previously
+    /// processed block as `since` avoids going through all the proposals whose voting
+    /// period ended earlier.
+    pub async fn find_ended(
+        neo4j: &neo4rs::Graph,
+        since: Option<&DateTime<Utc>>,
+        timestamp: &DateTime<Utc>,
+    ) -> Result<Vec<EndedProposal>, DatabaseError> {
+        const QUERY: &str = r#"
+            MATCH (p:Entity) -[e:ATTRIBUTE {space_id: $space_id}]-> (end_time:Attribute {id: "end_time"})
+            WHERE e.max_version IS NULL
+                AND toInteger(end_time.value) <= $timestamp
+                AND ($since IS NULL OR toInteger(end_time.value) > $since)
+            MATCH (p) -[s:ATTRIBUTE {space_id: $space_id}]-> (:Attribute {id: $status_attribute, value: $status})
+            WHERE s.max_version IS NULL
+            OPTIONAL MATCH (space:Entity) -[r:RELATION {relation_type: $proposals, space_id: $space_id}]-> (p)
+            WHERE r.max_version IS NULL
+            RETURN p.id AS id, toInteger(end_time.value) AS end_time, space.id AS space_id
+            "#;
+
+        let query = neo4rs::query(QUERY)
+            .param("space_id", indexer_ids::INDEXER_SPACE_ID)
+            .param("status_attribute", indexer_ids::PROPOSAL_STATUS_ATTRIBUTE)
+            .param("status", Value::from(ProposalStatus::Proposed).value)
+            .param("proposals", indexer_ids::PROPOSALS)
+            .param("since", since.map(|since| since.timestamp()))
+            .param("timestamp", timestamp.timestamp());
+
+        Ok(transaction::execute(neo4j, "proposal::find_ended", query)
+            .await?
+            .into_stream_as::<EndedProposal>()
+            .try_collect()
+            .await?)
+    }
+
+    /// Returns the vote tally stored on the proposal `proposal_id` (see
+    /// [`Proposal::set_vote_tally`])
+    pub async fn vote_tally(
+        neo4j: &neo4rs::Graph,
+        proposal_id: &str,
+    ) -> Result<VoteTally, DatabaseError> {
+        let tally: Option<VoteTally> =
+            attributes::find_one(neo4j, proposal_id, indexer_ids::INDEXER_SPACE_ID, None)
+                .send()
+                .await?;
+
+        Ok(tally.unwrap_or_default())
+    }
+
+    /// Stores the vote tally of the proposal `proposal_id`
+    pub async fn set_vote_tally(
+        neo4j: &neo4rs::Graph,
+        block: &BlockMetadata,
+        proposal_id: &str,
+        tally: VoteTally,
+    ) -> Result<(), DatabaseError> {
+        attributes::insert_one(
+            neo4j,
+            block,
+            proposal_id,
+            indexer_ids::INDEXER_SPACE_ID,
+            "0",
+            tally,
+        )
+        .send()
+        .await
+    }
+
+    /// Counts the current votes cast on the proposal `proposal_id` from its `VOTE_CAST`
+    /// relations. Unlike [`Proposal::vote_tally`], this does not rely on the stored tally.
+    pub async fn count_votes(
+        neo4j: &neo4rs::Graph,
+        proposal_id: &str,
+    ) -> Result<VoteTally, DatabaseError> {
+        const QUERY: &str = r#"
+            MATCH (:Entity) -[r:RELATION {relation_type: $vote_cast}]-> (:Entity {id: $proposal_id})
+            WHERE r.max_version IS NULL
+            MATCH (:Entity {id: r.id}) -[a:ATTRIBUTE {space_id: $space_id}]-> (vote:Attribute {id: $vote_type})
+            WHERE a.max_version IS NULL
+            RETURN count(CASE vote.value WHEN "ACCEPT" THEN 1 END) AS accept,
+                   count(CASE vote.value WHEN "REJECT" THEN 1 END) AS reject,
+                   count(CASE vote.value WHEN "ABSTAIN" THEN 1 END) AS abstain,
+                   count(vote) AS voters
+            "#;
+
+        let query = neo4rs::query(QUERY)
+            .param("space_id", indexer_ids::INDEXER_SPACE_ID)
+            .param("vote_cast", indexer_ids::VOTE_CAST_TYPE)
+            .param("vote_type", indexer_ids::VOTE_TYPE_ATTRIBUTE)
+            .param("proposal_id", proposal_id);
+
+        let tally = transaction::execute(neo4j, "proposal::count_votes", query)
+            .await?
+            .into_stream_as::<VoteTally>()
+            .try_next()
+            .await?;
+
+        Ok(tally.unwrap_or_default())
+    }
+
+    /// Returns the IDs of the proposals on which at least one vote was cast
+    pub async fn find_voted(neo4j: &neo4rs::Graph) -> Result<Vec<String>, DatabaseError> {
+        const QUERY: &str = r#"
+            MATCH (:Entity) -[r:RELATION {relation_type: $vote_cast}]-> (p:Entity)
+            WHERE r.max_version IS NULL
+            RETURN DISTINCT p.id AS id
+            "#;
+
+        #[derive(Deserialize)]
+        struct Row {
+            id: String,
+        }
+
+        let query = neo4rs::query(QUERY).param("vote_cast", indexer_ids::VOTE_CAST_TYPE);
+
+        Ok(transaction::execute(neo4j, "proposal::find_voted", query)
+            .await?
+            .into_stream_as::<Row>()
+            .map_ok(|row| row.id)
+            .try_collect()
+            .await?)
+    }
+}
+
+/// Proposal whose voting period ended, see [`Proposal::find_ended`]
+#[derive(Clone, Debug, Deserialize)]
+pub struct EndedProposal {
+    pub id: String,
+    /// End of the voting period (UNIX timestamp in seconds)
+    pub end_time: i64,
+    /// Space the proposal was created in
+    pub space_id: Option<String>,
+}
+
+/// Number of votes of each type cast on a proposal. Only the current vote of each
+/// account is counted, so `voters` is the sum of the other counts.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub struct VoteTally {
+    pub accept: u64,
+    pub reject: u64,
+    pub abstain: u64,
+    pub voters: u64,
+}
+
+impl VoteTally {
+    /// Counts the vote `vote` of an account whose previous vote on the proposal (if
+    /// any) was `previous`. The counts saturate at zero so that a tally which is out of
+    /// sync with the votes (e.g.: indexed before the tally existed) cannot underflow.
+    pub fn apply(&mut self, previous: Option<VoteType>, vote: VoteType) {
+        match previous {
+            Some(previous) => {
+                let count = self.count_mut(previous);
+                *count = count.saturating_sub(1);
+            }
+            None => self.voters += 1,
+        }
+        *self.count_mut(vote) += 1;
+    }
+
+    fn count_mut(&mut self, vote: VoteType) -> &mut u64 {
+        match vote {
+            VoteType::Accept => &mut self.accept,
+            VoteType::Reject => &mut self.reject,
+            VoteType::Abstain => &mut self.abstain,
+        }
+    }
+}
+
+/// Voting settings of a governance plugin, which decide whether a proposal passes once
+/// its voting period ends (see Aragon's `MajorityVotingBase`). Ratios are expressed in
+/// parts per million (see [`VotingSettings::RATIO_BASE`]).
+///
+/// The settings are not part of the substreams output: the sink indexes the settings it
+/// is configured with on each space when its governance plugin is created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VotingSettings {
+    /// Share of the accept and reject votes which must be accept votes (exclusive)
+    pub support_threshold: u64,
+    /// Share of the voting power (i.e.: editors) which must have voted (inclusive)
+    pub min_participation: u64,
+}
+
+impl VotingSettings {
+    pub const RATIO_BASE: u64 = 1_000_000;
+
+    /// Whether a proposal with the votes `tally` passes, `voting_power` being the
+    /// number of accounts allowed to vote on it
+    pub fn passes(&self, tally: &VoteTally, voting_power: u64) -> bool {
+        let support_reached = Self::RATIO_BASE.saturating_sub(self.support_threshold)
+            * tally.accept
+            > self.support_threshold * tally.reject;
+        let participation_reached = (tally.accept + tally.reject + tally.abstain)
+            * Self::RATIO_BASE
+            >= self.min_participation * voting_power;
+
+        support_reached && participation_reached
+    }
+}
+
+/// Majority (more than 50% of accept votes) without minimum participation
+impl Default for VotingSettings {
+    fn default() -> Self {
+        Self {
+            support_threshold: Self::RATIO_BASE / 2,
+            min_participation: 0,
+        }
+    }
+}
+
+impl IntoAttributes for VoteTally {
+    fn into_attributes(self) -> Result<mapping::Attributes, TriplesConversionError> {
+        Ok(mapping::Attributes::default()
+            .attribute((indexer_ids::PROPOSAL_ACCEPT_VOTES_ATTRIBUTE, self.accept))
+            .attribute((indexer_ids::PROPOSAL_REJECT_VOTES_ATTRIBUTE, self.reject))
+            .attribute((indexer_ids::PROPOSAL_ABSTAIN_VOTES_ATTRIBUTE, self.abstain))
+            .attribute((indexer_ids::PROPOSAL_VOTER_COUNT_ATTRIBUTE, self.voters)))
+    }
+}
+
+/// Proposals indexed before vote tallies were recorded have no votes attributes
+impl FromAttributes for VoteTally {
+    fn from_attributes(
+        mut attributes: mapping::Attributes,
+    ) -> Result<Self, TriplesConversionError> {
+        Ok(Self {
+            accept: attributes
+                .pop_opt(indexer_ids::PROPOSAL_ACCEPT_VOTES_ATTRIBUTE)?
+                .unwrap_or_default(),
+            reject: attributes
+                .pop_opt(indexer_ids::PROPOSAL_REJECT_VOTES_ATTRIBUTE)?
+                .unwrap_or_default(),
+            abstain: attributes
+                .pop_opt(indexer_ids::PROPOSAL_ABSTAIN_VOTES_ATTRIBUTE)?
+                .unwrap_or_default(),
+            voters: attributes
+                .pop_opt(indexer_ids::PROPOSAL_VOTER_COUNT_ATTRIBUTE)?
+                .unwrap_or_default(),
+        })
+    }
 }
 
 impl IntoAttributes for Proposal {
     fn into_attributes(
         self,
     ) -> Result<grc20_core::mapping::Attributes, grc20_core::mapping::TriplesConversionError> {
-        Ok(grc20_core::mapping::Attributes::default()
-            .attribute(("onchain_proposal_id", self.onchain_proposal_id))
-            .attribute(("status", self.status.to_string()))
-            .attribute(("plugin_address", self.plugin_address))
-            .attribute(("start_time", self.start_time))
-            .attribute(("end_time", self.end_time)))
+        let mut attributes = self.votes.into_attributes()?;
+        attributes.attribute_mut(("onchain_proposal_id", self.onchain_proposal_id));
+        attributes.attribute_mut((indexer_ids::PROPOSAL_STATUS_ATTRIBUTE, self.status));
+        attributes.attribute_mut(("plugin_address", self.plugin_address));
+        attributes.attribute_mut(("start_time", self.start_time));
+        attributes.attribute_mut(("end_time", self.end_time));
+
+        Ok(attributes)
     }
 }
 
@@ -111,10 +343,11 @@ impl FromAttributes for Proposal {
     ) -> Result<Self, grc20_core::mapping::TriplesConversionError> {
         Ok(Self {
             onchain_proposal_id: attributes.pop("onchain_proposal_id")?,
-            status: attributes.pop("status")?,
+            status: attributes.pop(indexer_ids::PROPOSAL_STATUS_ATTRIBUTE)?,
             plugin_address: attributes.pop("plugin_address")?,
             start_time: attributes.pop("start_time")?,
             end_time: attributes.pop("end_time")?,
+            votes: VoteTally::from_attributes(attributes)?,
         })
     }
 }
@@ -154,6 +387,33 @@ pub enum ProposalStatus {
     Executed,
 }
 
+impl ProposalStatus {
+    /// Returns the status at time `now` of a proposal whose voting period ends at
+    /// `end_time` (both UNIX timestamps in seconds) given the votes cast on it, the
+    /// voting settings of its plugin and its voting power (see
+    /// [`VotingSettings::passes`]).
+    ///
+    /// A proposal stays open until the end of its voting period, after which it is
+    /// accepted if it passes and rejected otherwise. Proposals which are no longer open
+    /// keep their status (an accepted proposal only becomes executed through its
+    /// execution event).
+    pub fn evaluate(
+        self,
+        end_time: i64,
+        now: i64,
+        tally: &VoteTally,
+        settings: &VotingSettings,
+        voting_power: u64,
+    ) -> Self {
+        match self {
+            Self::Proposed if now < end_time => Self::Proposed,
+            Self::Proposed if settings.passes(tally, voting_power) => Self::Accepted,
+            Self::Proposed => Self::Rejected,
+            _ => self,
+        }
+    }
+}
+
 impl From<ProposalStatus> for Value {
     fn from(status: ProposalStatus) -> Self {
         match status {
@@ -582,6 +842,55 @@ impl EditProposal {
         .with_type(indexer_ids::PROPOSAL_TYPE)
         .with_type(indexer_ids::EDIT_PROPOSAL)
     }
+
+    /// Creates the proposal of an edit which was published without an onchain proposal
+    /// (e.g.: edits of personal spaces). The proposal is identified by the plugin that
+    /// published the edit and the edit ID, and is considered executed at `timestamp`.
+    pub fn synthetic(
+        plugin_address: &str,
+        edit_id: &str,
+        content_uri: String,
+        timestamp: &DateTime<Utc>,
+    ) -> Entity<Self> {
+        let time = timestamp.timestamp().to_string();
+
+        Self::new(
+            Proposal {
+                onchain_proposal_id: edit_id.to_string(),
+                status: ProposalStatus::Executed,
+                plugin_address: checksum_address(plugin_address),
+                start_time: time.clone(),
+                end_time: time,
+                votes: VoteTally::default(),
+            },
+            content_uri,
+        )
+        .with_type(indexer_ids::SYNTHETIC_PROPOSAL)
+    }
+
+    /// Finds the edit proposal (onchain or synthetic) of the edit `content_uri`
+    pub async fn find_by_content_uri(
+        neo4j: &neo4rs::Graph,
+        content_uri: &str,
+    ) -> Result<Option<Entity<Self>>, DatabaseError> {
+        let stream = entity::find_many::<Entity<Self>>(neo4j)
+            .with_filter(
+                EntityFilter::default()
+                    .attribute(
+                        AttributeFilter::new("content_uri")
+                            .value(PropFilter::default().value(content_uri)),
+                    )
+                    .relations(TypesFilter::default().r#type(indexer_ids::EDIT_PROPOSAL)),
+            )
+            .space_id(indexer_ids::INDEXER_SPACE_ID)
+            .limit(1)
+            .send()
+            .await?;
+
+        pin_mut!(stream);
+
+        stream.next().await.transpose()
+    }
 }
 
 impl IntoAttributes for EditProposal {
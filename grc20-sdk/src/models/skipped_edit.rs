@@ -0,0 +1,55 @@
+use grc20_core::{ids, indexer_ids, mapping::Entity};
+
+/// Edit that was skipped by the indexer because its space, author or DAO is
+/// blacklisted. Kept so that the edit can be backfilled if the blacklist entry
+/// is removed.
+#[derive(Clone)]
+#[grc20_core::entity]
+#[grc20(schema_type = indexer_ids::SKIPPED_EDIT_TYPE)]
+pub struct SkippedEdit {
+    #[grc20(attribute = indexer_ids::EDIT_CONTENT_URI_ATTRIBUTE)]
+    pub content_uri: String,
+    /// ID of the space the edit was published to
+    #[grc20(attribute = indexer_ids::SKIPPED_EDIT_SPACE_ATTRIBUTE)]
+    pub space: String,
+    /// Address of the blacklisted author of the edit, or of its creator if the edit was
+    /// skipped because of its space or DAO
+    #[grc20(attribute = indexer_ids::SKIPPED_EDIT_AUTHOR_ATTRIBUTE)]
+    pub author: String,
+    /// Address of the DAO of the space
+    #[grc20(attribute = indexer_ids::SKIPPED_EDIT_DAO_ATTRIBUTE)]
+    pub dao_address: String,
+    #[grc20(attribute = indexer_ids::BLOCK_NUMBER_ATTRIBUTE)]
+    pub block_number: u64,
+    #[grc20(attribute = indexer_ids::SKIPPED_EDIT_REASON_ATTRIBUTE)]
+    pub reason: String,
+}
+
+pub fn new_id(space_id: &str, content_uri: &str) -> String {
+    ids::create_id_from_unique_string(format!("SKIPPED:{space_id}:{content_uri}"))
+}
+
+pub fn new(
+    space_id: impl Into<String>,
+    content_uri: impl Into<String>,
+    author: impl Into<String>,
+    dao_address: impl Into<String>,
+    block_number: u64,
+    reason: impl Into<String>,
+) -> Entity<SkippedEdit> {
+    let space_id = space_id.into();
+    let content_uri = content_uri.into();
+
+    Entity::new(
+        new_id(&space_id, &content_uri),
+        SkippedEdit {
+            content_uri,
+            space: space_id,
+            author: author.into(),
+            dao_address: dao_address.into(),
+            block_number,
+            reason: reason.into(),
+        },
+    )
+    .with_type(indexer_ids::SKIPPED_EDIT_TYPE)
+}
@@ -1,14 +1,12 @@
-pub mod parent_spaces_query;
+pub mod related_spaces_query;
 pub mod space_editors_query;
 pub mod space_model;
 // pub mod space_hierarchy;
 pub mod space_members_query;
 pub mod space_types_query;
-pub mod subspaces_query;
 
-pub use parent_spaces_query::ParentSpacesQuery;
+pub use related_spaces_query::{RelatedSpacesQuery, RelationDirection};
 pub use space_editors_query::SpaceEditorsQuery;
 pub use space_members_query::SpaceMembersQuery;
 pub use space_model::*;
 pub use space_types_query::{FindSpaceTypeQuery, FindSpaceTypesQuery};
-pub use subspaces_query::SubspacesQuery;
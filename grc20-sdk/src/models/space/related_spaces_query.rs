@@ -0,0 +1,122 @@
+use futures::{Stream, TryStreamExt};
+
+use grc20_core::{
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{
+        aggregation::SpaceRanking,
+        query_utils::{
+            query_builder::{QueryBuilder, Subquery},
+            QueryStream,
+        },
+        transaction,
+    },
+    neo4rs,
+};
+
+/// Direction in which to follow the space relations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelationDirection {
+    /// Follow the relations from the space to the related spaces
+    Outgoing,
+    /// Follow the relations from the related spaces to the space
+    Incoming,
+}
+
+/// Query to find the spaces related to a given space through a space relation
+/// type (e.g.: parent spaces, subspaces, predecessors or successors)
+pub struct RelatedSpacesQuery<T> {
+    neo4j: neo4rs::Graph,
+    name: &'static str,
+    space_id: String,
+    relation_type: &'static str,
+    direction: RelationDirection,
+    include_start: bool,
+    limit: usize,
+    skip: Option<usize>,
+    max_depth: Option<usize>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> RelatedSpacesQuery<T> {
+    pub(crate) fn new(
+        neo4j: neo4rs::Graph,
+        name: &'static str,
+        space_id: String,
+        relation_type: &'static str,
+        direction: RelationDirection,
+    ) -> Self {
+        Self {
+            neo4j,
+            name,
+            space_id,
+            relation_type,
+            direction,
+            include_start: true,
+            limit: 100,
+            skip: None,
+            max_depth: Some(1),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Whether to include the space itself (at depth 0) in the results
+    pub(crate) fn include_start(mut self, include_start: bool) -> Self {
+        self.include_start = include_start;
+        self
+    }
+
+    /// Limit the number of results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Skip a number of results
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    /// Limit the depth of the search
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn subquery(&self) -> QueryBuilder {
+        let (left, right) = match self.direction {
+            RelationDirection::Outgoing => ("-", "->"),
+            RelationDirection::Incoming => ("<-", "-"),
+        };
+
+        QueryBuilder::default()
+            .subquery(format!(
+                r#"MATCH (start:Entity {{id: $space_id}}) (() {left}[r:RELATION {{relation_type: "{}", space_id: "{}"}} WHERE r.max_version IS NULL]{right} (s:Entity)){{,}}"#,
+                self.relation_type,
+                indexer_ids::INDEXER_SPACE_ID,
+            ))
+            .subquery("WHERE size(s) = size(COLLECT { WITH s UNWIND s AS _ RETURN DISTINCT _ })")
+            .subquery_opt((!self.include_start).then_some("AND size(s) > 0"))
+            .subquery_opt(self.max_depth.map(|depth| format!("AND size(s) <= {}", depth)))
+            .subquery("WITH {space_id: LAST([start] + s).id, depth: SIZE(s)} AS spaces")
+            .subquery("ORDER BY spaces.depth")
+            .skip_opt(self.skip)
+            .limit(self.limit)
+            .params("space_id", self.space_id.clone())
+    }
+}
+
+impl QueryStream<SpaceRanking> for RelatedSpacesQuery<SpaceRanking> {
+    async fn send(
+        self,
+    ) -> Result<impl Stream<Item = Result<SpaceRanking, DatabaseError>>, DatabaseError> {
+        let query = self.subquery().r#return("spaces");
+
+        Ok(transaction::execute(&self.neo4j, self.name, query.build())
+            .await?
+            .into_stream_as::<SpaceRanking>()
+            .map_err(DatabaseError::from)
+            .and_then(|row| async move { Ok(row) }))
+    }
+}
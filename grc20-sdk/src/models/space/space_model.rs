@@ -1,4 +1,5 @@
-use futures::{pin_mut, StreamExt};
+use futures::{pin_mut, StreamExt, TryStreamExt};
+use serde::Deserialize;
 use web3_utils::checksum_address;
 
 use grc20_core::{
@@ -9,14 +10,16 @@ use grc20_core::{
     mapping::{
         prop_filter,
         query_utils::{AttributeFilter, PropFilter, Query, QueryStream},
-        relation, Entity, EntityNode, Relation, TriplesConversionError, Value,
+        relation, transaction, Entity, EntityNode, Relation, Triple, TriplesConversionError, Value,
     },
     neo4rs, network_ids, system_ids,
 };
 
+use crate::models::proposal::VotingSettings;
+
 use super::{
-    FindSpaceTypeQuery, FindSpaceTypesQuery, ParentSpacesQuery, SpaceEditorsQuery,
-    SpaceMembersQuery, SubspacesQuery,
+    FindSpaceTypeQuery, FindSpaceTypesQuery, RelatedSpacesQuery, RelationDirection,
+    SpaceEditorsQuery, SpaceMembersQuery,
 };
 
 #[derive(Clone, PartialEq)]
@@ -48,6 +51,29 @@ pub struct Space {
     /// The address of the personal space admin plugin contract.
     #[grc20(attribute = indexer_ids::SPACE_PERSONAL_PLUGIN_ADDRESS)]
     pub personal_space_admin_plugin: Option<String>,
+
+    /// Whether the space was archived. Archived spaces are excluded from
+    /// `find_many` results by default.
+    #[grc20(attribute = indexer_ids::SPACE_ARCHIVED, exclude_by_default)]
+    pub archived: Option<bool>,
+
+    /// Support threshold of the voting plugin (see [`VotingSettings`]).
+    #[grc20(attribute = indexer_ids::SPACE_SUPPORT_THRESHOLD)]
+    pub support_threshold: Option<u64>,
+
+    /// Minimum participation of the voting plugin (see [`VotingSettings`]).
+    #[grc20(attribute = indexer_ids::SPACE_MIN_PARTICIPATION)]
+    pub min_participation: Option<u64>,
+}
+
+impl Space {
+    /// Voting settings of the space's voting plugin, if indexed
+    pub fn voting_settings(&self) -> Option<VotingSettings> {
+        Some(VotingSettings {
+            support_threshold: self.support_threshold?,
+            min_participation: self.min_participation?,
+        })
+    }
 }
 
 /// Generates a unique ID for a space based on its network and DAO contract address.
@@ -198,6 +224,18 @@ pub async fn find_by_personal_plugin_address(
     stream.next().await.transpose()
 }
 
+/// Flag a space as archived.
+pub async fn archive(
+    neo4j: &neo4rs::Graph,
+    block: &BlockMetadata,
+    space_id: &str,
+) -> Result<(), DatabaseError> {
+    Triple::new(space_id, indexer_ids::SPACE_ARCHIVED, true)
+        .insert(neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+        .send()
+        .await
+}
+
 /// Find all members of a space
 pub fn members(neo4j: &neo4rs::Graph, space_id: &str) -> SpaceMembersQuery {
     SpaceMembersQuery::new(neo4j.clone(), space_id.to_string())
@@ -208,14 +246,79 @@ pub fn editors(neo4j: &neo4rs::Graph, space_id: &str) -> SpaceEditorsQuery {
     SpaceEditorsQuery::new(neo4j.clone(), space_id.to_string())
 }
 
+/// Counts the current editors of a space
+pub async fn editor_count(neo4j: &neo4rs::Graph, space_id: &str) -> Result<u64, DatabaseError> {
+    const QUERY: &str = r#"
+        MATCH (:Entity) -[r:RELATION {relation_type: $editor, space_id: $space_id}]-> (:Entity {id: $space})
+        WHERE r.max_version IS NULL
+        RETURN count(r) AS count
+        "#;
+
+    #[derive(Deserialize)]
+    struct Row {
+        count: u64,
+    }
+
+    let query = neo4rs::query(QUERY)
+        .param("editor", indexer_ids::EDITOR_RELATION)
+        .param("space_id", indexer_ids::INDEXER_SPACE_ID)
+        .param("space", space_id);
+
+    let row = transaction::execute(neo4j, "space::editor_count", query)
+        .await?
+        .into_stream_as::<Row>()
+        .try_next()
+        .await?;
+
+    Ok(row.map(|row| row.count).unwrap_or_default())
+}
+
 /// Find all parent spaces of a given space
-pub fn parent_spaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> ParentSpacesQuery<T> {
-    ParentSpacesQuery::new(neo4j.clone(), space_id.to_string())
+pub fn parent_spaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> RelatedSpacesQuery<T> {
+    RelatedSpacesQuery::new(
+        neo4j.clone(),
+        "space::parent_spaces",
+        space_id.to_string(),
+        indexer_ids::PARENT_SPACE,
+        RelationDirection::Outgoing,
+    )
 }
 
 /// Find all subspaces of a given space
-pub fn subspaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> SubspacesQuery<T> {
-    SubspacesQuery::new(neo4j.clone(), space_id.to_string())
+pub fn subspaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> RelatedSpacesQuery<T> {
+    RelatedSpacesQuery::new(
+        neo4j.clone(),
+        "space::subspaces",
+        space_id.to_string(),
+        indexer_ids::PARENT_SPACE,
+        RelationDirection::Incoming,
+    )
+}
+
+/// Find the spaces a given space succeeds, i.e.: the space it was upgraded from,
+/// the space that one was upgraded from, etc.
+pub fn predecessors<T>(neo4j: &neo4rs::Graph, space_id: &str) -> RelatedSpacesQuery<T> {
+    RelatedSpacesQuery::new(
+        neo4j.clone(),
+        "space::predecessors",
+        space_id.to_string(),
+        indexer_ids::SUCCESSOR_SPACE,
+        RelationDirection::Incoming,
+    )
+    .include_start(false)
+}
+
+/// Find the spaces succeeding a given space, i.e.: the spaces it was upgraded to,
+/// the spaces those were upgraded to, etc.
+pub fn successors<T>(neo4j: &neo4rs::Graph, space_id: &str) -> RelatedSpacesQuery<T> {
+    RelatedSpacesQuery::new(
+        neo4j.clone(),
+        "space::successors",
+        space_id.to_string(),
+        indexer_ids::SUCCESSOR_SPACE,
+        RelationDirection::Outgoing,
+    )
+    .include_start(false)
 }
 
 /// Find all types defined in a space
@@ -268,6 +371,8 @@ pub struct SpaceBuilder {
     voting_plugin_address: Option<String>,
     member_access_plugin: Option<String>,
     personal_space_admin_plugin: Option<String>,
+    archived: Option<bool>,
+    voting_settings: Option<VotingSettings>,
 }
 
 impl SpaceBuilder {
@@ -281,6 +386,8 @@ impl SpaceBuilder {
             voting_plugin_address: None,
             member_access_plugin: None,
             personal_space_admin_plugin: None,
+            archived: None,
+            voting_settings: None,
         }
     }
 
@@ -319,6 +426,16 @@ impl SpaceBuilder {
         self
     }
 
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.archived = Some(archived);
+        self
+    }
+
+    pub fn voting_settings(mut self, voting_settings: VotingSettings) -> Self {
+        self.voting_settings = Some(voting_settings);
+        self
+    }
+
     pub fn build(self) -> Entity<Space> {
         Entity::new(
             &self.id,
@@ -330,6 +447,13 @@ impl SpaceBuilder {
                 voting_plugin_address: self.voting_plugin_address,
                 member_access_plugin: self.member_access_plugin,
                 personal_space_admin_plugin: self.personal_space_admin_plugin,
+                archived: self.archived,
+                support_threshold: self
+                    .voting_settings
+                    .map(|settings| settings.support_threshold),
+                min_participation: self
+                    .voting_settings
+                    .map(|settings| settings.min_participation),
             },
         )
         .with_type(system_ids::SPACE_TYPE)
@@ -377,3 +501,29 @@ impl ParentSpace {
         .await
     }
 }
+
+/// Successor space relation (for space upgrades).
+/// Space > SUCCESSOR_SPACE > Space
+#[derive(Clone)]
+#[grc20_core::relation]
+#[grc20(relation_type = indexer_ids::SUCCESSOR_SPACE)]
+pub struct SuccessorSpace;
+
+impl SuccessorSpace {
+    pub fn generate_id(space_id: &str, successor_space_id: &str) -> String {
+        ids::create_id_from_unique_string(format!(
+            "SUCCESSOR_SPACE:{space_id}:{successor_space_id}"
+        ))
+    }
+
+    pub fn new(space_id: &str, successor_space_id: &str) -> Relation<Self, EntityNodeRef> {
+        Relation::new(
+            Self::generate_id(space_id, successor_space_id),
+            space_id,
+            successor_space_id,
+            indexer_ids::SUCCESSOR_SPACE,
+            "0",
+            Self,
+        )
+    }
+}
@@ -7,7 +7,7 @@ use grc20_core::{
     neo4rs, system_ids,
 };
 
-use super::ParentSpacesQuery;
+use super::parent_spaces;
 
 /// Query to find all types defined in a space
 pub struct FindSpaceTypeQuery {
@@ -39,14 +39,13 @@ impl Query<Option<EntityNode>> for FindSpaceTypeQuery {
         let mut spaces = vec![self.space_id.clone()];
 
         if !self.strict {
-            let parent_spaces: Vec<String> =
-                ParentSpacesQuery::new(self.neo4j.clone(), self.space_id.clone())
-                    .max_depth(None)
-                    .send()
-                    .await?
-                    .map_ok(|ranking| ranking.space_id)
-                    .try_collect()
-                    .await?;
+            let parent_spaces: Vec<String> = parent_spaces(&self.neo4j, &self.space_id)
+                .max_depth(None)
+                .send()
+                .await?
+                .map_ok(|ranking| ranking.space_id)
+                .try_collect()
+                .await?;
 
             spaces.extend(parent_spaces);
         }
@@ -120,14 +119,13 @@ impl QueryStream<EntityNode> for FindSpaceTypesQuery {
         let mut spaces = vec![self.space_id.clone()];
 
         if !self.strict {
-            let parent_spaces: Vec<String> =
-                ParentSpacesQuery::new(self.neo4j.clone(), self.space_id.clone())
-                    .max_depth(None)
-                    .send()
-                    .await?
-                    .map_ok(|ranking| ranking.space_id)
-                    .try_collect()
-                    .await?;
+            let parent_spaces: Vec<String> = parent_spaces(&self.neo4j, &self.space_id)
+                .max_depth(None)
+                .send()
+                .await?
+                .map_ok(|ranking| ranking.space_id)
+                .try_collect()
+                .await?;
 
             spaces.extend(parent_spaces);
         }
@@ -1,8 +1,12 @@
 //! This module contains models reserved for use by the KG Indexer.
 
 use grc20_core::{
+    error::DatabaseError,
     ids, indexer_ids,
-    mapping::{self, entity::EntityNodeRef, Relation, TriplesConversionError},
+    mapping::{
+        self, entity::EntityNodeRef, query_utils::Query, triple, Relation, TriplesConversionError,
+    },
+    neo4rs,
 };
 
 /// A vote cast by a user on a proposal.
@@ -36,10 +40,31 @@ impl VoteCast {
             Self { vote_type },
         )
     }
+
+    /// Returns the current vote of the account `account_id` on the proposal `proposal_id`
+    pub async fn find_vote_type(
+        neo4j: &neo4rs::Graph,
+        account_id: &str,
+        proposal_id: &str,
+    ) -> Result<Option<VoteType>, DatabaseError> {
+        triple::find_one(
+            neo4j,
+            indexer_ids::VOTE_TYPE_ATTRIBUTE,
+            Self::new_id(account_id, proposal_id),
+            indexer_ids::INDEXER_SPACE_ID,
+            None,
+        )
+        .send()
+        .await?
+        .map(|triple| triple.value.try_into())
+        .transpose()
+        .map_err(DatabaseError::from)
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VoteType {
+    Abstain,
     Accept,
     Reject,
 }
@@ -49,6 +74,7 @@ impl TryFrom<u64> for VoteType {
 
     fn try_from(vote: u64) -> Result<Self, Self::Error> {
         match vote {
+            1 => Ok(Self::Abstain),
             2 => Ok(Self::Accept),
             3 => Ok(Self::Reject),
             _ => Err(format!("Invalid vote type: {}", vote)),
@@ -59,6 +85,7 @@ impl TryFrom<u64> for VoteType {
 impl From<VoteType> for mapping::Value {
     fn from(vote_type: VoteType) -> Self {
         match vote_type {
+            VoteType::Abstain => mapping::Value::text("ABSTAIN"),
             VoteType::Accept => mapping::Value::text("ACCEPT"),
             VoteType::Reject => mapping::Value::text("REJECT"),
         }
@@ -70,6 +97,7 @@ impl TryFrom<mapping::Value> for VoteType {
 
     fn try_from(value: mapping::Value) -> Result<Self, Self::Error> {
         match (value.value_type, value.value.as_str()) {
+            (mapping::ValueType::Text, "ABSTAIN") => Ok(Self::Abstain),
             (mapping::ValueType::Text, "ACCEPT") => Ok(Self::Accept),
             (mapping::ValueType::Text, "REJECT") => Ok(Self::Reject),
             (value_type, _) => Err(TriplesConversionError::InvalidValue(format!(
@@ -2,6 +2,8 @@ use std::{fs, path::Path};
 
 use prost::Message;
 
+pub mod metrics;
+
 const IPFS_CACHE_DIR: &str = "ipfs-cache";
 
 #[derive(Debug, thiserror::Error)]
@@ -44,15 +46,19 @@ impl IpfsClient {
 
         if cache && cache_path.exists() {
             tracing::info!("Cache hit for {}", hash);
+            metrics::CACHE_REQUESTS.with_label_values(&["hit"]).inc();
             let cached_data = fs::read(&cache_path)?;
             return Ok(cached_data);
-        } else {
+        } else if cache {
             tracing::info!("Cache miss for {}", hash);
+            metrics::CACHE_REQUESTS.with_label_values(&["miss"]).inc();
         }
 
+        let timer = metrics::FETCH_DURATION.start_timer();
         let url = format!("{}{}", self.url, hash);
         let res = self.client.get(&url).send().await?;
         let bytes = res.bytes().await?;
+        timer.observe_duration();
 
         // Cache the result
         if cache {
@@ -0,0 +1,19 @@
+use lazy_static::lazy_static;
+use prometheus::{register_histogram, register_int_counter_vec, Histogram, IntCounterVec};
+
+lazy_static! {
+    /// Time spent fetching files from the IPFS gateway (cache hits are not included).
+    pub static ref FETCH_DURATION: Histogram = register_histogram!(
+        "ipfs_fetch_duration_seconds",
+        "Time spent fetching files from the IPFS gateway"
+    )
+    .expect("Failed to create ipfs_fetch_duration_seconds histogram");
+
+    /// Lookups in the local IPFS cache, labelled by `result` (`hit` or `miss`).
+    pub static ref CACHE_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "ipfs_cache_requests_total",
+        "Number of lookups in the local IPFS cache",
+        &["result"]
+    )
+    .expect("Failed to create ipfs_cache_requests_total counter");
+}
@@ -1,12 +1,12 @@
 use clap::{Args, Parser};
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use embedding::{Embedder, FastEmbedder};
 use futures::{TryStreamExt, future::join_all};
 use grc20_core::{
     entity::{self, Entity, EntityFilter, EntityNode, EntityRelationFilter, TypesFilter},
     mapping::{Attributes, Query, QueryStream, RelationEdge, prop_filter},
     neo4rs, relation, system_ids,
 };
-use grc20_sdk::models::BaseEntity;
+use grc20_sdk::models::{BaseEntity, EmbeddingConfig};
 use rmcp::{
     Error as McpError, RoleServer, ServerHandler,
     model::*,
@@ -43,6 +43,15 @@ async fn main() -> anyhow::Result<()> {
     )
     .await?;
 
+    let embedder: Arc<dyn Embedder> = Arc::new(FastEmbedder::new(&args.embedding_model)?);
+    // Search results would be meaningless with another model than the index's
+    EmbeddingConfig::check(&neo4j, embedder.model(), embedder.dim()).await?;
+    tracing::info!(
+        "Using embedding model {} (dim = {})",
+        embedder.model(),
+        embedder.dim()
+    );
+
     let config = SseServerConfig {
         bind: BIND_ADDRESS.parse()?,
         sse_path: "/sse".to_string(),
@@ -70,7 +79,7 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
-    let ct = sse_server.with_service(move || KnowledgeGraph::new(neo4j.clone()));
+    let ct = sse_server.with_service(move || KnowledgeGraph::new(neo4j.clone(), embedder.clone()));
 
     tokio::signal::ctrl_c().await?;
     ct.cancel();
@@ -83,27 +92,23 @@ pub struct StructRequest {
     pub b: i32,
 }
 
-const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
-
 #[derive(Clone)]
 pub struct KnowledgeGraph {
     neo4j: neo4rs::Graph,
-    pub embedding_model: Arc<TextEmbedding>,
+    pub embedder: Arc<dyn Embedder>,
 }
 
 #[tool(tool_box)]
 impl KnowledgeGraph {
     #[allow(dead_code)]
-    pub fn new(neo4j: neo4rs::Graph) -> Self {
-        Self {
-            neo4j,
-            embedding_model: Arc::new(
-                TextEmbedding::try_new(
-                    InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
-                )
-                .expect("Failed to initialize embedding model"),
-            ),
-        }
+    pub fn new(neo4j: neo4rs::Graph, embedder: Arc<dyn Embedder>) -> Self {
+        Self { neo4j, embedder }
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f64>, McpError> {
+        self.embedder.embed_one(text).map_err(|e| {
+            McpError::internal_error("embedding_failed", Some(json!({ "error": e.to_string() })))
+        })
     }
 
     fn _create_resource_text(&self, uri: &str, name: &str) -> Resource {
@@ -117,15 +122,7 @@ impl KnowledgeGraph {
         #[schemars(description = "The query string to search for types")]
         query: String,
     ) -> Result<CallToolResult, McpError> {
-        let embedding = self
-            .embedding_model
-            .embed(vec![&query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let embedding = self.embed(&query)?;
 
         let results = entity::search::<Entity<BaseEntity>>(&self.neo4j, embedding)
             .filter(
@@ -175,15 +172,7 @@ impl KnowledgeGraph {
         #[schemars(description = "The query string to search for relation types")]
         query: String,
     ) -> Result<CallToolResult, McpError> {
-        let embedding = self
-            .embedding_model
-            .embed(vec![&query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let embedding = self.embed(&query)?;
 
         let results = entity::search::<Entity<BaseEntity>>(&self.neo4j, embedding)
             .filter(entity::EntityFilter::default().relations(
@@ -232,15 +221,7 @@ impl KnowledgeGraph {
         #[schemars(description = "The query string to search for properties")]
         query: String,
     ) -> Result<CallToolResult, McpError> {
-        let embedding = self
-            .embedding_model
-            .embed(vec![&query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let embedding = self.embed(&query)?;
 
         let results = entity::search::<Entity<BaseEntity>>(&self.neo4j, embedding)
             .filter(
@@ -440,15 +421,7 @@ impl KnowledgeGraph {
         #[schemars(description = "The value of the attribute of an Entity")]
         attribute_value: String,
     ) -> Result<CallToolResult, McpError> {
-        let embedding = self
-            .embedding_model
-            .embed(vec![&attribute_value], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let embedding = self.embed(&attribute_value)?;
 
         let entities = entity::search::<Entity<BaseEntity>>(&self.neo4j, embedding)
             .filter(entity::EntityFilter::default())
@@ -532,6 +505,10 @@ impl ServerHandler for KnowledgeGraph {
 struct AppArgs {
     #[clap(flatten)]
     neo4j_args: Neo4jArgs,
+
+    /// Embedding model used for search. Must match the model the index was built with.
+    #[arg(long, env = "EMBEDDING_MODEL", default_value = embedding::DEFAULT_MODEL)]
+    embedding_model: String,
 }
 
 #[derive(Debug, Args)]
@@ -73,8 +73,7 @@ async fn main() -> anyhow::Result<()> {
         .await?;
     println!("Space created");
 
-    sink.handle_edits_published(edit_events, &[], &block)
-        .await?;
+    sink.handle_edits_published(edit_events, &block).await?;
     println!("Time taken: {:?}", time.elapsed());
 
     Ok(())
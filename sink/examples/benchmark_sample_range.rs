@@ -6,7 +6,11 @@ use grc20_core::{
     neo4rs,
 };
 use sink::events::EventHandler;
-use substreams_utils::sink::Sink;
+use substreams_utils::{
+    sink::Sink,
+    substreams::{EndpointConfig, EndpointPool},
+    BlockRange,
+};
 use testcontainers::{
     core::{IntoContainerPort, WaitFor},
     runners::AsyncRunner,
@@ -80,11 +84,21 @@ async fn main() -> anyhow::Result<()> {
 
     let sink = EventHandler::new(neo4j, None)?;
 
-    let endpoint_url =
-        env::var("SUBSTREAMS_ENDPOINT_URL").expect("SUBSTREAMS_ENDPOINT_URL not set");
+    let endpoint = EndpointConfig {
+        url: env::var("SUBSTREAMS_ENDPOINT_URL").expect("SUBSTREAMS_ENDPOINT_URL not set"),
+        token: env::var("SUBSTREAMS_API_TOKEN").ok(),
+    };
 
-    sink.run(&endpoint_url, PKG_FILE, MODULE_NAME, 515, 1000, Some(32))
-        .await?;
+    sink.run(
+        EndpointPool::new(&[endpoint]).await?,
+        PKG_FILE,
+        MODULE_NAME,
+        BlockRange::new(515, 1000),
+        &[],
+        false,
+        Some(32),
+    )
+    .await?;
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use embedding::{Embedder, FastEmbedder};
 use grc20_core::{
     block::BlockMetadata,
     entity::EntityNodeRef,
@@ -7,8 +7,6 @@ use grc20_core::{
     neo4rs, relation, system_ids,
 };
 
-const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
-
 const NEO4J_URL: &str = "bolt://localhost:7687";
 const NEO4J_USER: &str = "neo4j";
 const NEO4J_PASSWORD: &str = "password";
@@ -72,12 +70,10 @@ async fn main() -> anyhow::Result<()> {
         .await
         .expect("Failed to connect to Neo4j");
 
-    let embedding_model = TextEmbedding::try_new(
-        InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
-    )?;
+    let embedding_model = FastEmbedder::new(embedding::DEFAULT_MODEL)?;
 
     // Reset and bootstrap the database
-    reset_db(&neo4j).await?;
+    reset_db(&neo4j, embedding_model.dim()).await?;
     bootstrap(&neo4j, &embedding_model).await?;
 
     // Create some common types
@@ -325,7 +321,7 @@ async fn main() -> anyhow::Result<()> {
 
 pub async fn bootstrap(
     neo4j: &neo4rs::Graph,
-    embedding_model: &TextEmbedding,
+    embedding_model: &dyn Embedder,
 ) -> anyhow::Result<()> {
     let triples = vec![
         // Value types
@@ -398,18 +394,17 @@ pub async fn bootstrap(
 
     // Compute embeddings
     let embeddings =
-        embedding_model.embed(triples.iter().map(|t| &t.value.value).collect(), None)?;
+        embedding_model.embed(triples.iter().map(|t| t.value.value.clone()).collect())?;
 
     let triples_with_embeddings = triples
         .into_iter()
         .zip(embeddings)
         .map(|(triple, embedding)| {
-            let embedding = embedding.into_iter().map(|e| e as f64).collect();
             Triple::with_embedding(triple.entity, triple.attribute, triple.value, embedding)
         });
 
     triple::insert_many(
-        &neo4j,
+        neo4j,
         &BlockMetadata::default(),
         system_ids::ROOT_SPACE_ID,
         DEFAULT_VERSION,
@@ -422,7 +417,7 @@ pub async fn bootstrap(
     // Create properties
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Properties",
         system_ids::RELATION_SCHEMA_TYPE,
         Some(system_ids::ATTRIBUTE),
@@ -432,7 +427,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Types",
         system_ids::RELATION_SCHEMA_TYPE,
         Some(system_ids::SCHEMA_TYPE),
@@ -442,7 +437,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Value Type",
         system_ids::RELATION_SCHEMA_TYPE,
         None::<&str>,
@@ -452,7 +447,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Relation type attribute",
         system_ids::RELATION_SCHEMA_TYPE,
         None::<&str>,
@@ -462,7 +457,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Relation index",
         system_ids::TEXT,
         None::<&str>,
@@ -472,7 +467,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Relation value type",
         system_ids::RELATION_SCHEMA_TYPE,
         Some(system_ids::SCHEMA_TYPE),
@@ -482,7 +477,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Name",
         system_ids::TEXT,
         None::<&str>,
@@ -492,7 +487,7 @@ pub async fn bootstrap(
 
     create_property(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Description",
         system_ids::TEXT,
         None::<&str>,
@@ -503,7 +498,7 @@ pub async fn bootstrap(
     // Create types
     create_type(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Type",
         [system_ids::SCHEMA_TYPE],
         [
@@ -518,7 +513,7 @@ pub async fn bootstrap(
 
     create_type(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Relation schema type",
         [system_ids::RELATION_SCHEMA_TYPE],
         [system_ids::RELATION_VALUE_RELATIONSHIP_TYPE],
@@ -528,7 +523,7 @@ pub async fn bootstrap(
 
     create_type(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Attribute",
         [system_ids::SCHEMA_TYPE],
         [
@@ -542,7 +537,7 @@ pub async fn bootstrap(
 
     create_type(
         neo4j,
-        &embedding_model,
+        embedding_model,
         "Relation instance type",
         [system_ids::RELATION_TYPE],
         [
@@ -558,7 +553,7 @@ pub async fn bootstrap(
 
 pub async fn create_entity(
     neo4j: &neo4rs::Graph,
-    embedding_model: &TextEmbedding,
+    embedding_model: &dyn Embedder,
     name: impl Into<String>,
     description: Option<&str>,
     types: impl IntoIterator<Item = &str>,
@@ -576,14 +571,7 @@ pub async fn create_entity(
             &entity_id,
             system_ids::NAME_ATTRIBUTE,
             name.clone(),
-            embedding_model
-                .embed(vec![name], Some(1))
-                .unwrap_or(vec![Vec::<f32>::new()])
-                .get(0)
-                .unwrap_or(&Vec::<f32>::new())
-                .iter()
-                .map(|&x| x as f64)
-                .collect(),
+            embedding_model.embed_one(&name).unwrap_or_default(),
         )])
         .send()
         .await?;
@@ -638,7 +626,7 @@ pub async fn create_entity(
 /// Creates a type with the given name, types, and properties.
 pub async fn create_type(
     neo4j: &neo4rs::Graph,
-    embedding_model: &TextEmbedding,
+    embedding_model: &dyn Embedder,
     name: impl Into<String>,
     types: impl IntoIterator<Item = &str>,
     properties: impl IntoIterator<Item = &str>,
@@ -659,14 +647,7 @@ pub async fn create_type(
             &type_id,
             system_ids::NAME_ATTRIBUTE,
             name.clone(),
-            embedding_model
-                .embed(vec![name], Some(1))
-                .unwrap_or(vec![Vec::<f32>::new()])
-                .get(0)
-                .unwrap_or(&Vec::<f32>::new())
-                .iter()
-                .map(|&x| x as f64)
-                .collect(),
+            embedding_model.embed_one(&name).unwrap_or_default(),
         )])
         .send()
         .await?;
@@ -701,7 +682,7 @@ pub async fn create_type(
 /// Note: if that is the case, then `value_type` should be the system_ids::RELATION_SCHEMA_TYPE type).
 pub async fn create_property(
     neo4j: &neo4rs::Graph,
-    embedding_model: &TextEmbedding,
+    embedding_model: &dyn Embedder,
     name: impl Into<String>,
     value_type: impl Into<String>,
     relation_value_type: Option<impl Into<String>>,
@@ -718,14 +699,7 @@ pub async fn create_property(
             &property_id,
             system_ids::NAME_ATTRIBUTE,
             string_name.clone(),
-            embedding_model
-                .embed(vec![string_name], Some(1))
-                .unwrap_or(vec![Vec::<f32>::new()])
-                .get(0)
-                .unwrap_or(&Vec::<f32>::new())
-                .iter()
-                .map(|&x| x as f64)
-                .collect(),
+            embedding_model.embed_one(&string_name).unwrap_or_default(),
         )])
         .send()
         .await?;
@@ -801,9 +775,7 @@ pub async fn set_types(
     Ok(())
 }
 
-pub async fn reset_db(neo4j: &neo4rs::Graph) -> anyhow::Result<()> {
-    let embedding_dim = TextEmbedding::get_model_info(&EMBEDDING_MODEL)?.dim;
-
+pub async fn reset_db(neo4j: &neo4rs::Graph, embedding_dim: usize) -> anyhow::Result<()> {
     // Delete indexes
     neo4j
         .run(neo4rs::query("DROP INDEX entity_id_index IF EXISTS"))
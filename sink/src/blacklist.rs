@@ -1,18 +1,243 @@
+//! Blacklist of spaces, authors and DAOs whose edits are skipped by the sink.
+//!
+//! The blacklist is read from a YAML file which is reloaded whenever it changes, e.g.:
+//! ```yaml
+//! spaces:
+//!   - 25omwWh6HYgeRQKCaSpVpa
+//! authors:
+//!   - "0x84713663033dC5ba5699280728545df11e76BCC1"
+//! daos:
+//!   - "0xd3a0Fa4e7A8a3C8c9a3BB0AdD0E4c3C5fd5d6D3b"
+//! ```
+//! Author and DAO addresses are compared case-insensitively.
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
 use serde::Deserialize;
+use tokio::sync::Mutex;
 
-const SPACE_BLACKLIST_FILE: &str = "spaces_blacklist.yaml";
+pub const DEFAULT_BLACKLIST_FILE: &str = "spaces_blacklist.yaml";
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct SpacesBlacklist {
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Blacklist {
+    /// Blacklisted space IDs
     pub spaces: Vec<String>,
+    /// Blacklisted author addresses
+    pub authors: Vec<String>,
+    /// Blacklisted DAO addresses
+    pub daos: Vec<String>,
+}
+
+impl Blacklist {
+    pub fn is_empty(&self) -> bool {
+        self.spaces.is_empty() && self.authors.is_empty() && self.daos.is_empty()
+    }
+
+    /// Returns the blacklist entry matching an edit published to `space_id` (by the DAO
+    /// `dao_address`) and authored by `authors`, or `None` if it is not blacklisted.
+    /// The edit is skipped if any of its authors is blacklisted.
+    pub fn check(
+        &self,
+        space_id: &str,
+        authors: &[String],
+        dao_address: &str,
+    ) -> Option<BlacklistMatch> {
+        let blacklisted_author = authors.iter().find(|author| {
+            self.authors
+                .iter()
+                .any(|address| address.eq_ignore_ascii_case(author))
+        });
+
+        if self.spaces.iter().any(|space| space == space_id) {
+            Some(BlacklistMatch::Space(space_id.to_string()))
+        } else if let Some(author) = blacklisted_author {
+            Some(BlacklistMatch::Author(author.clone()))
+        } else if self
+            .daos
+            .iter()
+            .any(|address| address.eq_ignore_ascii_case(dao_address))
+        {
+            Some(BlacklistMatch::Dao(dao_address.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Blacklist entry matching an edit. Its display is the reason why the edit is skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlacklistMatch {
+    /// The space the edit was published to is blacklisted
+    Space(String),
+    /// One of the authors of the edit is blacklisted
+    Author(String),
+    /// The DAO of the space is blacklisted
+    Dao(String),
+}
+
+impl fmt::Display for BlacklistMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlacklistMatch::Space(space_id) => write!(f, "Space {space_id} is blacklisted"),
+            BlacklistMatch::Author(author) => write!(f, "Author {author} is blacklisted"),
+            BlacklistMatch::Dao(dao_address) => write!(f, "DAO {dao_address} is blacklisted"),
+        }
+    }
 }
 
-pub fn load() -> anyhow::Result<Option<SpacesBlacklist>> {
-    if !std::path::Path::new(SPACE_BLACKLIST_FILE).exists() {
+pub async fn load(path: impl AsRef<Path>) -> anyhow::Result<Option<Blacklist>> {
+    if !tokio::fs::try_exists(&path).await? {
         return Ok(None);
     }
 
-    let blacklist = std::fs::read_to_string(SPACE_BLACKLIST_FILE)?;
-    let blacklist: SpacesBlacklist = serde_yaml::from_str(&blacklist)?;
+    let blacklist = tokio::fs::read_to_string(path).await?;
+    let blacklist: Blacklist = serde_yaml::from_str(&blacklist)?;
     Ok(Some(blacklist))
 }
+
+/// Blacklist loaded from a file, which is reloaded when the file is modified
+/// (or cleared when the file is removed). The file is only read when needed, i.e.:
+/// once per block (see [`BlacklistFile::current`]).
+pub struct BlacklistFile {
+    path: PathBuf,
+    state: Mutex<BlacklistState>,
+}
+
+struct BlacklistState {
+    /// Modification time of the file when it was last read (`None` if it was not read
+    /// yet or did not exist)
+    modified: Option<Option<SystemTime>>,
+    blacklist: Arc<Blacklist>,
+}
+
+impl BlacklistFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::new(BlacklistState {
+                modified: None,
+                blacklist: Arc::new(Blacklist::default()),
+            }),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the current blacklist, reloading the file first if it changed
+    /// since it was last read.
+    pub async fn current(&self) -> Arc<Blacklist> {
+        let modified = modified_time(&self.path).await;
+        let mut state = self.state.lock().await;
+
+        if state.modified != Some(modified) {
+            state.modified = Some(modified);
+            if let Some(blacklist) = Self::reload(&self.path).await {
+                state.blacklist = Arc::new(blacklist);
+            }
+        }
+
+        state.blacklist.clone()
+    }
+
+    /// Loads the blacklist at `path`. Returns `None` if the file is invalid, in
+    /// which case the previous blacklist should be kept.
+    async fn reload(path: &Path) -> Option<Blacklist> {
+        match load(path).await {
+            Ok(Some(blacklist)) => {
+                tracing::info!(
+                    "Loaded blacklist {}: spaces: [{}], authors: [{}], DAOs: [{}]",
+                    path.display(),
+                    blacklist.spaces.join(", "),
+                    blacklist.authors.join(", "),
+                    blacklist.daos.join(", ")
+                );
+                Some(blacklist)
+            }
+            Ok(None) => {
+                tracing::info!("No blacklist found at {}", path.display());
+                Some(Blacklist::default())
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Error loading blacklist {}, skipping: {:?}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+async fn modified_time(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let blacklist: Blacklist = serde_yaml::from_str(
+            r#"
+spaces:
+  - space
+authors:
+  - "0xA1"
+daos:
+  - "0xAbCdEf0000000000000000000000000000000000"
+"#,
+        )
+        .unwrap();
+
+        let authors = |authors: &[&str]| authors.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            blacklist.check("space", &authors(&["0x01"]), "0x02"),
+            Some(BlacklistMatch::Space("space".to_string()))
+        );
+        assert_eq!(
+            blacklist.check("other_space", &authors(&["0x01", "0xa1"]), "0x02"),
+            Some(BlacklistMatch::Author("0xa1".to_string()))
+        );
+        assert_eq!(
+            blacklist.check(
+                "other_space",
+                &authors(&["0x01"]),
+                "0xabcdef0000000000000000000000000000000000"
+            ),
+            Some(BlacklistMatch::Dao(
+                "0xabcdef0000000000000000000000000000000000".to_string()
+            ))
+        );
+        assert_eq!(
+            blacklist.check("other_space", &authors(&["0x01"]), "0x02"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_match_reason() {
+        assert_eq!(
+            BlacklistMatch::Author("0xa1".to_string()).to_string(),
+            "Author 0xa1 is blacklisted"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let blacklist = BlacklistFile::new("missing_blacklist.yaml");
+        assert!(blacklist.current().await.is_empty());
+    }
+}
@@ -54,6 +54,11 @@ pub fn triples() -> Vec<Triple> {
             system_ids::NAME_ATTRIBUTE,
             "Space Kind",
         ),
+        Triple::new(
+            indexer_ids::SPACE_ARCHIVED,
+            system_ids::NAME_ATTRIBUTE,
+            "Space Archived",
+        ),
         // Member and Editor relations
         Triple::new(
             indexer_ids::MEMBER_RELATION,
@@ -71,6 +76,12 @@ pub fn triples() -> Vec<Triple> {
             system_ids::NAME_ATTRIBUTE,
             "Parent Space",
         ),
+        // Successor space
+        Triple::new(
+            indexer_ids::SUCCESSOR_SPACE,
+            system_ids::NAME_ATTRIBUTE,
+            "Successor Space",
+        ),
         // Voting
         Triple::new(
             indexer_ids::VOTE_CAST_TYPE,
@@ -88,6 +99,31 @@ pub fn triples() -> Vec<Triple> {
             system_ids::NAME_ATTRIBUTE,
             "Proposal Type",
         ),
+        Triple::new(
+            indexer_ids::PROPOSAL_STATUS_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Proposal Status",
+        ),
+        Triple::new(
+            indexer_ids::PROPOSAL_ACCEPT_VOTES_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Accept Votes",
+        ),
+        Triple::new(
+            indexer_ids::PROPOSAL_REJECT_VOTES_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Reject Votes",
+        ),
+        Triple::new(
+            indexer_ids::PROPOSAL_ABSTAIN_VOTES_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Abstain Votes",
+        ),
+        Triple::new(
+            indexer_ids::PROPOSAL_VOTER_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Voter Count",
+        ),
         Triple::new(
             indexer_ids::ADD_MEMBER_PROPOSAL,
             system_ids::NAME_ATTRIBUTE,
@@ -123,6 +159,11 @@ pub fn triples() -> Vec<Triple> {
             system_ids::NAME_ATTRIBUTE,
             "Edit Proposal",
         ),
+        Triple::new(
+            indexer_ids::SYNTHETIC_PROPOSAL,
+            system_ids::NAME_ATTRIBUTE,
+            "Synthetic Proposal",
+        ),
         // Proposed account and subspace
         Triple::new(
             indexer_ids::PROPOSED_ACCOUNT,
@@ -145,6 +186,79 @@ pub fn triples() -> Vec<Triple> {
             system_ids::NAME_ATTRIBUTE,
             "Proposals",
         ),
+        // Failed edits
+        Triple::new(
+            indexer_ids::FAILED_EDIT_TYPE,
+            system_ids::NAME_ATTRIBUTE,
+            "Failed Edit",
+        ),
+        Triple::new(
+            indexer_ids::FAILED_EDIT_SPACE_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Failed Edit Space",
+        ),
+        Triple::new(
+            indexer_ids::FAILED_EDIT_REASON_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Failed Edit Reason",
+        ),
+        // Skipped edits
+        Triple::new(
+            indexer_ids::SKIPPED_EDIT_TYPE,
+            system_ids::NAME_ATTRIBUTE,
+            "Skipped Edit",
+        ),
+        Triple::new(
+            indexer_ids::SKIPPED_EDIT_SPACE_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Skipped Edit Space",
+        ),
+        Triple::new(
+            indexer_ids::SKIPPED_EDIT_AUTHOR_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Skipped Edit Author",
+        ),
+        Triple::new(
+            indexer_ids::SKIPPED_EDIT_DAO_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Skipped Edit DAO",
+        ),
+        Triple::new(
+            indexer_ids::SKIPPED_EDIT_REASON_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Skipped Edit Reason",
+        ),
+        // Edits
+        Triple::new(
+            indexer_ids::EDIT_SET_TRIPLE_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Set Triple Count",
+        ),
+        Triple::new(
+            indexer_ids::EDIT_DELETE_TRIPLE_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Delete Triple Count",
+        ),
+        Triple::new(
+            indexer_ids::EDIT_CREATE_RELATION_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Create Relation Count",
+        ),
+        Triple::new(
+            indexer_ids::EDIT_DELETE_RELATION_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Delete Relation Count",
+        ),
+        Triple::new(
+            indexer_ids::EDIT_DELETE_ENTITY_COUNT_ATTRIBUTE,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Delete Entity Count",
+        ),
+        Triple::new(
+            indexer_ids::EDIT_AUTHOR,
+            system_ids::NAME_ATTRIBUTE,
+            "Edit Author",
+        ),
         // Indexer version
         Triple::new(
             indexer_ids::CURSOR_ID,
@@ -2,3 +2,26 @@
 // mod bootstrap_templates;
 pub mod boostrap_indexer;
 pub mod constants;
+
+use grc20_core::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{query_utils::Query, triple},
+    neo4rs,
+};
+
+/// Inserts the indexer entities (i.e.: the types and attributes of the indexer space).
+/// Idempotent, so it is run on every start to insert the entities added since the
+/// database was created.
+pub async fn bootstrap_indexer(neo4j: &neo4rs::Graph) -> Result<(), DatabaseError> {
+    triple::insert_many(
+        neo4j,
+        &BlockMetadata::default(),
+        indexer_ids::INDEXER_SPACE_ID,
+        "0",
+    )
+    .triples(boostrap_indexer::triples())
+    .send()
+    .await
+}
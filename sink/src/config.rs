@@ -0,0 +1,626 @@
+//! Configuration of the sink binary.
+//!
+//! Settings are read from a TOML file (optional), then overridden by environment
+//! variables and finally by command line flags, e.g.:
+//! ```toml
+//! [substreams]
+//! endpoint_url = "https://substreams.example.com"
+//! # Default token of the endpoints
+//! api_token = "..."
+//! # Switch to the next endpoint after this many consecutive failures
+//! failures_before_failover = 3
+//! package = "geo-substream.spkg"
+//! module = "geo_out"
+//! start_block = 880
+//! end_block = 0
+//! # Alternatively, a block range resolved against the initial block of the module
+//! # (e.g.: "1000:+500", "+10:", ":-"). Overrides `start_block` and `end_block`.
+//! # block_range = ":-"
+//! final_blocks_only = false
+//!
+//! # Parameters of the modules of the package, by module name
+//! [substreams.params]
+//! geo_out = "..."
+//!
+//! # Fallback endpoints, used in order after `endpoint_url`
+//! [[substreams.endpoints]]
+//! url = "https://backup.substreams.example.com"
+//! token = "..."
+//!
+//! [neo4j]
+//! uri = "neo4j://localhost:7687"
+//! user = "neo4j"
+//! pass = "neo4j"
+//!
+//! [http]
+//! port = 8081
+//! # Maximum age (in seconds) of the last processed block for the sink to be ready
+//! max_head_lag = 300
+//!
+//! # Blocks failing with a transient error (e.g.: Neo4j unavailable) are retried with
+//! # an exponential backoff (delays in seconds) before the sink gives up
+//! [retry]
+//! max_attempts = 5
+//! initial_delay = 1
+//! max_delay = 60
+//! ```
+//!
+//! Environment variables: `SUBSTREAMS_ENDPOINT_URL`, `SUBSTREAMS_API_TOKEN`, `SUBSTREAMS_PACKAGE`, `SUBSTREAMS_MODULE`,
+//! `SUBSTREAMS_START_BLOCK`, `SUBSTREAMS_END_BLOCK`, `SUBSTREAMS_BLOCK_RANGE`, `NEO4J_URI`, `NEO4J_USER`, `NEO4J_PASS`
+//! `KG_SINK_HTTP_PORT`, `KG_SINK_MAX_HEAD_LAG` and `KG_SINK_RETRY_MAX_ATTEMPTS`. Empty
+//! variables are ignored.
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use substreams_utils::{
+    substreams::{EndpointConfig, DEFAULT_FAILURES_BEFORE_FAILOVER},
+    BlockRange, RetryPolicy,
+};
+
+pub const DEFAULT_CONFIG_FILE: &str = "sink.toml";
+pub const DEFAULT_PACKAGE: &str = "geo-substream.spkg";
+pub const DEFAULT_MODULE: &str = "geo_out";
+pub const DEFAULT_START_BLOCK: u64 = 880;
+pub const DEFAULT_END_BLOCK: u64 = 0;
+pub const DEFAULT_HTTP_PORT: u16 = 8081;
+pub const DEFAULT_MAX_HEAD_LAG: u64 = 300;
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_RETRY_INITIAL_DELAY: u64 = 1;
+pub const DEFAULT_RETRY_MAX_DELAY: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid value {value:?} for environment variable {name}: {reason}")]
+    InvalidEnv {
+        name: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("Missing setting {setting} (set it in the config file or with {env})")]
+    Missing {
+        setting: &'static str,
+        env: &'static str,
+    },
+    #[error("Invalid setting {setting}: {reason}")]
+    Invalid {
+        setting: &'static str,
+        reason: String,
+    },
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub substreams: SubstreamsConfig,
+    pub neo4j: Neo4jConfig,
+    pub http: HttpConfig,
+    pub retry: RetryConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubstreamsConfig {
+    pub endpoint_url: Option<String>,
+    /// Token of the endpoints which do not have their own
+    pub api_token: Option<String>,
+    /// Additional endpoints the sink fails over to, after `endpoint_url`
+    pub endpoints: Vec<EndpointEntry>,
+    /// Number of consecutive failures of an endpoint before switching to the next one
+    pub failures_before_failover: u32,
+    /// Path or URL of the substreams package
+    pub package: String,
+    /// Name of the output module of the package
+    pub module: String,
+    pub start_block: u64,
+    /// Block at which to stop streaming (exclusive), or 0 to stream indefinitely
+    pub end_block: u64,
+    /// Block range (see [`BlockRange`]), overrides `start_block` and `end_block`
+    pub block_range: Option<String>,
+    /// Parameters of the modules, by module name
+    pub params: BTreeMap<String, String>,
+    /// Only stream final (irreversible) blocks
+    pub final_blocks_only: bool,
+}
+
+impl Default for SubstreamsConfig {
+    fn default() -> Self {
+        Self {
+            endpoint_url: None,
+            api_token: None,
+            endpoints: vec![],
+            failures_before_failover: DEFAULT_FAILURES_BEFORE_FAILOVER,
+            package: DEFAULT_PACKAGE.to_string(),
+            module: DEFAULT_MODULE.to_string(),
+            start_block: DEFAULT_START_BLOCK,
+            end_block: DEFAULT_END_BLOCK,
+            block_range: None,
+            params: BTreeMap::new(),
+            final_blocks_only: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointEntry {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Neo4jConfig {
+    pub uri: Option<String>,
+    pub user: String,
+    pub pass: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub port: u16,
+    /// Maximum age in seconds of the last processed block for the sink to be
+    /// reported as ready
+    pub max_head_lag: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_HTTP_PORT,
+            max_head_lag: DEFAULT_MAX_HEAD_LAG,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Maximum number of attempts per block, including the first one (1 disables
+    /// retries)
+    pub max_attempts: u32,
+    /// Delay in seconds before the first retry, doubled after each retry
+    pub initial_delay: u64,
+    /// Maximum delay in seconds between two attempts
+    pub max_delay: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_RETRY_MAX_ATTEMPTS,
+            initial_delay: DEFAULT_RETRY_INITIAL_DELAY,
+            max_delay: DEFAULT_RETRY_MAX_DELAY,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file at `path` and applies the environment overrides. If `path`
+    /// is `None`, [`DEFAULT_CONFIG_FILE`] is loaded if it exists.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        toml::from_str(&content).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Overrides the settings with the environment variables returned by `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        if let Some(endpoint_url) = var("SUBSTREAMS_ENDPOINT_URL") {
+            self.substreams.endpoint_url = Some(endpoint_url);
+        }
+        if let Some(api_token) = var("SUBSTREAMS_API_TOKEN") {
+            self.substreams.api_token = Some(api_token);
+        }
+        if let Some(package) = var("SUBSTREAMS_PACKAGE") {
+            self.substreams.package = package;
+        }
+        if let Some(module) = var("SUBSTREAMS_MODULE") {
+            self.substreams.module = module;
+        }
+        if let Some(start_block) = var("SUBSTREAMS_START_BLOCK") {
+            self.substreams.start_block = parse_env("SUBSTREAMS_START_BLOCK", start_block)?;
+        }
+        if let Some(end_block) = var("SUBSTREAMS_END_BLOCK") {
+            self.substreams.end_block = parse_env("SUBSTREAMS_END_BLOCK", end_block)?;
+        }
+        if let Some(block_range) = var("SUBSTREAMS_BLOCK_RANGE") {
+            self.substreams.block_range = Some(block_range);
+        }
+        if let Some(uri) = var("NEO4J_URI") {
+            self.neo4j.uri = Some(uri);
+        }
+        if let Some(user) = var("NEO4J_USER") {
+            self.neo4j.user = user;
+        }
+        if let Some(pass) = var("NEO4J_PASS") {
+            self.neo4j.pass = pass;
+        }
+        if let Some(port) = var("KG_SINK_HTTP_PORT") {
+            self.http.port = parse_env("KG_SINK_HTTP_PORT", port)?;
+        }
+        if let Some(max_head_lag) = var("KG_SINK_MAX_HEAD_LAG") {
+            self.http.max_head_lag = parse_env("KG_SINK_MAX_HEAD_LAG", max_head_lag)?;
+        }
+        if let Some(max_attempts) = var("KG_SINK_RETRY_MAX_ATTEMPTS") {
+            self.retry.max_attempts = parse_env("KG_SINK_RETRY_MAX_ATTEMPTS", max_attempts)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn neo4j_uri(&self) -> Result<&str, ConfigError> {
+        self.neo4j.uri.as_deref().ok_or(ConfigError::Missing {
+            setting: "neo4j.uri",
+            env: "NEO4J_URI",
+        })
+    }
+
+    pub fn endpoint_url(&self) -> Result<&str, ConfigError> {
+        self.substreams
+            .endpoint_url
+            .as_deref()
+            .ok_or(ConfigError::Missing {
+                setting: "substreams.endpoint_url",
+                env: "SUBSTREAMS_ENDPOINT_URL",
+            })
+    }
+
+    /// Returns the substreams endpoints in failover order: `endpoint_url` first, then
+    /// `endpoints`. Endpoints without a token use `api_token`.
+    pub fn endpoints(&self) -> Result<Vec<EndpointConfig>, ConfigError> {
+        let substreams = &self.substreams;
+        let endpoints = substreams
+            .endpoint_url
+            .iter()
+            .map(|url| (url, None))
+            .chain(
+                substreams
+                    .endpoints
+                    .iter()
+                    .map(|endpoint| (&endpoint.url, endpoint.token.as_ref())),
+            )
+            .map(|(url, token)| EndpointConfig {
+                url: url.clone(),
+                token: token.or(substreams.api_token.as_ref()).cloned(),
+            })
+            .collect::<Vec<_>>();
+
+        if endpoints.is_empty() {
+            return Err(ConfigError::Missing {
+                setting: "substreams.endpoint_url",
+                env: "SUBSTREAMS_ENDPOINT_URL",
+            });
+        }
+
+        Ok(endpoints)
+    }
+
+    /// Checks the settings required to connect to Neo4j
+    pub fn validate_neo4j(&self) -> Result<(), ConfigError> {
+        let uri = self.neo4j_uri()?;
+        if !uri.contains("://") {
+            return Err(ConfigError::Invalid {
+                setting: "neo4j.uri",
+                reason: format!("{uri:?} is not a URI (e.g.: neo4j://localhost:7687)"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Range of blocks to stream: `substreams.block_range` if set, `start_block` to
+    /// `end_block` otherwise
+    pub fn block_range(&self) -> Result<BlockRange, ConfigError> {
+        match &self.substreams.block_range {
+            Some(block_range) => block_range.parse().map_err(|e| ConfigError::Invalid {
+                setting: "substreams.block_range",
+                reason: format!("{block_range:?}: {e:#}"),
+            }),
+            None => Ok(BlockRange::new(
+                self.substreams.start_block as i64,
+                self.substreams.end_block,
+            )),
+        }
+    }
+
+    /// Parameters of the modules as `(module name, value)` pairs
+    pub fn module_params(&self) -> Vec<(String, String)> {
+        self.substreams
+            .params
+            .iter()
+            .map(|(module, value)| (module.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Policy used to retry the blocks failing with a transient error
+    pub fn retry_policy(&self) -> Result<RetryPolicy, ConfigError> {
+        if self.retry.max_attempts == 0 {
+            return Err(ConfigError::Invalid {
+                setting: "retry.max_attempts",
+                reason: "must be at least 1".to_string(),
+            });
+        }
+
+        Ok(RetryPolicy::new(self.retry.max_attempts)
+            .initial_delay(Duration::from_secs(self.retry.initial_delay))
+            .max_delay(Duration::from_secs(self.retry.max_delay)))
+    }
+
+    /// Checks the settings required to stream blocks
+    pub fn validate_substreams(&self) -> Result<(), ConfigError> {
+        for endpoint in self.endpoints()? {
+            if !endpoint.url.starts_with("http://") && !endpoint.url.starts_with("https://") {
+                return Err(ConfigError::Invalid {
+                    setting: "substreams.endpoint_url",
+                    reason: format!("{:?} must start with http:// or https://", endpoint.url),
+                });
+            }
+        }
+
+        let package = &self.substreams.package;
+        if !package.starts_with("http") && !Path::new(package).is_file() {
+            return Err(ConfigError::Invalid {
+                setting: "substreams.package",
+                reason: format!("package file {package:?} not found"),
+            });
+        }
+
+        if self.substreams.module.is_empty() {
+            return Err(ConfigError::Invalid {
+                setting: "substreams.module",
+                reason: "module name is empty".to_string(),
+            });
+        }
+
+        let substreams = &self.substreams;
+        if substreams.block_range.is_some() {
+            self.block_range()?;
+        } else if substreams.end_block != 0 && substreams.end_block <= substreams.start_block {
+            return Err(ConfigError::Invalid {
+                setting: "substreams.end_block",
+                reason: format!(
+                    "end block {} must be after start block {} (or 0 to stream indefinitely)",
+                    substreams.end_block, substreams.start_block
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_env<T>(name: &'static str, value: String) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e: T::Err| ConfigError::InvalidEnv {
+            name,
+            reason: e.to_string(),
+            value,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config: Config = toml::from_str(
+            r#"
+[substreams]
+endpoint_url = "https://file.example.com"
+start_block = 100
+
+[neo4j]
+uri = "neo4j://localhost:7687"
+"#,
+        )
+        .unwrap();
+
+        config
+            .apply_env(env(&[
+                ("SUBSTREAMS_START_BLOCK", "200"),
+                ("SUBSTREAMS_ENDPOINT_URL", ""),
+                ("KG_SINK_HTTP_PORT", "9000"),
+                ("KG_SINK_MAX_HEAD_LAG", "60"),
+                ("KG_SINK_RETRY_MAX_ATTEMPTS", "3"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.endpoint_url().unwrap(), "https://file.example.com");
+        assert_eq!(config.substreams.start_block, 200);
+        assert_eq!(config.substreams.module, DEFAULT_MODULE);
+        assert_eq!(config.http.port, 9000);
+        assert_eq!(config.http.max_head_lag, 60);
+        assert_eq!(
+            config.retry_policy().unwrap(),
+            RetryPolicy::new(3)
+                .initial_delay(Duration::from_secs(DEFAULT_RETRY_INITIAL_DELAY))
+                .max_delay(Duration::from_secs(DEFAULT_RETRY_MAX_DELAY))
+        );
+        assert!(config.validate_neo4j().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let mut config = Config::default();
+
+        let err = config
+            .apply_env(env(&[("SUBSTREAMS_START_BLOCK", "abc")]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidEnv {
+                name: "SUBSTREAMS_START_BLOCK",
+                ..
+            }
+        ));
+
+        assert!(matches!(
+            config.validate_neo4j(),
+            Err(ConfigError::Missing {
+                setting: "neo4j.uri",
+                ..
+            })
+        ));
+
+        config.substreams.endpoint_url = Some("substreams.example.com".to_string());
+        assert!(matches!(
+            config.validate_substreams(),
+            Err(ConfigError::Invalid {
+                setting: "substreams.endpoint_url",
+                ..
+            })
+        ));
+
+        assert!(toml::from_str::<Config>("[substreams]\nstart = 1").is_err());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let mut config: Config = toml::from_str(
+            r#"
+[substreams]
+endpoint_url = "https://primary.example.com"
+
+[[substreams.endpoints]]
+url = "https://backup.example.com"
+token = "backup-token"
+
+[[substreams.endpoints]]
+url = "https://other.example.com"
+"#,
+        )
+        .unwrap();
+
+        config
+            .apply_env(env(&[("SUBSTREAMS_API_TOKEN", "default-token")]))
+            .unwrap();
+
+        assert_eq!(
+            config.endpoints().unwrap(),
+            vec![
+                EndpointConfig {
+                    url: "https://primary.example.com".to_string(),
+                    token: Some("default-token".to_string()),
+                },
+                EndpointConfig {
+                    url: "https://backup.example.com".to_string(),
+                    token: Some("backup-token".to_string()),
+                },
+                EndpointConfig {
+                    url: "https://other.example.com".to_string(),
+                    token: Some("default-token".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            config.substreams.failures_before_failover,
+            DEFAULT_FAILURES_BEFORE_FAILOVER
+        );
+
+        // Fallback endpoints alone are enough
+        config.substreams.endpoint_url = None;
+        assert_eq!(config.endpoints().unwrap().len(), 2);
+
+        config.substreams.endpoints.clear();
+        assert!(matches!(
+            config.endpoints(),
+            Err(ConfigError::Missing { .. })
+        ));
+    }
+
+    #[test]
+    fn test_block_range_and_params() {
+        let mut config: Config = toml::from_str(
+            r#"
+[substreams]
+start_block = 100
+end_block = 200
+
+[substreams.params]
+geo_out = "space=abc"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.block_range().unwrap().resolve(880), (100, 200));
+        assert_eq!(
+            config.module_params(),
+            vec![("geo_out".to_string(), "space=abc".to_string())]
+        );
+
+        config
+            .apply_env(env(&[("SUBSTREAMS_BLOCK_RANGE", "1000:+500")]))
+            .unwrap();
+        assert_eq!(config.block_range().unwrap().resolve(880), (1000, 1500));
+
+        config.substreams.block_range = Some("+20:-".to_string());
+        assert_eq!(config.block_range().unwrap().resolve(880), (900, 0));
+
+        config.substreams.block_range = Some(":".to_string());
+        assert_eq!(config.block_range().unwrap().resolve(880), (880, 0));
+
+        config.substreams.block_range = Some("abc:+10".to_string());
+        assert!(matches!(
+            config.block_range(),
+            Err(ConfigError::Invalid {
+                setting: "substreams.block_range",
+                ..
+            })
+        ));
+    }
+}
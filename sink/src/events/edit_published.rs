@@ -1,58 +1,143 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+};
+
 use futures::{stream, StreamExt, TryStreamExt};
 use grc20_core::{
     block::BlockMetadata,
-    entity::EntityNodeRef,
+    entity::{self, EntityNodeRef},
     error::DatabaseError,
-    ids, indexer_ids,
+    indexer_ids,
     mapping::{self, query_utils::Query, triple, Entity, RelationEdge, Triple},
     network_ids,
     pb::{self, geo},
     relation,
 };
 use grc20_sdk::models::{
-    self,
-    edit::{Edits, ProposedEdit},
-    space, Proposal,
+    self, account,
+    edit::{EditAuthor, Edits, ProposedEdit},
+    failed_edit, skipped_edit, space, EditProposal, ProposalCreator, Proposals,
 };
 use ipfs::deserialize;
 
 use super::{handler::HandlerError, EventHandler};
+use crate::{
+    blacklist::{Blacklist, BlacklistMatch},
+    metrics,
+};
 
 pub struct Edit {
     pub name: String,
     pub proposal_id: String,
     pub space_id: String,
     pub space_plugin_address: String,
+    pub dao_address: String,
+    /// First author of the edit
     pub creator: String,
+    pub authors: Vec<String>,
     pub content_uri: String,
     pub ops: Vec<pb::ipfs::Op>,
 }
 
+/// Edit (or op of an edit) rejected by the sink, recorded as a failed edit in the
+/// indexer space instead of being dropped.
+#[derive(Clone, Debug)]
+pub struct RejectedEdit {
+    pub space_id: String,
+    pub content_uri: String,
+    pub reason: String,
+}
+
+/// Content of an `EditPublished` event, fetched from IPFS.
+pub enum EditContent {
+    /// Edits to apply (`ADD_EDIT` and `IMPORT_SPACE` actions) along with the
+    /// edits that could not be decoded
+    Edits {
+        edits: Vec<Edit>,
+        rejected: Vec<RejectedEdit>,
+    },
+    /// Archival of the space (`ARCHIVE_SPACE` action)
+    ArchiveSpace { space_id: String },
+}
+
+impl EditContent {
+    fn rejected(space_id: String, content_uri: String, reason: impl Into<String>) -> Self {
+        Self::Edits {
+            edits: vec![],
+            rejected: vec![RejectedEdit {
+                space_id,
+                content_uri,
+                reason: reason.into(),
+            }],
+        }
+    }
+}
+
 impl EventHandler {
     pub async fn handle_edits_published(
         &self,
-        edits_published: Vec<(geo::EditPublished, Vec<Edit>)>,
-        _created_space_ids: &[String],
+        edits_published: Vec<(geo::EditPublished, EditContent)>,
         block: &BlockMetadata,
     ) -> Result<(), HandlerError> {
-        let edits = edits_published
-            .into_iter()
-            .flat_map(|(_, edits)| edits)
-            .collect::<Vec<_>>();
+        let mut edits = vec![];
+        let mut rejected_edits = vec![];
+        let mut archived_space_ids = vec![];
+
+        for (_, content) in edits_published {
+            match content {
+                EditContent::Edits {
+                    edits: content_edits,
+                    rejected,
+                } => {
+                    edits.extend(content_edits);
+                    rejected_edits.extend(rejected);
+                }
+                EditContent::ArchiveSpace { space_id } => archived_space_ids.push(space_id),
+            }
+        }
 
-        // let space_id = Space::new_id(network_ids::GEO, address)
+        for rejected in &rejected_edits {
+            self.record_failed_edit(block, rejected).await?;
+        }
 
-        // TODO: Create "synthetic" proposals for newly created spaces and
-        // personal spaces
+        // Edits of different spaces are independent and processed concurrently, while
+        // the edits of a space are written sequentially in block order (and prepared,
+        // i.e.: validated and embedded, ahead of being written). The version index of an
+        // edit is derived from its position in the block, so it does not depend on the
+        // order in which the spaces are processed.
+        let mut edits_by_space: BTreeMap<String, Vec<(usize, Edit)>> = BTreeMap::new();
+        for (idx, edit) in edits.into_iter().enumerate() {
+            edits_by_space
+                .entry(edit.space_id.clone())
+                .or_default()
+                .push((idx, edit));
+        }
 
-        stream::iter(edits)
-            .enumerate()
-            .map(Ok) // Need to wrap the proposal in a Result to use try_for_each
-            .try_for_each(
-                |(idx, proposal)| async move { self.process_edit(block, proposal, idx).await },
-            )
-            .await
-            .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?; // TODO: Convert anyhow::Error to HandlerError properly
+        // The blacklist is read once for the whole block
+        let blacklist = self.blacklist.current().await;
+
+        stream::iter(edits_by_space.into_values())
+            .map(|space_edits| {
+                stream::iter(space_edits)
+                    .map(|(idx, edit)| self.prepare_edit(&blacklist, edit, idx))
+                    .buffered(self.edit_concurrency)
+                    .try_for_each(|prepared_edit| self.write_edit(block, prepared_edit))
+            })
+            .buffer_unordered(self.edit_concurrency)
+            .try_collect::<()>()
+            .await?;
+
+        for space_id in archived_space_ids {
+            tracing::info!(
+                "Block #{} ({}): Archiving space {}",
+                block.block_number,
+                block.timestamp,
+                space_id
+            );
+
+            space::archive(&self.neo4j, block, &space_id).await?;
+        }
 
         Ok(())
     }
@@ -60,62 +145,19 @@ impl EventHandler {
     pub async fn fetch_edit(
         &self,
         edit_published: &geo::EditPublished,
-    ) -> Result<Vec<Edit>, HandlerError> {
-        let space_id = space::new_id(network_ids::GEO, &edit_published.dao_address);
-
+    ) -> Result<EditContent, HandlerError> {
         let bytes = self
             .ipfs
             .get_bytes(&edit_published.content_uri.replace("ipfs://", ""), true)
             .await?;
 
-        let metadata = if let Ok(metadata) = deserialize::<pb::ipfs::IpfsMetadata>(&bytes) {
-            metadata
-        } else {
-            tracing::warn!("Invalid metadata for edit {}", edit_published.content_uri);
-            return Ok(vec![]);
-        };
-
-        match metadata.r#type() {
-            pb::ipfs::ActionType::AddEdit => {
-                let edit = deserialize::<pb::ipfs::Edit>(&bytes)?;
-                Ok(vec![Edit {
-                    name: edit.name,
-                    content_uri: edit_published.content_uri.clone(),
-                    proposal_id: edit.id,
-                    space_id: space_id.clone(),
-                    space_plugin_address: edit_published.plugin_address.clone(),
-                    creator: edit.authors[0].clone(),
-                    ops: edit.ops,
-                }])
-            }
-            pb::ipfs::ActionType::ImportSpace => {
-                let import = deserialize::<pb::ipfs::Import>(&bytes)?;
-                stream::iter(import.edits)
-                    .map(|edit_uri| {
-                        let space_id = space_id.clone();
-                        let space_plugin_address = edit_published.plugin_address.clone();
-
-                        async move {
-                            let hash = edit_uri.replace("ipfs://", "");
-                            let edit = self.ipfs.get::<pb::ipfs::ImportEdit>(&hash, true).await?;
-
-                            Ok(Edit {
-                                name: edit.name,
-                                content_uri: edit_uri,
-                                proposal_id: edit.id,
-                                space_id,
-                                space_plugin_address,
-                                creator: edit.authors[0].clone(),
-                                ops: edit.ops,
-                            })
-                        }
-                    })
-                    .buffered(16)
-                    .try_collect::<Vec<_>>()
-                    .await
-            }
-            _ => Ok(vec![]),
-        }
+        decode_edit(edit_published, &bytes, |edit_uri| async move {
+            Ok(self
+                .ipfs
+                .get_bytes(&edit_uri.replace("ipfs://", ""), true)
+                .await?)
+        })
+        .await
     }
 
     pub async fn process_edit(
@@ -123,76 +165,139 @@ impl EventHandler {
         block: &BlockMetadata,
         edit: Edit,
         index: usize,
-    ) -> Result<(), DatabaseError> {
-        // TODO: Store edit metadata
-        // 1. Check if edit exists (i.e.: was created via edit proposal)
-        // 2. If exists, update edit metadata
-        // 3. If not, create edit metadata
-
-        if self.spaces_blacklist.contains(&edit.space_id) {
-            tracing::warn!(
-                "Block #{} ({}): Space {} is blacklisted, skipping edit",
-                block.block_number,
-                block.timestamp,
-                edit.space_id
-            );
-            return Ok(());
+    ) -> Result<(), HandlerError> {
+        let blacklist = self.blacklist.current().await;
+        let prepared_edit = self.prepare_edit(&blacklist, edit, index).await?;
+        self.write_edit(block, prepared_edit).await
+    }
+
+    /// Groups the ops of the `index`-th edit of a block, and validates and embeds the
+    /// triples it sets. Does not write to the database.
+    async fn prepare_edit(
+        &self,
+        blacklist: &Blacklist,
+        mut edit: Edit,
+        index: usize,
+    ) -> Result<PreparedEdit, HandlerError> {
+        if let Some(blacklisted) = blacklist.check(&edit.space_id, &edit.authors, &edit.dao_address)
+        {
+            return Ok(PreparedEdit::Skipped { edit, blacklisted });
         }
 
+        // Group ops by type
+        let num_ops = edit.ops.len();
+        let op_groups = OpGroups::from_ops(std::mem::take(&mut edit.ops));
+        let op_counts = OpCounts::new(&op_groups);
+
+        // Invalid triples are recorded as failed edits and skipped
+        let mut invalid_triples = vec![];
+        let mut prepared_groups = Vec::with_capacity(op_groups.len());
+        for mut op_group in op_groups {
+            let mut set_triples = Vec::with_capacity(op_group.set_triples.len());
+            for triple in std::mem::take(&mut op_group.set_triples) {
+                let triple_ref = format!("{}/{}", triple.entity, triple.attribute);
+                match Triple::try_from(triple) {
+                    Ok(triple) => set_triples.push(triple),
+                    Err(e) => invalid_triples.push(format!("Invalid triple {triple_ref}: {e}")),
+                }
+            }
+
+            self.embed_triples(&mut set_triples).await?;
+            prepared_groups.push((op_group, set_triples));
+        }
+
+        Ok(PreparedEdit::Ready {
+            edit,
+            index,
+            num_ops,
+            op_counts,
+            op_groups: prepared_groups,
+            invalid_triples,
+        })
+    }
+
+    async fn write_edit(
+        &self,
+        block: &BlockMetadata,
+        prepared_edit: PreparedEdit,
+    ) -> Result<(), HandlerError> {
+        let (edit, index, num_ops, op_counts, op_groups, invalid_triples) = match prepared_edit {
+            PreparedEdit::Skipped { edit, blacklisted } => {
+                self.record_skipped_edit(block, &edit, &blacklisted).await?;
+                return Ok(());
+            }
+            PreparedEdit::Ready {
+                edit,
+                index,
+                num_ops,
+                op_counts,
+                op_groups,
+                invalid_triples,
+            } => (edit, index, num_ops, op_counts, op_groups, invalid_triples),
+        };
+
         let version_index = if self.versioning {
             mapping::new_version_index(block.block_number, index)
         } else {
             "0".to_string()
         };
 
-        if self.governance {
-            let edit_medatata =
-                models::Edit::new(edit.name, edit.content_uri, Some(version_index.clone()));
-            let proposal_id = Proposal::gen_id(&edit.space_plugin_address, &edit.proposal_id);
-            self.create_edit_relations(block, edit_medatata, &edit.space_id, &proposal_id)
-                .await?;
-        }
-
-        // Group ops by type
-        let num_ops = edit.ops.len();
-        let op_groups = OpGroups::from_ops(edit.ops);
+        self.record_edit(block, &edit, &op_counts, &version_index)
+            .await?;
 
         tracing::info!(
-            "Block #{} ({}): Processing {} ops for proposal {}: {} set triples, {} delete triples, {} create relations, {} delete relations",
+            "Block #{} ({}): Processing {} ops for proposal {}: {} set triples, {} delete triples, {} create relations, {} delete relations, {} delete entities",
             block.block_number,
             block.timestamp,
             num_ops,
             edit.proposal_id,
-            op_groups.set_triples.len(),
-            op_groups.delete_triples.len(),
-            op_groups.create_relations.len(),
-            op_groups.delete_relations.len(),
+            op_counts.set_triples,
+            op_counts.delete_triples,
+            op_counts.create_relations,
+            op_counts.delete_relations,
+            op_counts.delete_entities,
         );
 
-        // Handle SET_TRIPLE ops
-        triple::insert_many(&self.neo4j, block, &edit.space_id, &version_index)
-            .triples(op_groups.set_triples.into_iter().map(|triple| {
-                let mut triple: Triple = triple.try_into().expect("Failed to convert triple");
-
-                if ids::indexed(&triple.attribute) {
-                    let embedding = self
-                        .embedding_model
-                        .embed(vec![&triple.value.value], None)
-                        .expect("Failed to get embedding")
-                        .pop()
-                        .expect("Embedding is empty");
-                    triple.embedding = Some(embedding.into_iter().map(|v| v as f64).collect());
-                }
+        for reason in invalid_triples {
+            self.record_failed_edit(
+                block,
+                &RejectedEdit {
+                    space_id: edit.space_id.clone(),
+                    content_uri: edit.content_uri.clone(),
+                    reason,
+                },
+            )
+            .await?;
+        }
 
-                triple
-            }))
+        for (op_group, set_triples) in op_groups {
+            self.apply_op_group(block, &edit, &version_index, op_group, set_triples)
+                .await?;
+        }
+
+        op_counts.record_metrics();
+
+        Ok(())
+    }
+
+    async fn apply_op_group(
+        &self,
+        block: &BlockMetadata,
+        edit: &Edit,
+        version_index: &str,
+        op_group: OpGroups,
+        set_triples: Vec<Triple>,
+    ) -> Result<(), HandlerError> {
+        // Handle SET_TRIPLE ops
+        triple::insert_many(&self.neo4j, block, &edit.space_id, version_index)
+            .triples(set_triples)
             .send()
             .await?;
 
         // Handle DELETE_TRIPLE ops
-        triple::delete_many(&self.neo4j, block, &edit.space_id, &version_index)
+        triple::delete_many(&self.neo4j, block, &edit.space_id, version_index)
             .triples(
-                op_groups
+                op_group
                     .delete_triples
                     .into_iter()
                     .map(|triple| (triple.entity, triple.attribute)),
@@ -205,10 +310,10 @@ impl EventHandler {
             &self.neo4j,
             block,
             &edit.space_id,
-            &version_index,
+            version_index,
         )
         .relations(
-            op_groups
+            op_group
                 .create_relations
                 .into_iter()
                 .map(|relation| relation.into()),
@@ -217,9 +322,9 @@ impl EventHandler {
         .await?;
 
         // Handle DELETE_RELATION ops
-        relation::delete_many(&self.neo4j, block, &edit.space_id, &version_index)
+        relation::delete_many(&self.neo4j, block, &edit.space_id, version_index)
             .relations(
-                op_groups
+                op_group
                     .delete_relations
                     .into_iter()
                     .map(|relation| relation.id),
@@ -227,9 +332,173 @@ impl EventHandler {
             .send()
             .await?;
 
+        // Handle DELETE_ENTITY ops
+        entity::delete_many(&self.neo4j, block, &edit.space_id, version_index)
+            .entities(op_group.delete_entities.into_iter().map(|entity| entity.id))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records a rejected edit (or op) as a failed edit in the indexer space
+    pub(crate) async fn record_failed_edit(
+        &self,
+        block: &BlockMetadata,
+        rejected: &RejectedEdit,
+    ) -> Result<(), DatabaseError> {
+        tracing::warn!(
+            "Block #{} ({}): Rejected edit {} in space {}: {}",
+            block.block_number,
+            block.timestamp,
+            rejected.content_uri,
+            rejected.space_id,
+            rejected.reason
+        );
+
+        metrics::FAILED_EDITS.inc();
+
+        failed_edit::new(
+            &rejected.space_id,
+            &rejected.content_uri,
+            block.block_number,
+            &rejected.reason,
+        )
+        .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+        .send()
+        .await
+    }
+
+    /// Records an edit skipped because of the blacklist in the indexer space. The
+    /// recorded author is the blacklisted one if the edit was skipped because of one of
+    /// its authors, and the creator of the edit otherwise.
+    async fn record_skipped_edit(
+        &self,
+        block: &BlockMetadata,
+        edit: &Edit,
+        blacklisted: &BlacklistMatch,
+    ) -> Result<(), DatabaseError> {
+        tracing::warn!(
+            "Block #{} ({}): Skipping edit {} in space {}: {}",
+            block.block_number,
+            block.timestamp,
+            edit.content_uri,
+            edit.space_id,
+            blacklisted
+        );
+
+        metrics::SKIPPED_EDITS.inc();
+
+        let author = match blacklisted {
+            BlacklistMatch::Author(author) => author,
+            _ => &edit.creator,
+        };
+
+        skipped_edit::new(
+            &edit.space_id,
+            &edit.content_uri,
+            author,
+            &edit.dao_address,
+            block.block_number,
+            blacklisted.to_string(),
+        )
+        .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+        .send()
+        .await
+    }
+
+    /// Records the metadata of an edit (name, authors, op counts and block) along with
+    /// its proposal. Edits published without an onchain proposal (e.g.: edits of
+    /// personal spaces, or every edit when governance events are not indexed) get a
+    /// synthetic proposal, so that the history of all spaces can be queried the same way.
+    async fn record_edit(
+        &self,
+        block: &BlockMetadata,
+        edit: &Edit,
+        op_counts: &OpCounts,
+        version_index: &str,
+    ) -> Result<(), DatabaseError> {
+        let mut edit_metadata = models::Edit::new(
+            edit.name.clone(),
+            edit.content_uri.clone(),
+            Some(version_index.to_string()),
+        );
+        edit_metadata.attributes.block_number = Some(block.block_number);
+        edit_metadata.attributes.set_triple_count = Some(op_counts.set_triples as u64);
+        edit_metadata.attributes.delete_triple_count = Some(op_counts.delete_triples as u64);
+        edit_metadata.attributes.create_relation_count = Some(op_counts.create_relations as u64);
+        edit_metadata.attributes.delete_relation_count = Some(op_counts.delete_relations as u64);
+        edit_metadata.attributes.delete_entity_count = Some(op_counts.delete_entities as u64);
+        let edit_id = edit_metadata.id().to_string();
+
+        // Authors are inserted first since the synthetic proposal references its creator
+        let mut author_ids = Vec::with_capacity(edit.authors.len());
+        for author in &edit.authors {
+            let account = account::new(author.clone());
+            author_ids.push(account.id().to_string());
+            account
+                .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+                .send()
+                .await?;
+        }
+
+        // Onchain proposals are only indexed along with the governance events
+        let proposal = if self.governance {
+            EditProposal::find_by_content_uri(&self.neo4j, &edit.content_uri).await?
+        } else {
+            None
+        };
+
+        let proposal_id = match proposal {
+            Some(proposal) => proposal.id().to_string(),
+            None => self.create_synthetic_proposal(block, edit).await?,
+        };
+
+        self.create_edit_relations(block, edit_metadata, &edit.space_id, &proposal_id)
+            .await?;
+
+        for (idx, author_id) in author_ids.into_iter().enumerate() {
+            EditAuthor::new(&edit_id, author_id, idx)
+                .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+                .send()
+                .await?;
+        }
+
         Ok(())
     }
 
+    /// Creates the synthetic proposal of `edit` and returns its ID
+    async fn create_synthetic_proposal(
+        &self,
+        block: &BlockMetadata,
+        edit: &Edit,
+    ) -> Result<String, DatabaseError> {
+        let proposal = EditProposal::synthetic(
+            &edit.space_plugin_address,
+            &edit.proposal_id,
+            edit.content_uri.clone(),
+            &block.timestamp,
+        );
+        let proposal_id = proposal.id().to_string();
+
+        proposal
+            .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await?;
+
+        Proposals::new(&edit.space_id, &proposal_id)
+            .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await?;
+
+        ProposalCreator::new(&proposal_id, &account::new_id(&edit.creator))
+            .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await?;
+
+        Ok(proposal_id)
+    }
+
     async fn create_edit_relations(
         &self,
         block: &BlockMetadata,
@@ -260,20 +529,193 @@ impl EventHandler {
     }
 }
 
-// Ops are grouped by type
+/// Decodes the content (`bytes`) of an `EditPublished` event. The edits of imports
+/// are fetched with `fetch`, which returns the content of the given edit URI.
+pub async fn decode_edit<F, Fut>(
+    edit_published: &geo::EditPublished,
+    bytes: &[u8],
+    fetch: F,
+) -> Result<EditContent, HandlerError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, HandlerError>>,
+{
+    let space_id = space::new_id(network_ids::GEO, &edit_published.dao_address);
+
+    let content_uri = edit_published.content_uri.clone();
+
+    let metadata = match deserialize::<pb::ipfs::IpfsMetadata>(bytes) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return Ok(EditContent::rejected(
+                space_id,
+                content_uri,
+                format!("Invalid metadata: {e}"),
+            ))
+        }
+    };
+
+    match metadata.r#type() {
+        pb::ipfs::ActionType::AddEdit => {
+            let edit = match deserialize::<pb::ipfs::Edit>(bytes) {
+                Ok(edit) => edit,
+                Err(e) => {
+                    return Ok(EditContent::rejected(
+                        space_id,
+                        content_uri,
+                        format!("Invalid edit: {e}"),
+                    ))
+                }
+            };
+
+            let Some(creator) = edit.authors.first().cloned() else {
+                return Ok(EditContent::rejected(
+                    space_id,
+                    content_uri,
+                    "Edit has no authors",
+                ));
+            };
+
+            Ok(EditContent::Edits {
+                edits: vec![Edit {
+                    name: edit.name,
+                    content_uri,
+                    proposal_id: edit.id,
+                    space_id,
+                    space_plugin_address: edit_published.plugin_address.clone(),
+                    dao_address: edit_published.dao_address.clone(),
+                    creator,
+                    authors: edit.authors,
+                    ops: edit.ops,
+                }],
+                rejected: vec![],
+            })
+        }
+        pb::ipfs::ActionType::ImportSpace => {
+            let import = match deserialize::<pb::ipfs::Import>(bytes) {
+                Ok(import) => import,
+                Err(e) => {
+                    return Ok(EditContent::rejected(
+                        space_id,
+                        content_uri,
+                        format!("Invalid import: {e}"),
+                    ))
+                }
+            };
+
+            let (edits, rejected) = stream::iter(import.edits)
+                .map(|edit_uri| {
+                    let space_id = space_id.clone();
+                    let space_plugin_address = edit_published.plugin_address.clone();
+                    let dao_address = edit_published.dao_address.clone();
+
+                    let bytes = fetch(edit_uri.clone());
+
+                    async move {
+                        let edit = match deserialize::<pb::ipfs::ImportEdit>(&bytes.await?) {
+                            Ok(edit) => edit,
+                            Err(e) => {
+                                return Ok(Err(RejectedEdit {
+                                    space_id,
+                                    content_uri: edit_uri,
+                                    reason: format!("Invalid import edit: {e}"),
+                                }))
+                            }
+                        };
+
+                        let Some(creator) = edit.authors.first().cloned() else {
+                            return Ok(Err(RejectedEdit {
+                                space_id,
+                                content_uri: edit_uri,
+                                reason: "Edit has no authors".to_string(),
+                            }));
+                        };
+
+                        Ok::<_, HandlerError>(Ok(Edit {
+                            name: edit.name,
+                            content_uri: edit_uri,
+                            proposal_id: edit.id,
+                            space_id,
+                            space_plugin_address,
+                            dao_address,
+                            creator,
+                            authors: edit.authors,
+                            ops: edit.ops,
+                        }))
+                    }
+                })
+                .buffered(16)
+                .try_fold(
+                    (vec![], vec![]),
+                    |(mut edits, mut rejected), result| async move {
+                        match result {
+                            Ok(edit) => edits.push(edit),
+                            Err(rejected_edit) => rejected.push(rejected_edit),
+                        }
+                        Ok((edits, rejected))
+                    },
+                )
+                .await?;
+
+            Ok(EditContent::Edits { edits, rejected })
+        }
+        pb::ipfs::ActionType::ArchiveSpace => Ok(EditContent::ArchiveSpace { space_id }),
+        _ => Ok(EditContent::Edits {
+            edits: vec![],
+            rejected: vec![],
+        }),
+    }
+}
+
+/// Edit whose ops are ready to be written (see [`EventHandler::handle_edits_published`])
+enum PreparedEdit {
+    /// Edit skipped because of the blacklist
+    Skipped {
+        edit: Edit,
+        blacklisted: BlacklistMatch,
+    },
+    Ready {
+        edit: Edit,
+        /// Position of the edit in the block
+        index: usize,
+        num_ops: usize,
+        op_counts: OpCounts,
+        /// Op groups along with the valid (and embedded) triples they set
+        op_groups: Vec<(OpGroups, Vec<Triple>)>,
+        /// Reasons why triples set by the edit were rejected
+        invalid_triples: Vec<String>,
+    },
+}
+
+/// Ops of an edit grouped by type. The ops of a group are applied type by type (set
+/// triples, delete triples, create relations, delete relations, delete entities), so
+/// an op writing what an earlier op of the group deleted (e.g.: setting a triple of an
+/// entity deleted earlier in the edit) starts a new group to preserve the op order.
 #[derive(Debug, Default)]
 pub struct OpGroups {
     set_triples: Vec<pb::ipfs::Triple>,
     delete_triples: Vec<pb::ipfs::Triple>,
     create_relations: Vec<pb::ipfs::Relation>,
     delete_relations: Vec<pb::ipfs::Relation>,
+    delete_entities: Vec<pb::ipfs::Entity>,
+
+    // What the group deletes, used to detect conflicting ops
+    deleted_triples: HashSet<(String, String)>,
+    deleted_relations: HashSet<String>,
+    deleted_entities: HashSet<String>,
 }
 
 impl OpGroups {
-    pub fn from_ops(ops: impl IntoIterator<Item = pb::ipfs::Op>) -> Self {
-        let mut op_groups = Self::default();
+    pub fn from_ops(ops: impl IntoIterator<Item = pb::ipfs::Op>) -> Vec<Self> {
+        let mut groups = vec![Self::default()];
 
         for op in ops {
+            let mut group = groups.last_mut().expect("At least one group");
+            if group.conflicts_with(&op) {
+                groups.push(Self::default());
+                group = groups.last_mut().expect("At least one group");
+            }
+
             match (op.r#type(), op) {
                 (
                     pb::ipfs::OpType::SetTriple,
@@ -282,7 +724,10 @@ impl OpGroups {
                         ..
                     },
                 ) => {
-                    op_groups.set_triples.push(triple);
+                    group.set_triples.push(triple);
+                }
+                (pb::ipfs::OpType::SetTripleBatch, pb::ipfs::Op { triples, .. }) => {
+                    group.set_triples.extend(triples);
                 }
                 (
                     pb::ipfs::OpType::DeleteTriple,
@@ -291,7 +736,10 @@ impl OpGroups {
                         ..
                     },
                 ) => {
-                    op_groups.delete_triples.push(triple);
+                    group
+                        .deleted_triples
+                        .insert((triple.entity.clone(), triple.attribute.clone()));
+                    group.delete_triples.push(triple);
                 }
 
                 (
@@ -301,7 +749,7 @@ impl OpGroups {
                         ..
                     },
                 ) => {
-                    op_groups.create_relations.push(relation);
+                    group.create_relations.push(relation);
                 }
                 (
                     pb::ipfs::OpType::DeleteRelation,
@@ -310,7 +758,19 @@ impl OpGroups {
                         ..
                     },
                 ) => {
-                    op_groups.delete_relations.push(relation);
+                    group.deleted_relations.insert(relation.id.clone());
+                    group.delete_relations.push(relation);
+                }
+
+                (
+                    pb::ipfs::OpType::DeleteEntity,
+                    pb::ipfs::Op {
+                        entity: Some(entity),
+                        ..
+                    },
+                ) => {
+                    group.deleted_entities.insert(entity.id.clone());
+                    group.delete_entities.push(entity);
                 }
 
                 (typ, maybe_triple) => {
@@ -319,6 +779,76 @@ impl OpGroups {
             }
         }
 
+        groups.retain(|group| !group.is_empty());
+        groups
+    }
+
+    /// Whether `op` writes a triple or a relation deleted by an earlier op of the
+    /// group, in which case it must be applied after the deletion
+    fn conflicts_with(&self, op: &pb::ipfs::Op) -> bool {
+        let deleted_triple = |triple: &pb::ipfs::Triple| {
+            self.deleted_entities.contains(&triple.entity)
+                || self
+                    .deleted_triples
+                    .contains(&(triple.entity.clone(), triple.attribute.clone()))
+        };
+
+        match op.r#type() {
+            pb::ipfs::OpType::SetTriple => op.triple.as_ref().is_some_and(deleted_triple),
+            pb::ipfs::OpType::SetTripleBatch => op.triples.iter().any(deleted_triple),
+            pb::ipfs::OpType::CreateRelation => op.relation.as_ref().is_some_and(|relation| {
+                self.deleted_relations.contains(&relation.id)
+                    || self.deleted_entities.contains(&relation.from_entity)
+                    || self.deleted_entities.contains(&relation.to_entity)
+            }),
+            _ => false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.set_triples.is_empty()
+            && self.delete_triples.is_empty()
+            && self.create_relations.is_empty()
+            && self.delete_relations.is_empty()
+            && self.delete_entities.is_empty()
+    }
+}
+
+/// Number of ops of each type of an edit
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OpCounts {
+    pub set_triples: usize,
+    pub delete_triples: usize,
+    pub create_relations: usize,
+    pub delete_relations: usize,
+    pub delete_entities: usize,
+}
+
+impl OpCounts {
+    pub fn new(op_groups: &[OpGroups]) -> Self {
         op_groups
+            .iter()
+            .fold(Self::default(), |counts, group| Self {
+                set_triples: counts.set_triples + group.set_triples.len(),
+                delete_triples: counts.delete_triples + group.delete_triples.len(),
+                create_relations: counts.create_relations + group.create_relations.len(),
+                delete_relations: counts.delete_relations + group.delete_relations.len(),
+                delete_entities: counts.delete_entities + group.delete_entities.len(),
+            })
+    }
+
+    /// Counts the ops in the processed ops metric, once they are written
+    fn record_metrics(&self) {
+        for (op_type, count) in [
+            (pb::ipfs::OpType::SetTriple, self.set_triples),
+            (pb::ipfs::OpType::DeleteTriple, self.delete_triples),
+            (pb::ipfs::OpType::CreateRelation, self.create_relations),
+            (pb::ipfs::OpType::DeleteRelation, self.delete_relations),
+            (pb::ipfs::OpType::DeleteEntity, self.delete_entities),
+        ] {
+            metrics::OPS_PROCESSED
+                .with_label_values(&[op_type.as_str_name()])
+                .inc_by(count as u64);
+        }
     }
 }
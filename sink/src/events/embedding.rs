@@ -0,0 +1,46 @@
+use embedding::EmbeddingError;
+use grc20_core::{ids, mapping::Triple};
+
+use super::{handler::HandlerError, EventHandler};
+use crate::metrics;
+
+impl EventHandler {
+    /// Computes the embeddings of the indexed `triples` in batches on the blocking
+    /// thread pool (embedding is CPU-bound and would otherwise stall the runtime).
+    pub(crate) async fn embed_triples(&self, triples: &mut [Triple]) -> Result<(), HandlerError> {
+        let (indices, texts): (Vec<_>, Vec<_>) = triples
+            .iter()
+            .enumerate()
+            .filter(|(_, triple)| ids::indexed(&triple.attribute))
+            .map(|(idx, triple)| (idx, triple.value.value.clone()))
+            .unzip();
+
+        if texts.is_empty() {
+            return Ok(());
+        }
+
+        metrics::EMBEDDED_TEXTS.inc_by(texts.len() as u64);
+        let timer = metrics::EMBEDDING_TIME.start_timer();
+
+        let embedder = self.embedder.clone();
+        let embeddings = tokio::task::spawn_blocking(move || embedder.embed(texts))
+            .await
+            .map_err(|e| HandlerError::Other(Box::new(e)))??;
+
+        timer.observe_duration();
+
+        if embeddings.len() != indices.len() {
+            return Err(EmbeddingError::Count {
+                expected: indices.len(),
+                actual: embeddings.len(),
+            }
+            .into());
+        }
+
+        for (idx, embedding) in indices.into_iter().zip(embeddings) {
+            triples[idx].embedding = Some(embedding);
+        }
+
+        Ok(())
+    }
+}
@@ -1,22 +1,38 @@
 use chrono::DateTime;
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use embedding::{Embedder, FastEmbedder};
 use futures::{stream, StreamExt, TryStreamExt};
 use grc20_core::{
-    block::BlockMetadata, error::DatabaseError, ids::create_geo_id, indexer_ids, mapping::Query,
-    neo4rs, pb::geo::GeoOutput,
+    block::BlockMetadata,
+    error::DatabaseError,
+    ids::create_geo_id,
+    indexer_ids,
+    mapping::{transaction, Query},
+    neo4rs,
+    pb::geo::GeoOutput,
 };
+use grc20_sdk::models::proposal::VotingSettings;
 use ipfs::IpfsClient;
 use prost::Message;
-use substreams_utils::pb::sf::substreams::rpc::v2::BlockScopedData;
+use substreams_utils::{
+    pb::sf::substreams::rpc::v2::{BlockScopedData, BlockUndoSignal},
+    RetryPolicy,
+};
 
 use crate::{
-    blacklist, metrics,
+    blacklist::{self, BlacklistFile},
+    health::HealthState,
+    metrics,
     preprocess::{self, EventData},
+    reindex::{self, ReindexScope},
 };
 use cache::KgCache;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
+
+/// IPFS gateway used to fetch edits when none is provided
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://gateway.lighthouse.storage/ipfs/";
 
-const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
+/// Maximum number of spaces whose edits are processed concurrently by default
+pub const DEFAULT_EDIT_CONCURRENCY: usize = 8;
 
 #[derive(thiserror::Error, Debug)]
 pub enum HandlerError {
@@ -32,24 +48,45 @@ pub enum HandlerError {
     #[error("Cache error: {0}")]
     CacheError(#[from] cache::CacheError),
 
+    #[error("Embedding error: {0}")]
+    EmbeddingError(#[from] embedding::EmbeddingError),
+
     // #[error("KG error: {0}")]
     // KgError(#[from] kg::Error),
     #[error("Error processing event: {0}")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
 
+impl HandlerError {
+    /// Whether the error is likely transient (e.g.: Neo4j, IPFS or memcache being
+    /// unreachable), in which case processing the block again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            HandlerError::DatabaseError(
+                DatabaseError::Neo4jError(_) | DatabaseError::TransactionError(_)
+            ) | HandlerError::IpfsError(ipfs::Error::Reqwest(_) | ipfs::Error::Io(_))
+                | HandlerError::CacheError(cache::CacheError::Memcache(_))
+        )
+    }
+}
+
 pub struct EventHandler {
     pub(crate) ipfs: IpfsClient,
     pub(crate) neo4j: neo4rs::Graph,
     #[allow(dead_code)]
     pub(crate) cache: Option<Arc<KgCache>>,
-    pub(crate) spaces_blacklist: Vec<String>,
-    pub(crate) embedding_model: fastembed::TextEmbedding,
-    pub(crate) embedding_model_dim: usize,
+    pub(crate) blacklist: BlacklistFile,
+    pub(crate) embedder: Arc<dyn Embedder>,
+    pub(crate) health: HealthState,
+    pub(crate) retry_policy: RetryPolicy,
 
     // Handler config
     pub(crate) versioning: bool,
     pub(crate) governance: bool,
+    pub(crate) edit_concurrency: usize,
+    pub(crate) voting_settings: VotingSettings,
+    pub(crate) reindex: Option<ReindexScope>,
 }
 
 impl EventHandler {
@@ -57,59 +94,61 @@ impl EventHandler {
         &self.neo4j
     }
 
+    pub fn embedder(&self) -> &Arc<dyn Embedder> {
+        &self.embedder
+    }
+
     pub fn embedding_dim(&self) -> usize {
-        self.embedding_model_dim
+        self.embedder.dim()
+    }
+
+    /// Progress of the handler, also used to request a graceful shutdown
+    pub fn health(&self) -> &HealthState {
+        &self.health
     }
 
     pub fn new(neo4j: neo4rs::Graph, cache: Option<Arc<KgCache>>) -> Result<Self, HandlerError> {
-        Self::new_with_ipfs(
-            neo4j,
-            IpfsClient::from_url("https://gateway.lighthouse.storage/ipfs/"),
-            cache,
-        )
+        Self::new_with_ipfs(neo4j, IpfsClient::from_url(DEFAULT_IPFS_GATEWAY), cache)
     }
 
+    /// Creates a handler using the default embedding model
     pub fn new_with_ipfs(
         neo4j: neo4rs::Graph,
         ipfs: IpfsClient,
         cache: Option<Arc<KgCache>>,
     ) -> Result<Self, HandlerError> {
-        Ok(Self {
+        let embedder = FastEmbedder::new(embedding::DEFAULT_MODEL).inspect_err(|e| {
+            tracing::error!("Error initializing embedding model: {:?}", e);
+        })?;
+
+        Ok(Self::new_with_embedder(
+            neo4j,
+            ipfs,
+            cache,
+            Arc::new(embedder),
+        ))
+    }
+
+    pub fn new_with_embedder(
+        neo4j: neo4rs::Graph,
+        ipfs: IpfsClient,
+        cache: Option<Arc<KgCache>>,
+        embedder: Arc<dyn Embedder>,
+    ) -> Self {
+        Self {
             ipfs,
             neo4j,
             cache,
-            spaces_blacklist: match blacklist::load() {
-                Ok(Some(blacklist)) => {
-                    tracing::info!("Blacklisting spaces: {}", blacklist.spaces.join(", "));
-                    blacklist.spaces
-                }
-                Ok(None) => {
-                    tracing::info!("No blacklist found");
-                    vec![]
-                }
-                Err(e) => {
-                    tracing::warn!("Error loading blacklist, skipping: {:?}", e);
-                    vec![]
-                }
-            },
-            embedding_model: TextEmbedding::try_new(
-                InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
-            )
-            .map_err(|e| {
-                tracing::error!("Error initializing embedding model: {:?}", e);
-                HandlerError::Other(format!("Error initializing embedding model: {:?}", e).into())
-            })?,
-            embedding_model_dim: TextEmbedding::get_model_info(&EMBEDDING_MODEL)
-                .map_err(|e| {
-                    tracing::error!("Error getting embedding model info: {:?}", e);
-                    HandlerError::Other(
-                        format!("Error getting embedding model info: {:?}", e).into(),
-                    )
-                })?
-                .dim,
+            blacklist: BlacklistFile::new(blacklist::DEFAULT_BLACKLIST_FILE),
+            embedder,
+            health: HealthState::default(),
+            retry_policy: RetryPolicy::default(),
             versioning: false,
             governance: false,
-        })
+            edit_concurrency: DEFAULT_EDIT_CONCURRENCY,
+            voting_settings: VotingSettings::default(),
+            reindex: None,
+        }
     }
 
     pub fn versioning(mut self, versioning: bool) -> Self {
@@ -121,6 +160,55 @@ impl EventHandler {
         self.governance = governance;
         self
     }
+
+    /// Sets the maximum number of spaces whose edits are processed concurrently within
+    /// a block, and of edits of a space prepared (i.e.: validated and embedded) ahead of
+    /// being written. The edits of a space are always written in block order.
+    pub fn edit_concurrency(mut self, edit_concurrency: usize) -> Self {
+        self.edit_concurrency = edit_concurrency.max(1);
+        self
+    }
+
+    /// Sets the voting settings indexed on the spaces whose governance plugin is
+    /// created, and used for the spaces indexed without voting settings. The substreams
+    /// output does not include the settings of the plugins.
+    pub fn voting_settings(mut self, voting_settings: VotingSettings) -> Self {
+        self.voting_settings = voting_settings;
+        self
+    }
+
+    /// Sets the policy used to retry blocks failing with a retryable error (see
+    /// [`HandlerError::is_retryable`]). Blocks are not retried by default.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Configures the handler to re-index the given scope (see [`crate::reindex`]).
+    /// Progress is tracked with a separate cursor and other spaces are ignored.
+    pub fn reindex(mut self, scope: ReindexScope) -> Self {
+        self.reindex = Some(scope);
+        self
+    }
+
+    pub fn reindex_scope(&self) -> Option<&ReindexScope> {
+        self.reindex.as_ref()
+    }
+
+    /// ID of the cursor entity used to track the progress of the handler
+    pub(crate) fn cursor_id(&self) -> &'static str {
+        if self.reindex.is_some() {
+            indexer_ids::REINDEX_CURSOR_ID
+        } else {
+            indexer_ids::CURSOR_ID
+        }
+    }
+
+    /// Sets the blacklist file (reloaded when modified)
+    pub fn blacklist_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.blacklist = BlacklistFile::new(path);
+        self
+    }
 }
 
 fn get_block_metadata(block: &BlockScopedData) -> anyhow::Result<BlockMetadata> {
@@ -157,14 +245,16 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
         let block = get_block_metadata(raw_block)
             .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?;
 
-        let data = GeoOutput::decode(output.value.as_slice())?;
+        let mut data = GeoOutput::decode(output.value.as_slice())?;
+
+        // When re-indexing specific spaces, only their events are processed again
+        if let Some(scope) = &self.reindex {
+            scope.retain_dao_events(&mut data);
+        }
 
         let prefetched_edits = stream::iter(data.edits_published)
             .then(|edit_event| async {
-                let edit = self
-                    .fetch_edit(&edit_event)
-                    .await
-                    .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?;
+                let edit = self.fetch_edit(&edit_event).await?;
                 Result::<_, Self::Error>::Ok((edit_event, edit))
             })
             .try_collect::<Vec<_>>()
@@ -199,10 +289,14 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
     async fn process_block_scoped_data(
         &self,
         _raw_block: &BlockScopedData,
-        data: preprocess::EventData,
+        mut data: preprocess::EventData,
     ) -> Result<(), Self::Error> {
         let _timer = metrics::BLOCK_PROCESSING_TIME.start_timer();
 
+        if let Some(scope) = &self.reindex {
+            reindex::retain_plugin_events(&self.neo4j, scope, &mut data).await?;
+        }
+
         let drift = chrono::Utc::now().timestamp() - data.block.timestamp.timestamp();
         metrics::HEAD_BLOCK_TIME_DRIFT.set(drift as f64);
         metrics::HEAD_BLOCK_NUMBER.set(data.block.block_number as f64);
@@ -217,13 +311,38 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                 data.spaces_created.len()
             );
         }
-        let created_space_ids = stream::iter(&data.spaces_created)
-            .then(|event| async {
+        let timer = metrics::start_event_timer("spaces_created", data.spaces_created.len());
+        stream::iter(&data.spaces_created)
+            .map(Ok)
+            .try_for_each(|event| async {
                 self.handle_space_created(event, &data.edits_published, &data.block)
                     .await
+                    .map(|_| ())
             })
-            .try_collect::<Vec<_>>()
             .await?;
+        drop(timer);
+
+        // Handle successor space creation
+        if !data.successor_spaces_created.is_empty() {
+            tracing::info!(
+                "Block #{} ({}): Processing {} successor space created events",
+                data.block.block_number,
+                data.block.timestamp,
+                data.successor_spaces_created.len()
+            );
+        }
+        let timer = metrics::start_event_timer(
+            "successor_spaces_created",
+            data.successor_spaces_created.len(),
+        );
+        stream::iter(&data.successor_spaces_created)
+            .map(Ok)
+            .try_for_each(|event| async {
+                self.handle_successor_space_created(event, &data.block)
+                    .await
+            })
+            .await?;
+        drop(timer);
 
         if self.governance {
             // Handle personal space creation
@@ -235,12 +354,17 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.personal_plugins_created.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "personal_plugins_created",
+                data.personal_plugins_created.len(),
+            );
             stream::iter(&data.personal_plugins_created)
                 .map(Ok)
                 .try_for_each(|event| async {
                     self.handle_personal_space_created(event, &data.block).await
                 })
                 .await?;
+            drop(timer);
 
             // Handle new governance plugin creation
             if !data.governance_plugins_created.is_empty() {
@@ -251,6 +375,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.governance_plugins_created.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "governance_plugins_created",
+                data.governance_plugins_created.len(),
+            );
             stream::iter(&data.governance_plugins_created)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -258,6 +386,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             if !data.initial_editors_added.is_empty() {
                 tracing::info!(
@@ -267,6 +396,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.initial_editors_added.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "initial_editors_added",
+                data.initial_editors_added.len(),
+            );
             stream::iter(&data.initial_editors_added)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -274,6 +407,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             if !data.members_added.is_empty() {
                 tracing::info!(
@@ -283,10 +417,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.members_added.len()
                 );
             }
+            let timer = metrics::start_event_timer("members_added", data.members_added.len());
             stream::iter(&data.members_added)
                 .map(Ok)
                 .try_for_each(|event| async { self.handle_member_added(event, &data.block).await })
                 .await?;
+            drop(timer);
 
             if !data.members_removed.is_empty() {
                 tracing::info!(
@@ -296,12 +432,14 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.members_removed.len()
                 );
             }
+            let timer = metrics::start_event_timer("members_removed", data.members_removed.len());
             stream::iter(&data.members_removed)
                 .map(Ok)
                 .try_for_each(|event| async {
                     self.handle_member_removed(event, &data.block).await
                 })
                 .await?;
+            drop(timer);
 
             if !data.editors_added.is_empty() {
                 tracing::info!(
@@ -311,10 +449,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.editors_added.len()
                 );
             }
+            let timer = metrics::start_event_timer("editors_added", data.editors_added.len());
             stream::iter(&data.editors_added)
                 .map(Ok)
                 .try_for_each(|event| async { self.handle_editor_added(event, &data.block).await })
                 .await?;
+            drop(timer);
 
             if !data.editors_removed.is_empty() {
                 tracing::info!(
@@ -324,12 +464,14 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.editors_removed.len()
                 );
             }
+            let timer = metrics::start_event_timer("editors_removed", data.editors_removed.len());
             stream::iter(&data.editors_removed)
                 .map(Ok)
                 .try_for_each(|event| async {
                     self.handle_editor_removed(event, &data.block).await
                 })
                 .await?;
+            drop(timer);
         }
 
         if !data.subspaces_added.is_empty() {
@@ -340,10 +482,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                 data.subspaces_added.len()
             );
         }
+        let timer = metrics::start_event_timer("subspaces_added", data.subspaces_added.len());
         stream::iter(&data.subspaces_added)
             .map(Ok)
             .try_for_each(|event| async { self.handle_subspace_added(event, &data.block).await })
             .await?;
+        drop(timer);
 
         if !data.subspaces_removed.is_empty() {
             tracing::info!(
@@ -353,10 +497,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                 data.subspaces_removed.len()
             );
         }
+        let timer = metrics::start_event_timer("subspaces_removed", data.subspaces_removed.len());
         stream::iter(&data.subspaces_removed)
             .map(Ok)
             .try_for_each(|event| async { self.handle_subspace_removed(event, &data.block).await })
             .await?;
+        drop(timer);
 
         if self.governance {
             if !data.proposed_added_members.is_empty() {
@@ -367,6 +513,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_added_members.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_added_members",
+                data.proposed_added_members.len(),
+            );
             stream::iter(&data.proposed_added_members)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -374,6 +524,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             if !data.proposed_removed_members.is_empty() {
                 tracing::info!(
@@ -383,6 +534,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_removed_members.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_removed_members",
+                data.proposed_removed_members.len(),
+            );
             stream::iter(&data.proposed_removed_members)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -390,6 +545,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             if !data.proposed_added_editors.is_empty() {
                 tracing::info!(
@@ -399,6 +555,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_added_editors.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_added_editors",
+                data.proposed_added_editors.len(),
+            );
             stream::iter(&data.proposed_added_editors)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -406,6 +566,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             if !data.proposed_removed_editors.is_empty() {
                 tracing::info!(
@@ -415,6 +576,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_removed_editors.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_removed_editors",
+                data.proposed_removed_editors.len(),
+            );
             stream::iter(&data.proposed_removed_editors)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -422,6 +587,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             // Handle proposed add subspace
             if !data.proposed_added_subspaces.is_empty() {
@@ -432,6 +598,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_added_subspaces.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_added_subspaces",
+                data.proposed_added_subspaces.len(),
+            );
             stream::iter(&data.proposed_added_subspaces)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -439,6 +609,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             // Handle remove subspace proposal created
             if !data.proposed_removed_subspaces.is_empty() {
@@ -449,6 +620,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.proposed_removed_subspaces.len()
                 );
             }
+            let timer = metrics::start_event_timer(
+                "proposed_removed_subspaces",
+                data.proposed_removed_subspaces.len(),
+            );
             stream::iter(&data.proposed_removed_subspaces)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -456,6 +631,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             // Handle publish edit proposal created
             if !data.edits.is_empty() {
@@ -466,6 +642,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.edits.len()
                 );
             }
+            let timer = metrics::start_event_timer("edits", data.edits.len());
             stream::iter(&data.edits)
                 .map(Ok)
                 .try_for_each(|event| async {
@@ -473,6 +650,7 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                         .await
                 })
                 .await?;
+            drop(timer);
 
             // Handle vote cast
             if !data.votes_cast.is_empty() {
@@ -483,10 +661,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.votes_cast.len()
                 );
             }
+            let timer = metrics::start_event_timer("votes_cast", data.votes_cast.len());
             stream::iter(&data.votes_cast)
                 .map(Ok)
                 .try_for_each(|event| async { self.handle_vote_cast(event, &data.block).await })
                 .await?;
+            drop(timer);
         }
 
         // Handle edits published
@@ -498,8 +678,10 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                 data.edits_published.len()
             );
         }
-        self.handle_edits_published(data.edits_published, &created_space_ids, &data.block)
+        let timer = metrics::start_event_timer("edits_published", data.edits_published.len());
+        self.handle_edits_published(data.edits_published, &data.block)
             .await?;
+        drop(timer);
 
         if self.governance {
             // Handle proposal executed
@@ -511,6 +693,8 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     data.executed_proposals.len()
                 );
             }
+            let timer =
+                metrics::start_event_timer("executed_proposals", data.executed_proposals.len());
             stream::iter(&data.executed_proposals)
                 .enumerate()
                 .map(Ok)
@@ -519,6 +703,11 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
                     async move { self.handle_proposal_executed(event, block_ref, idx).await }
                 })
                 .await?;
+            drop(timer);
+
+            // Close the proposals whose voting period ended. This is done after the
+            // proposals executed in this block are marked as such.
+            self.update_proposal_statuses(&data.block).await?;
         }
 
         // Persist block number and timestamp
@@ -529,12 +718,12 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
             "0",
         )
         .triple(grc20_core::mapping::triple::Triple::new(
-            indexer_ids::CURSOR_ID,
+            self.cursor_id(),
             indexer_ids::BLOCK_NUMBER_ATTRIBUTE,
             data.block.block_number,
         ))
         .triple(grc20_core::mapping::triple::Triple::new(
-            indexer_ids::CURSOR_ID,
+            self.cursor_id(),
             indexer_ids::BLOCK_TIMESTAMP_ATTRIBUTE,
             data.block.timestamp,
         ))
@@ -544,11 +733,97 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
         Ok(())
     }
 
+    async fn process_block_undo_signal(
+        &self,
+        undo_signal: &BlockUndoSignal,
+    ) -> Result<(), Self::Error> {
+        let last_valid_block = undo_signal
+            .last_valid_block
+            .as_ref()
+            .ok_or(HandlerError::Other(
+                "Block undo signal without last valid block".into(),
+            ))?;
+
+        tracing::warn!(
+            "Block #{} ({}): Chain reorganization, rolling back all changes made after this block",
+            last_valid_block.number,
+            last_valid_block.id,
+        );
+
+        // Only the blocks up to the last processed one have written to the graph
+        let mut rollback =
+            grc20_core::mapping::rollback::rollback(&self.neo4j, last_valid_block.number);
+        if let Some(head) = reindex::cursor_block(&self.neo4j, self.cursor_id()).await? {
+            rollback = rollback.head(head);
+        }
+        rollback.send().await?;
+
+        metrics::HEAD_BLOCK_NUMBER.set(last_valid_block.number as f64);
+
+        // Persist block number. The block timestamp is unknown until the next block, so
+        // it is cleared: ended proposals are then looked up regardless of their end time.
+        grc20_core::mapping::triple::delete_one(
+            &self.neo4j,
+            &BlockMetadata::default(),
+            indexer_ids::BLOCK_TIMESTAMP_ATTRIBUTE,
+            self.cursor_id(),
+            indexer_ids::INDEXER_SPACE_ID,
+            "0",
+        )
+        .send()
+        .await?;
+
+        grc20_core::mapping::triple::Triple::new(
+            self.cursor_id(),
+            indexer_ids::BLOCK_NUMBER_ATTRIBUTE,
+            last_valid_block.number,
+        )
+        .insert(
+            &self.neo4j,
+            &BlockMetadata::default(),
+            indexer_ids::INDEXER_SPACE_ID,
+            "0",
+        )
+        .send()
+        .await?;
+
+        Ok(())
+    }
+
+    async fn apply_block_scoped_data(
+        &self,
+        raw_block: BlockScopedData,
+        data: preprocess::EventData,
+    ) -> Result<(), Self::Error> {
+        let block = data.block.clone();
+
+        transaction::transaction(&self.neo4j, async {
+            self.process_block_scoped_data(&raw_block, data).await?;
+            self.persist_cursor(raw_block.cursor.clone()).await
+        })
+        .await?;
+
+        self.health.record_block(&block);
+        Ok(())
+    }
+
+    async fn apply_block_undo_signal(
+        &self,
+        undo_signal: BlockUndoSignal,
+    ) -> Result<(), Self::Error> {
+        transaction::transaction(&self.neo4j, async {
+            self.process_block_undo_signal(&undo_signal).await?;
+            self.persist_cursor(undo_signal.last_valid_cursor.clone())
+                .await
+        })
+        .await
+    }
+
     async fn load_persisted_cursor(&self) -> Result<Option<String>, Self::Error> {
         let cursor = grc20_core::mapping::triple::find_one(
             &self.neo4j,
             indexer_ids::CURSOR_ATTRIBUTE,
-            indexer_ids::CURSOR_ID,
+            self.cursor_id(),
             indexer_ids::INDEXER_SPACE_ID,
             Some("0".to_string()),
         )
@@ -558,9 +833,21 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
         Ok(cursor.map(|c| c.value.value))
     }
 
+    async fn shutdown_signal(&self) {
+        self.health.shutdown_requested().await
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy.clone()
+    }
+
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        error.is_retryable()
+    }
+
     async fn persist_cursor(&self, cursor: String) -> Result<(), Self::Error> {
         grc20_core::mapping::triple::Triple::new(
-            indexer_ids::CURSOR_ID,
+            self.cursor_id(),
             indexer_ids::CURSOR_ATTRIBUTE,
             cursor,
         )
@@ -2,12 +2,14 @@ pub mod handler;
 
 mod edit_published;
 mod editors;
+mod embedding;
 mod members;
 mod proposal_created;
 mod proposal_executed;
 mod space_created;
 mod subspaces;
+mod successor_space_created;
 mod vote_cast;
 
-pub use edit_published::Edit;
+pub use edit_published::{decode_edit, Edit, EditContent, RejectedEdit};
 pub use handler::{EventHandler, HandlerError};
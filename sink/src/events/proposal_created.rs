@@ -8,7 +8,7 @@ use grc20_core::{
 };
 use grc20_sdk::models::{
     account,
-    proposal::{ProposalStatus, ProposedAccount, ProposedSubspace},
+    proposal::{ProposalStatus, ProposedAccount, ProposedSubspace, VoteTally},
     space, AddEditorProposal, AddMemberProposal, AddSubspaceProposal, EditProposal, Proposal,
     ProposalCreator, Proposals, RemoveEditorProposal, RemoveMemberProposal, RemoveSubspaceProposal,
 };
@@ -33,6 +33,7 @@ impl EventHandler {
             plugin_address: checksum_address(&add_member_proposal.plugin_address),
             start_time: add_member_proposal.start_time.clone(),
             end_time: add_member_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_account_related_proposals(
@@ -63,6 +64,7 @@ impl EventHandler {
             plugin_address: checksum_address(&remove_member_proposal.plugin_address),
             start_time: remove_member_proposal.start_time.clone(),
             end_time: remove_member_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_account_related_proposals(
@@ -93,6 +95,7 @@ impl EventHandler {
             plugin_address: checksum_address(&add_editor_proposal.plugin_address),
             start_time: add_editor_proposal.start_time.clone(),
             end_time: add_editor_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_account_related_proposals(
@@ -123,6 +126,7 @@ impl EventHandler {
             plugin_address: checksum_address(&remove_editor_proposal.plugin_address),
             start_time: remove_editor_proposal.start_time.clone(),
             end_time: remove_editor_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_account_related_proposals(
@@ -153,6 +157,7 @@ impl EventHandler {
             plugin_address: checksum_address(&add_subspace_proposal.plugin_address),
             start_time: add_subspace_proposal.start_time.clone(),
             end_time: add_subspace_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_subspace_related_proposals(
@@ -184,6 +189,7 @@ impl EventHandler {
             plugin_address: checksum_address(&remove_subspace_proposal.plugin_address),
             start_time: remove_subspace_proposal.start_time.clone(),
             end_time: remove_subspace_proposal.end_time.clone(),
+            votes: VoteTally::default(),
         });
 
         self.create_subspace_related_proposals(
@@ -213,6 +219,7 @@ impl EventHandler {
                 plugin_address: checksum_address(&publish_edit_proposal.plugin_address),
                 start_time: publish_edit_proposal.start_time.clone(),
                 end_time: publish_edit_proposal.end_time.clone(),
+                votes: VoteTally::default(),
             },
             publish_edit_proposal.content_uri.clone(),
         );
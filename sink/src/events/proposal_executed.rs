@@ -1,11 +1,12 @@
 use super::{handler::HandlerError, EventHandler};
+use crate::reindex;
 use grc20_core::{
-    block::BlockMetadata,
-    indexer_ids,
-    mapping::{Query, Triple},
-    pb::geo,
+    block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::query_utils::Query, pb::geo,
+};
+use grc20_sdk::models::{
+    proposal::{EndedProposal, ProposalStatus, VoteTally},
+    space, Proposal,
 };
-use grc20_sdk::models::{proposal::ProposalStatus, Proposal};
 
 impl EventHandler {
     pub async fn handle_proposal_executed(
@@ -19,16 +20,76 @@ impl EventHandler {
             &proposal_executed.proposal_id,
         );
 
-        // Update proposal status
-        Triple::new(
-            &proposal_id,
-            "status", // TODO: Change to GRC20 id
-            ProposalStatus::Executed,
-        )
-        .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
-        .send()
-        .await?;
+        Proposal::set_status(&self.neo4j, block, &proposal_id, ProposalStatus::Executed).await?;
+
+        Ok(())
+    }
+
+    /// Closes the open proposals whose voting period ended since the previously
+    /// processed block and at or before `block`, marking them as accepted or rejected
+    /// depending on their votes (see [`ProposalStatus::evaluate`]).
+    ///
+    /// Must be called before the timestamp of `block` is persisted on the cursor.
+    pub async fn update_proposal_statuses(
+        &self,
+        block: &BlockMetadata,
+    ) -> Result<(), HandlerError> {
+        let since = reindex::cursor_timestamp(&self.neo4j, self.cursor_id()).await?;
+        let ended_proposals =
+            Proposal::find_ended(&self.neo4j, since.as_ref(), &block.timestamp).await?;
+
+        for proposal in ended_proposals {
+            let tally = Proposal::vote_tally(&self.neo4j, &proposal.id).await?;
+            self.close_proposal(block, &proposal, &tally).await?;
+        }
 
         Ok(())
     }
+
+    /// Sets the status of the ended proposal `proposal` given the votes `tally` cast on
+    /// it. The voting settings indexed on its space are used, or the ones of the handler
+    /// if its space has none (e.g.: spaces indexed before the settings were).
+    pub(crate) async fn close_proposal(
+        &self,
+        block: &BlockMetadata,
+        proposal: &EndedProposal,
+        tally: &VoteTally,
+    ) -> Result<(), DatabaseError> {
+        let (settings, voting_power) = match &proposal.space_id {
+            Some(space_id) => {
+                let settings =
+                    space::find_one(&self.neo4j, space_id, indexer_ids::INDEXER_SPACE_ID)
+                        .send()
+                        .await?
+                        .and_then(|space| space.attributes.voting_settings());
+                (
+                    settings.unwrap_or(self.voting_settings),
+                    space::editor_count(&self.neo4j, space_id).await?,
+                )
+            }
+            None => (self.voting_settings, 0),
+        };
+
+        let status = ProposalStatus::Proposed.evaluate(
+            proposal.end_time,
+            block.timestamp.timestamp(),
+            tally,
+            &settings,
+            voting_power,
+        );
+
+        tracing::info!(
+            "Block #{} ({}): Proposal {} ended ({} accept, {} reject, {} abstain votes, {} editors): {}",
+            block.block_number,
+            block.timestamp,
+            proposal.id,
+            tally.accept,
+            tally.reject,
+            tally.abstain,
+            voting_power,
+            status
+        );
+
+        Proposal::set_status(&self.neo4j, block, &proposal.id, status).await
+    }
 }
@@ -9,14 +9,14 @@ use grc20_sdk::models::{account, space, SpaceGovernanceType};
 
 use web3_utils::checksum_address;
 
-use super::{handler::HandlerError, Edit, EventHandler};
+use super::{handler::HandlerError, EditContent, EventHandler};
 
 impl EventHandler {
     /// Handles `GeoSpaceCreated` events.
     pub async fn handle_space_created(
         &self,
         space_created: &geo::GeoSpaceCreated,
-        edits_published: &[(geo::EditPublished, Vec<Edit>)],
+        edits_published: &[(geo::EditPublished, EditContent)],
         block: &BlockMetadata,
     ) -> Result<String, HandlerError> {
         let maybe_initial_proposal = edits_published.iter().find(|proposal| {
@@ -34,23 +34,36 @@ impl EventHandler {
                 if let Ok(metadata) = ipfs::deserialize::<pb::ipfs::IpfsMetadata>(&bytes) {
                     match metadata.r#type() {
                         pb::ipfs::ActionType::ImportSpace => {
-                            let import = ipfs::deserialize::<pb::ipfs::Import>(&bytes)?;
-
-                            tracing::info!(
-                                "Block #{} ({}): Found import for space {} (derived id: {})",
-                                block.block_number,
-                                block.timestamp,
-                                checksum_address(&space_created.space_address),
-                                space::new_id(
-                                    &import.previous_network,
-                                    &import.previous_contract_address,
-                                )
-                            );
-
-                            Some(space::new_id(
-                                &import.previous_network,
-                                &import.previous_contract_address,
-                            ))
+                            match ipfs::deserialize::<pb::ipfs::Import>(&bytes) {
+                                Ok(import) => {
+                                    tracing::info!(
+                                        "Block #{} ({}): Found import for space {} (derived id: {})",
+                                        block.block_number,
+                                        block.timestamp,
+                                        checksum_address(&space_created.space_address),
+                                        space::new_id(
+                                            &import.previous_network,
+                                            &import.previous_contract_address,
+                                        )
+                                    );
+
+                                    Some(space::new_id(
+                                        &import.previous_network,
+                                        &import.previous_contract_address,
+                                    ))
+                                }
+                                // Invalid imports are recorded as failed edits when the edit is processed
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "Block #{} ({}): Invalid import for space {}: {}",
+                                        block.block_number,
+                                        block.timestamp,
+                                        checksum_address(&space_created.space_address),
+                                        e
+                                    );
+                                    None
+                                }
+                            }
                         }
                         _ => None,
                     }
@@ -148,6 +161,14 @@ impl EventHandler {
                 .attribute((
                     indexer_ids::SPACE_MEMBER_PLUGIN_ADDRESS,
                     checksum_address(&governance_plugin_created.member_access_address),
+                ))
+                .attribute((
+                    indexer_ids::SPACE_SUPPORT_THRESHOLD,
+                    self.voting_settings.support_threshold,
+                ))
+                .attribute((
+                    indexer_ids::SPACE_MIN_PARTICIPATION,
+                    self.voting_settings.min_participation,
                 )),
         )
         .send()
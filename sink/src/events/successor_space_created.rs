@@ -0,0 +1,76 @@
+use grc20_core::{block::BlockMetadata, indexer_ids, mapping::query_utils::Query, pb::geo};
+use grc20_sdk::models::{space, space::SuccessorSpace};
+use web3_utils::checksum_address;
+
+use super::{handler::HandlerError, EventHandler};
+
+impl EventHandler {
+    /// Handles `SuccessorSpaceCreated` events by linking the predecessor space to
+    /// its successor.
+    pub async fn handle_successor_space_created(
+        &self,
+        successor_space_created: &geo::SuccessorSpaceCreated,
+        block: &BlockMetadata,
+    ) -> Result<(), HandlerError> {
+        // The predecessor may be referenced either by its DAO address or by its space plugin address
+        let predecessor = match space::find_entity_by_dao_address(
+            &self.neo4j,
+            &successor_space_created.predecessor_space,
+        )
+        .await?
+        {
+            Some(predecessor) => Some(predecessor),
+            None => {
+                space::find_entity_by_space_plugin_address(
+                    &self.neo4j,
+                    &successor_space_created.predecessor_space,
+                )
+                .await?
+            }
+        };
+
+        // The successor space is looked up by its plugin address since its ID may have been
+        // derived from an imported space.
+        let successor = space::find_entity_by_space_plugin_address(
+            &self.neo4j,
+            &successor_space_created.plugin_address,
+        )
+        .await?;
+
+        match (predecessor, successor) {
+            (Some(predecessor), Some(successor)) => {
+                SuccessorSpace::new(&predecessor.id, &successor.id)
+                    .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+                    .send()
+                    .await?;
+
+                tracing::info!(
+                    "Block #{} ({}): Linked space {} to its successor space {}",
+                    block.block_number,
+                    block.timestamp,
+                    predecessor.id,
+                    successor.id,
+                );
+            }
+            (None, _) => {
+                tracing::warn!(
+                    "Block #{} ({}): Could not find predecessor space {} of successor space with plugin_address = {}",
+                    block.block_number,
+                    block.timestamp,
+                    checksum_address(&successor_space_created.predecessor_space),
+                    checksum_address(&successor_space_created.plugin_address),
+                );
+            }
+            (_, None) => {
+                tracing::warn!(
+                    "Block #{} ({}): Could not find successor space with plugin_address = {}",
+                    block.block_number,
+                    block.timestamp,
+                    checksum_address(&successor_space_created.plugin_address),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use grc20_core::{block::BlockMetadata, indexer_ids, mapping::query_utils::Query, pb::geo};
-use grc20_sdk::models::{account, Proposal, VoteCast};
+use grc20_sdk::models::{account, Proposal, VoteCast, VoteType};
 
 use super::{handler::HandlerError, EventHandler};
 
@@ -11,17 +11,25 @@ impl EventHandler {
     ) -> Result<(), HandlerError> {
         let proposal_id = Proposal::gen_id(&vote.plugin_address, &vote.onchain_proposal_id);
         let account_id = account::new_id(&vote.voter);
+        let vote_type: VoteType = vote
+            .vote_option
+            .try_into()
+            .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?;
 
-        VoteCast::new(
-            &account_id,
-            &proposal_id,
-            vote.vote_option
-                .try_into()
-                .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?,
-        )
-        .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
-        .send()
-        .await?;
+        // Update the tally incrementally: a voter changing their vote moves it from
+        // one count to the other without being counted twice.
+        let previous_vote =
+            VoteCast::find_vote_type(&self.neo4j, &account_id, &proposal_id).await?;
+        if previous_vote != Some(vote_type) {
+            let mut tally = Proposal::vote_tally(&self.neo4j, &proposal_id).await?;
+            tally.apply(previous_vote, vote_type);
+            Proposal::set_vote_tally(&self.neo4j, block, &proposal_id, tally).await?;
+        }
+
+        VoteCast::new(&account_id, &proposal_id, vote_type)
+            .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await?;
 
         Ok(())
     }
@@ -0,0 +1,239 @@
+//! Liveness and readiness of the sink.
+//!
+//! - `/health/live` reports whether the process is up. It does not depend on Neo4j or
+//!   on the indexing progress so that orchestrators do not restart a sink which is
+//!   merely catching up.
+//! - `/health/ready` reports whether the sink is up to date: Neo4j is reachable, at
+//!   least one block was processed, the last processed block is less than
+//!   `max_head_lag` behind the current time and no shutdown was requested. It
+//!   answers `503 Service Unavailable` otherwise.
+//!
+//! Both endpoints return the last processed block and the head lag.
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use axum::{extract::State, http::StatusCode, response::Json, routing::get, Router};
+use chrono::{DateTime, Utc};
+use grc20_core::{block::BlockMetadata, neo4rs};
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// Maximum time to wait for Neo4j when checking readiness
+const NEO4J_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Progress and shutdown state of the sink. Clones share the same state.
+#[derive(Clone)]
+pub struct HealthState {
+    last_block: Arc<RwLock<Option<LastBlock>>>,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct LastBlock {
+    pub number: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self {
+            last_block: Arc::default(),
+            shutdown: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl HealthState {
+    /// Records `block` as the last block processed (and committed) by the sink
+    pub fn record_block(&self, block: &BlockMetadata) {
+        *self.last_block.write().expect("Health state lock poisoned") = Some(LastBlock {
+            number: block.block_number,
+            timestamp: block.timestamp,
+        });
+    }
+
+    pub fn last_block(&self) -> Option<LastBlock> {
+        *self.last_block.read().expect("Health state lock poisoned")
+    }
+
+    /// Asks the sink to stop after the block it is currently processing
+    pub fn request_shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Resolves once a shutdown was requested
+    pub async fn shutdown_requested(&self) {
+        let mut shutdown = self.shutdown.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = shutdown.wait_for(|shutdown| *shutdown).await;
+    }
+
+    /// Evaluates the readiness of the sink at time `now` given the result of the
+    /// Neo4j connectivity check
+    pub fn readiness(
+        &self,
+        neo4j: Result<(), String>,
+        max_head_lag: Duration,
+        now: DateTime<Utc>,
+    ) -> Readiness {
+        let last_block = self.last_block();
+        let head_lag = last_block.map(|block| (now - block.timestamp).num_seconds().max(0));
+        let shutting_down = self.is_shutting_down();
+
+        let reason = match (&neo4j, head_lag) {
+            _ if shutting_down => Some("shutting down".to_string()),
+            (Err(e), _) => Some(format!("Neo4j unreachable: {e}")),
+            (_, None) => Some("no block processed yet".to_string()),
+            (_, Some(lag)) if lag as u64 > max_head_lag.as_secs() => Some(format!(
+                "head lag of {lag}s exceeds {}s",
+                max_head_lag.as_secs()
+            )),
+            _ => None,
+        };
+
+        Readiness {
+            ready: reason.is_none(),
+            reason,
+            neo4j: match neo4j {
+                Ok(()) => "connected".to_string(),
+                Err(e) => e,
+            },
+            last_block,
+            head_lag_seconds: head_lag,
+            shutting_down,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    /// Why the sink is not ready, if it is not
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub neo4j: String,
+    pub last_block: Option<LastBlock>,
+    pub head_lag_seconds: Option<i64>,
+    pub shutting_down: bool,
+}
+
+#[derive(Clone)]
+struct HealthContext {
+    state: HealthState,
+    neo4j: neo4rs::Graph,
+    max_head_lag: Duration,
+}
+
+/// Routes of the health endpoints. `/health` is kept as an alias of `/health/live`.
+pub fn routes(state: HealthState, neo4j: neo4rs::Graph, max_head_lag: Duration) -> Router {
+    Router::new()
+        .route("/health", get(liveness))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+        .with_state(HealthContext {
+            state,
+            neo4j,
+            max_head_lag,
+        })
+}
+
+async fn liveness(State(context): State<HealthContext>) -> Json<serde_json::Value> {
+    let last_block = context.state.last_block();
+
+    Json(serde_json::json!({
+        "component": "sink",
+        "status": "ok",
+        "last_block": last_block,
+        "head_lag_seconds": last_block.map(|block| (Utc::now() - block.timestamp).num_seconds()),
+        "shutting_down": context.state.is_shutting_down(),
+    }))
+}
+
+async fn readiness(State(context): State<HealthContext>) -> (StatusCode, Json<Readiness>) {
+    let neo4j =
+        match tokio::time::timeout(NEO4J_TIMEOUT, context.neo4j.run(neo4rs::query("RETURN 1")))
+            .await
+        {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("no answer within {}s", NEO4J_TIMEOUT.as_secs())),
+        };
+
+    let readiness = context
+        .state
+        .readiness(neo4j, context.max_head_lag, Utc::now());
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(readiness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(block_number: u64, timestamp: i64) -> BlockMetadata {
+        BlockMetadata {
+            block_number,
+            timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_readiness() {
+        let state = HealthState::default();
+        let max_head_lag = Duration::from_secs(60);
+        let now = DateTime::from_timestamp(1000, 0).unwrap();
+
+        let readiness = state.readiness(Ok(()), max_head_lag, now);
+        assert!(!readiness.ready);
+        assert_eq!(readiness.reason.as_deref(), Some("no block processed yet"));
+
+        state.record_block(&block(10, 900));
+        let readiness = state.readiness(Ok(()), max_head_lag, now);
+        assert!(!readiness.ready);
+        assert_eq!(readiness.head_lag_seconds, Some(100));
+
+        state.record_block(&block(11, 970));
+        let readiness = state.readiness(Ok(()), max_head_lag, now);
+        assert!(readiness.ready);
+        assert_eq!(readiness.last_block.map(|block| block.number), Some(11));
+
+        let readiness = state.readiness(Err("connection refused".to_string()), max_head_lag, now);
+        assert!(!readiness.ready);
+        assert_eq!(readiness.neo4j, "connection refused");
+
+        state.request_shutdown();
+        assert!(!state.readiness(Ok(()), max_head_lag, now).ready);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_requested() {
+        let state = HealthState::default();
+        let waiter = tokio::spawn({
+            let state = state.clone();
+            async move { state.shutdown_requested().await }
+        });
+
+        state.request_shutdown();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("Shutdown not signaled")
+            .unwrap();
+
+        // Already requested
+        tokio::time::timeout(Duration::from_secs(1), state.shutdown_requested())
+            .await
+            .expect("Shutdown not signaled");
+    }
+}
@@ -1,5 +1,10 @@
 pub mod blacklist;
 pub mod bootstrap;
+pub mod config;
 pub mod events;
+pub mod health;
 pub mod metrics;
+pub mod migrations;
+pub mod offline;
 pub mod preprocess;
+pub mod reindex;
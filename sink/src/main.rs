@@ -1,107 +1,119 @@
-use std::{env, sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::Error;
-use axum::{response::Json, routing::get, Router};
+use axum::{routing::get, Router};
 use cache::{CacheConfig, KgCache};
-use clap::{Args, Parser};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use embedding::{Embedder, FastEmbedder};
 use grc20_core::{
     block::BlockMetadata,
+    error::DatabaseError,
     indexer_ids,
-    mapping::{self, query_utils::Query, triple},
+    mapping::{query_utils::Query, triple, Triple},
     neo4rs,
 };
-use sink::bootstrap;
-use sink::{events::EventHandler, metrics};
+use grc20_sdk::models::{proposal::VotingSettings, EmbeddingConfig};
+use ipfs::IpfsClient;
+use sink::config::Config;
+use sink::health::{self, HealthState};
+use sink::migrations::{self, MigrationAction, MigrationOutcome};
+use sink::offline;
+use sink::reindex::{self, ReindexScope};
+use sink::{blacklist, bootstrap};
+use sink::{
+    events::{
+        handler::{DEFAULT_EDIT_CONCURRENCY, DEFAULT_IPFS_GATEWAY},
+        EventHandler,
+    },
+    metrics,
+};
 use std::time::Duration;
-use substreams_utils::Sink;
+use substreams_utils::{
+    recording,
+    sink::{read_block_range, read_package, set_module_params},
+    substreams::EndpointPool,
+    substreams_stream::SubstreamsStream,
+    BlockRange, Sink,
+};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
-const PKG_FILE: &str = "geo-substream.spkg";
-const MODULE_NAME: &str = "geo_out";
-
-const DEFAULT_START_BLOCK: u64 = 880;
-const DEFAULT_END_BLOCK: u64 = 0;
-const DEFAULT_HTTP_PORT: u16 = 8081;
-
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let endpoint_url =
-        env::var("SUBSTREAMS_ENDPOINT_URL").expect("SUBSTREAMS_ENDPOINT_URL not set");
-    let start_block = env::var("SUBSTREAMS_START_BLOCK").unwrap_or_else(|_| {
-        tracing::warn!(
-            "SUBSTREAMS_START_BLOCK not set. Using default value: {}",
-            DEFAULT_START_BLOCK
-        );
-        DEFAULT_START_BLOCK.to_string()
-    });
-    let end_block = env::var("SUBSTREAMS_END_BLOCK").unwrap_or_else(|_| {
-        tracing::warn!(
-            "SUBSTREAMS_END_BLOCK not set. Using default value: {}",
-            DEFAULT_END_BLOCK
-        );
-        DEFAULT_END_BLOCK.to_string()
-    });
-
     let args = AppArgs::parse();
 
     set_log_level();
-    let _guard = init_tracing(args.log_file);
-
-    let neo4j = neo4rs::Graph::new(
-        &args.neo4j_args.neo4j_uri,
-        &args.neo4j_args.neo4j_user,
-        &args.neo4j_args.neo4j_pass,
-    )
-    .await?;
+    let _guard = init_tracing(args.log_file.clone());
 
-    let cache = if let Some(uri) = args.cache_args.memcache_uri {
-        let cache_config = CacheConfig::new(vec![uri])
-            .with_default_expiry(Duration::from_secs(args.cache_args.memcache_default_expiry));
-        Some(Arc::new(KgCache::new(cache_config)?))
-    } else {
-        None
-    };
+    let config = args.config()?;
+    config.validate_neo4j()?;
 
-    let sink = EventHandler::new(neo4j, cache)?
-        .versioning(!args.no_versioning)
-        .governance(!args.no_governance);
+    let neo4j =
+        neo4rs::Graph::new(config.neo4j_uri()?, &config.neo4j.user, &config.neo4j.pass).await?;
 
-    if args.reset_db {
-        reset_db(&sink).await?;
-    } else {
-        migration_check(&sink).await?;
+    match &args.command {
+        None | Some(Command::Run) => run(&args, &config, neo4j).await,
+        Some(Command::Reset) => {
+            let handler = build_handler(&args, neo4j)?;
+            reset_db(&handler).await?;
+            tracing::info!("Database reset");
+            Ok(())
+        }
+        Some(Command::Status) => status(&neo4j).await,
+        Some(Command::Verify) => verify(&args, &config, &neo4j).await,
+        Some(Command::Reindex(reindex_args)) => {
+            config.validate_substreams()?;
+            let handler = build_handler(&args, neo4j)?;
+            reindex(handler, &config, reindex_args).await
+        }
+        Some(Command::Ingest(ingest_args)) => {
+            let handler = build_handler(&args, neo4j)?;
+            ingest(&args, handler, ingest_args).await
+        }
+        Some(Command::Record(record_args)) => {
+            config.validate_substreams()?;
+            record(&config, neo4j, record_args).await
+        }
+        Some(Command::Replay(replay_args)) => {
+            let handler = build_handler(&args, neo4j)?;
+            replay(&args, handler, replay_args).await
+        }
     }
-
-    start_http_server().await;
-
-    sink.run(
-        &endpoint_url,
-        PKG_FILE,
-        MODULE_NAME,
-        start_block
-            .parse()
-            .unwrap_or_else(|_| panic!("Invalid start block: {}! Must be integer", start_block)),
-        end_block
-            .parse()
-            .unwrap_or_else(|_| panic!("Invalid end block: {}! Must be integer", end_block)),
-        Some(64),
-    )
-    .await?;
-
-    Ok(())
 }
 
 #[derive(Debug, Parser)]
-#[command(name = "stdout", version, about, arg_required_else_help = true)]
+#[command(name = "stdout", version, about)]
 struct AppArgs {
+    /// Runs the sink if omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// TOML config file. Defaults to `sink.toml` if it exists. Environment variables
+    /// and command line flags override the settings of the file.
+    #[arg(long, env = "SINK_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
     #[clap(flatten)]
     neo4j_args: Neo4jArgs,
 
     #[clap(flatten)]
     cache_args: CacheArgs,
 
-    /// Whether or not to reset the database
+    /// Embedding model used to index triples. Changing it requires resetting the database.
+    #[arg(long, env = "EMBEDDING_MODEL", default_value = embedding::DEFAULT_MODEL)]
+    embedding_model: String,
+
+    /// Maximum number of spaces whose edits are processed concurrently within a block.
+    /// The edits of a space are written in block order.
+    #[arg(long, env = "EDIT_CONCURRENCY", default_value_t = DEFAULT_EDIT_CONCURRENCY)]
+    edit_concurrency: usize,
+
+    /// Blacklist file (spaces, authors and DAOs whose edits are skipped). Changes
+    /// to the file are picked up without restarting the sink.
+    #[arg(long, env = "BLACKLIST_FILE", default_value = blacklist::DEFAULT_BLACKLIST_FILE)]
+    blacklist_file: String,
+
+    /// Whether or not to reset the database before running the sink
     #[arg(long)]
     reset_db: bool,
 
@@ -116,21 +128,164 @@ struct AppArgs {
     /// Whether to index governance events
     #[arg(long, default_value = "false")]
     no_governance: bool,
+
+    /// Support threshold of the governance plugins, in parts per million: proposals
+    /// pass with a larger share of accept votes among accept and reject votes. The
+    /// substreams output does not include the settings of the plugins.
+    #[arg(long, env = "SUPPORT_THRESHOLD", default_value_t = VotingSettings::default().support_threshold)]
+    support_threshold: u64,
+
+    /// Minimum participation of the governance plugins, in parts per million of the
+    /// editors of the space
+    #[arg(long, env = "MIN_PARTICIPATION", default_value_t = VotingSettings::default().min_participation)]
+    min_participation: u64,
+
+    /// Only stream final (irreversible) blocks. This trails the chain head but
+    /// guarantees that no block will ever need to be rolled back.
+    #[arg(long, default_value = "false")]
+    final_blocks_only: bool,
+
+    /// Range of blocks to stream, resolved against the initial block of the module
+    /// (e.g.: `1000:+500`, `+10:`, `:-`). Overrides `substreams.block_range`.
+    #[arg(long, allow_hyphen_values = true)]
+    block_range: Option<String>,
+
+    /// Parameter of a module of the package, as `<module>=<value>` (can be repeated).
+    /// Overrides `substreams.params`.
+    #[arg(long = "param", value_parser = parse_module_param)]
+    params: Vec<(String, String)>,
+}
+
+fn parse_module_param(param: &str) -> Result<(String, String), String> {
+    param
+        .split_once('=')
+        .map(|(module, value)| (module.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected <module>=<value>, got {param:?}"))
+}
+
+impl AppArgs {
+    /// Loads the config and applies the command line overrides
+    fn config(&self) -> anyhow::Result<Config> {
+        let mut config = Config::load(self.config.as_deref())?;
+
+        if let Some(uri) = &self.neo4j_args.neo4j_uri {
+            config.neo4j.uri = Some(uri.clone());
+        }
+        if let Some(user) = &self.neo4j_args.neo4j_user {
+            config.neo4j.user = user.clone();
+        }
+        if let Some(pass) = &self.neo4j_args.neo4j_pass {
+            config.neo4j.pass = pass.clone();
+        }
+        if self.final_blocks_only {
+            config.substreams.final_blocks_only = true;
+        }
+        if let Some(block_range) = &self.block_range {
+            config.substreams.block_range = Some(block_range.clone());
+        }
+        config.substreams.params.extend(self.params.iter().cloned());
+
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the sink (default)
+    Run,
+    /// Reset the database and exit
+    Reset,
+    /// Print the indexing status: cursor, last indexed block and database version
+    Status,
+    /// Check the config, the substreams package, the Neo4j connection and whether the
+    /// database can be used as is by this version of the sink
+    Verify,
+    /// Delete and rebuild the data of the given spaces and/or from the given block
+    /// up to the last indexed block, leaving the main cursor and other spaces
+    /// untouched. The sink must be stopped while re-indexing.
+    Reindex(ReindexArgs),
+    /// Process edits read from local files (a manifest or a directory of `*.pb` files)
+    /// instead of streaming them from a substreams endpoint
+    Ingest(IngestArgs),
+    /// Record the blocks streamed from the configured substreams endpoint to a file,
+    /// without processing them, until the end of the block range or SIGINT/SIGTERM
+    Record(RecordArgs),
+    /// Process the blocks of a recording file (see `record`) instead of streaming them
+    Replay(ReplayArgs),
 }
 
 #[derive(Debug, Args)]
-struct Neo4jArgs {
-    /// Neo4j database host
+#[command(group(ArgGroup::new("scope").required(true).multiple(true).args(["spaces", "start_block"])))]
+struct ReindexArgs {
+    /// ID of a space to re-index (can be repeated). All spaces are re-indexed if omitted.
+    #[arg(long = "space")]
+    spaces: Vec<String>,
+
+    /// First block to re-index (defaults to the start block of the sink)
     #[arg(long)]
-    neo4j_uri: String,
+    start_block: Option<u64>,
+}
 
-    /// Neo4j database user name
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("source").required(true).args(["manifest", "dir"])))]
+struct IngestArgs {
+    /// TOML manifest listing the edit files and their space and block metadata
     #[arg(long)]
-    neo4j_user: String,
+    manifest: Option<PathBuf>,
+
+    /// Directory of `*.pb` edit files, processed in file name order (one block per file)
+    #[arg(long, requires = "dao_address")]
+    dir: Option<PathBuf>,
 
-    /// Neo4j database user password
+    /// DAO address of the space the files of `--dir` are published to
     #[arg(long)]
-    neo4j_pass: String,
+    dao_address: Option<String>,
+
+    /// Space plugin address of the space (defaults to the DAO address)
+    #[arg(long)]
+    plugin_address: Option<String>,
+
+    /// Block of the first edit without an explicit block
+    #[arg(long, default_value_t = 1)]
+    start_block: u64,
+}
+
+#[derive(Debug, Args)]
+struct RecordArgs {
+    /// Recording file to create
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Cursor to resume streaming from. Defaults to the cursor of the sink, if
+    /// `--from-sink-cursor` is set, or to the start of the block range otherwise.
+    #[arg(long)]
+    cursor: Option<String>,
+
+    /// Resume streaming from the cursor of the sink
+    #[arg(long, conflicts_with = "cursor")]
+    from_sink_cursor: bool,
+}
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    /// Recording file to process
+    #[arg(long)]
+    input: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct Neo4jArgs {
+    /// Neo4j database host (overrides `neo4j.uri`)
+    #[arg(long, global = true)]
+    neo4j_uri: Option<String>,
+
+    /// Neo4j database user name (overrides `neo4j.user`)
+    #[arg(long, global = true)]
+    neo4j_user: Option<String>,
+
+    /// Neo4j database user password (overrides `neo4j.pass`)
+    #[arg(long, global = true)]
+    neo4j_pass: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -144,6 +299,453 @@ struct CacheArgs {
     memcache_default_expiry: u64,
 }
 
+fn build_handler(args: &AppArgs, neo4j: neo4rs::Graph) -> anyhow::Result<EventHandler> {
+    let cache = if let Some(uri) = &args.cache_args.memcache_uri {
+        let cache_config = CacheConfig::new(vec![uri.clone()])
+            .with_default_expiry(Duration::from_secs(args.cache_args.memcache_default_expiry));
+        Some(Arc::new(KgCache::new(cache_config)?))
+    } else {
+        None
+    };
+
+    let embedder = FastEmbedder::new(&args.embedding_model)?;
+    tracing::info!(
+        "Using embedding model {} (dim = {})",
+        args.embedding_model,
+        embedder.dim()
+    );
+
+    Ok(EventHandler::new_with_embedder(
+        neo4j,
+        IpfsClient::from_url(DEFAULT_IPFS_GATEWAY),
+        cache,
+        Arc::new(embedder),
+    )
+    .versioning(!args.no_versioning)
+    .governance(!args.no_governance)
+    .edit_concurrency(args.edit_concurrency)
+    .voting_settings(VotingSettings {
+        support_threshold: args.support_threshold,
+        min_participation: args.min_participation,
+    })
+    .blacklist_file(&args.blacklist_file))
+}
+
+async fn run(args: &AppArgs, config: &Config, neo4j: neo4rs::Graph) -> anyhow::Result<()> {
+    config.validate_substreams()?;
+
+    let handler = build_handler(args, neo4j)?.retry_policy(config.retry_policy()?);
+
+    if args.reset_db {
+        reset_db(&handler).await?;
+    } else {
+        migration_check(&handler).await?;
+    }
+
+    start_http_server(config, handler.neo4j().clone(), handler.health().clone()).await?;
+
+    // On SIGTERM/SIGINT, stop pulling blocks once the current one is committed
+    let health = handler.health().clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("Shutdown requested, finishing the current block");
+        health.request_shutdown();
+    });
+
+    let substreams = &config.substreams;
+    handler
+        .run(
+            endpoint_pool(config).await?,
+            &substreams.package,
+            &substreams.module,
+            config.block_range()?,
+            &config.module_params(),
+            substreams.final_blocks_only,
+            Some(64),
+        )
+        .await
+        .inspect_err(|e| {
+            if e.is_retryable() {
+                tracing::error!(
+                    "Giving up on a transient error, restarting the sink may succeed: {e}"
+                );
+            }
+        })?;
+
+    if handler.health().is_shutting_down() {
+        match handler.health().last_block() {
+            Some(block) => tracing::info!("Sink stopped after block #{}", block.number),
+            None => tracing::info!("Sink stopped before processing any block"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the pool of substreams endpoints the sink streams from (see
+/// [`Config::endpoints`])
+async fn endpoint_pool(config: &Config) -> anyhow::Result<EndpointPool> {
+    let endpoints = config.endpoints()?;
+    tracing::info!(
+        "Substreams endpoints (in failover order): {}",
+        endpoints
+            .iter()
+            .map(|endpoint| endpoint.url.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Ok(EndpointPool::new(&endpoints)
+        .await?
+        .failures_before_failover(config.substreams.failures_before_failover))
+}
+
+/// Resolves when the process receives SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Returns the value of `attribute` of the indexer cursor
+async fn cursor_value(
+    neo4j: &neo4rs::Graph,
+    attribute: &str,
+) -> Result<Option<String>, DatabaseError> {
+    Ok(triple::find_one(
+        neo4j,
+        attribute,
+        indexer_ids::CURSOR_ID,
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await?
+    .map(|triple| triple.value.value))
+}
+
+async fn status(neo4j: &neo4rs::Graph) -> anyhow::Result<()> {
+    let cursor = cursor_value(neo4j, indexer_ids::CURSOR_ATTRIBUTE).await?;
+    let head = reindex::indexed_head(neo4j).await?;
+    let version = cursor_value(neo4j, indexer_ids::VERSION_ATTRIBUTE).await?;
+    let embedding_config = EmbeddingConfig::load(neo4j).await?;
+    let pending = migrations::pending(neo4j, migrations::MIGRATIONS).await?;
+
+    println!("Cursor: {}", cursor.as_deref().unwrap_or("none"));
+    println!(
+        "Last indexed block: {}",
+        head.map_or("none".to_string(), |head| format!("#{head}"))
+    );
+    println!(
+        "Database version: {} (sink version: {})",
+        version.as_deref().unwrap_or("none"),
+        env!("GIT_TAG")
+    );
+    match embedding_config {
+        Some(config) => println!(
+            "Embedding model: {} (dim = {})",
+            config.attributes.model, config.attributes.dim
+        ),
+        None => println!("Embedding model: none"),
+    }
+    if pending.is_empty() {
+        println!("Pending migrations: none");
+    } else {
+        println!("Pending migrations: {}", pending.join(", "));
+    }
+
+    Ok(())
+}
+
+async fn verify(args: &AppArgs, config: &Config, neo4j: &neo4rs::Graph) -> anyhow::Result<()> {
+    let mut failures = 0;
+    let mut check = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("[ok] {name}: {detail}"),
+        Err(reason) => {
+            failures += 1;
+            println!("[FAILED] {name}: {reason}");
+        }
+    };
+
+    check(
+        "Config",
+        config
+            .validate_substreams()
+            .map(|_| "valid".to_string())
+            .map_err(|e| e.to_string()),
+    );
+
+    let substreams = &config.substreams;
+    check(
+        "Substreams package",
+        match read_package(&substreams.package).await {
+            Ok(package) => verify_package(package, config),
+            Err(e) => Err(format!("{e:#}")),
+        },
+    );
+
+    if let Err(e) = neo4j.run(neo4rs::query("RETURN 1")).await {
+        check("Neo4j", Err(e.to_string()));
+        anyhow::bail!("Verification failed: Neo4j is not reachable");
+    }
+    check("Neo4j", Ok("connected".to_string()));
+
+    if let Some(version) = cursor_value(neo4j, indexer_ids::VERSION_ATTRIBUTE).await? {
+        check("Database version", Ok(version));
+        verify_database(args, neo4j, &mut check).await?;
+    } else {
+        check(
+            "Database",
+            Ok("not initialized (it will be set up on start)".to_string()),
+        );
+    }
+
+    match failures {
+        0 => Ok(()),
+        _ => anyhow::bail!("Verification failed: {failures} check(s) failed"),
+    }
+}
+
+/// Checks that the output module exists and that the block range and the module
+/// parameters match the package
+fn verify_package(
+    mut package: substreams_utils::pb::sf::substreams::v1::Package,
+    config: &Config,
+) -> Result<String, String> {
+    let substreams = &config.substreams;
+    let block_range = config.block_range().map_err(|e| e.to_string())?;
+    let (start_block, end_block) = read_block_range(&package, &substreams.module, &block_range)
+        .map_err(|e| format!("{e:#} ({})", substreams.package))?;
+
+    if let Some(modules) = package.modules.as_mut() {
+        set_module_params(modules, &config.module_params()).map_err(|e| format!("{e:#}"))?;
+    }
+
+    Ok(format!(
+        "{} (module {}, blocks #{} to {})",
+        substreams.package,
+        substreams.module,
+        start_block,
+        match end_block {
+            0 => "head".to_string(),
+            end_block => format!("#{end_block}"),
+        }
+    ))
+}
+
+/// Checks that an initialized database can be used by this version of the sink
+/// without being reset
+async fn verify_database(
+    args: &AppArgs,
+    neo4j: &neo4rs::Graph,
+    check: &mut impl FnMut(&str, Result<String, String>),
+) -> anyhow::Result<()> {
+    let pending = migrations::pending(neo4j, migrations::MIGRATIONS).await?;
+    let reset_migration = migrations::MIGRATIONS
+        .iter()
+        .filter(|migration| pending.contains(&migration.id))
+        .find(|migration| matches!(migration.action, MigrationAction::Reset));
+    check(
+        "Migrations",
+        match reset_migration {
+            Some(migration) => Err(format!(
+                "migration {} requires a database reset",
+                migration.id
+            )),
+            None if pending.is_empty() => Ok("up to date".to_string()),
+            None => Ok(format!("{} pending, applied on start", pending.join(", "))),
+        },
+    );
+
+    let model = &args.embedding_model;
+    check(
+        "Embedding model",
+        match FastEmbedder::model_dim(model) {
+            Ok(dim) => match EmbeddingConfig::load(neo4j).await? {
+                Some(embedding_config) if embedding_config.attributes.matches(model, dim) => {
+                    Ok(format!("{model} (dim = {dim})"))
+                }
+                Some(embedding_config) => Err(format!(
+                    "database uses {} (dim = {}) but the sink uses {model} (dim = {dim}) (the database would be reset)",
+                    embedding_config.attributes.model, embedding_config.attributes.dim
+                )),
+                None => {
+                    Err("no embedding model recorded (the database would be reset)".to_string())
+                }
+            },
+            Err(e) => Err(e.to_string()),
+        },
+    );
+
+    Ok(())
+}
+
+/// Re-indexes the spaces and/or blocks selected by `args` with the blocks streamed
+/// from the configured substreams endpoint (see [`sink::reindex`])
+async fn reindex(handler: EventHandler, config: &Config, args: &ReindexArgs) -> anyhow::Result<()> {
+    let Some(head) = reindex::indexed_head(handler.neo4j()).await? else {
+        anyhow::bail!("Nothing to re-index: no block has been indexed yet");
+    };
+
+    let start_block = args.start_block.unwrap_or(config.substreams.start_block);
+    if start_block > head {
+        anyhow::bail!(
+            "Start block #{} is after the last indexed block #{}",
+            start_block,
+            head
+        );
+    }
+
+    // Later blocks depend on the writes of earlier ones, so a range cannot be rebuilt
+    // without the blocks following it
+    let (_, stop_block) = config.block_range()?.resolve(config.substreams.start_block);
+    if stop_block != 0 && stop_block <= head {
+        anyhow::bail!(
+            "The configured block range ends at block #{} but a re-index always ends at the last indexed block #{} since later blocks depend on the re-indexed ones. Unset the end block of the substreams configuration.",
+            stop_block,
+            head
+        );
+    }
+
+    let mut scope = ReindexScope::new(start_block);
+    if !args.spaces.is_empty() {
+        scope = scope.spaces(args.spaces.iter().cloned());
+    }
+    let handler = handler.reindex(scope).retry_policy(config.retry_policy()?);
+
+    reindex::prepare(&handler).await?;
+
+    tracing::info!("Re-indexing blocks #{} to #{}", start_block, head);
+
+    // Only final blocks are streamed since a rollback would not be limited to the
+    // re-indexed spaces. The end block is exclusive.
+    handler
+        .run(
+            endpoint_pool(config).await?,
+            &config.substreams.package,
+            &config.substreams.module,
+            BlockRange::new(start_block as i64, head + 1),
+            &config.module_params(),
+            true,
+            Some(64),
+        )
+        .await?;
+
+    reindex::finish(&handler).await?;
+    tracing::info!("Re-index complete");
+
+    Ok(())
+}
+
+/// Records the blocks of the configured block range to a file (see
+/// [`substreams_utils::recording`])
+async fn record(config: &Config, neo4j: neo4rs::Graph, args: &RecordArgs) -> anyhow::Result<()> {
+    let cursor = match &args.cursor {
+        Some(cursor) => Some(cursor.clone()),
+        None if args.from_sink_cursor => {
+            cursor_value(&neo4j, indexer_ids::CURSOR_ATTRIBUTE).await?
+        }
+        None => None,
+    };
+
+    let substreams = &config.substreams;
+    let stream = SubstreamsStream::from_package(
+        endpoint_pool(config).await?,
+        cursor,
+        read_package(&substreams.package).await?,
+        &substreams.module,
+        &config.block_range()?,
+        &config.module_params(),
+        substreams.final_blocks_only,
+    )?;
+
+    tracing::info!("Recording blocks to {}", args.output.display());
+    let count = recording::record(stream, &args.output, shutdown_signal()).await?;
+    tracing::info!("Recorded {} responses to {}", count, args.output.display());
+
+    Ok(())
+}
+
+/// Processes the blocks of a recording file, as if they were streamed
+async fn replay(
+    args: &AppArgs,
+    handler: EventHandler,
+    replay_args: &ReplayArgs,
+) -> anyhow::Result<()> {
+    if args.reset_db {
+        reset_db(&handler).await?;
+    } else {
+        migration_check(&handler).await?;
+    }
+
+    tracing::info!("Replaying {}", replay_args.input.display());
+    handler.replay(&replay_args.input, Some(64)).await?;
+
+    match handler.health().last_block() {
+        Some(block) => tracing::info!("Replay complete, last block #{}", block.number),
+        None => tracing::info!("Replay complete, no block processed"),
+    }
+
+    Ok(())
+}
+
+async fn ingest(
+    args: &AppArgs,
+    handler: EventHandler,
+    ingest_args: &IngestArgs,
+) -> anyhow::Result<()> {
+    let edits = match (&ingest_args.manifest, &ingest_args.dir) {
+        (Some(manifest), _) => offline::load_manifest(manifest, ingest_args.start_block)?,
+        (None, Some(dir)) => offline::read_dir(
+            dir,
+            ingest_args.dao_address.as_deref().unwrap_or_default(),
+            ingest_args.plugin_address.as_deref(),
+            ingest_args.start_block,
+        )?,
+        (None, None) => anyhow::bail!("Either --manifest or --dir is required"),
+    };
+
+    if args.reset_db {
+        reset_db(&handler).await?;
+    } else {
+        migration_check(&handler).await?;
+    }
+
+    let edit_count = edits.len();
+    let block_count = offline::ingest(&handler, edits).await?;
+    tracing::info!(
+        "Ingested {} edit files in {} blocks",
+        edit_count,
+        block_count
+    );
+
+    Ok(())
+}
+
 pub async fn reset_db(handler: &EventHandler) -> anyhow::Result<()> {
     // Delete indexes
     handler
@@ -188,6 +790,9 @@ pub async fn reset_db(handler: &EventHandler) -> anyhow::Result<()> {
             "CREATE INDEX relation_type_index FOR () -[r:RELATION]-> () ON (r.relation_type)",
         ))
         .await?;
+    for statement in migrations::BLOCK_INDEXES {
+        handler.neo4j().run(neo4rs::query(statement)).await?;
+    }
 
     handler.neo4j()
         .run(neo4rs::query(&format!(
@@ -197,15 +802,21 @@ pub async fn reset_db(handler: &EventHandler) -> anyhow::Result<()> {
         .await?;
 
     // Bootstrap indexer entities
-    mapping::triple::insert_many(
-        handler.neo4j(),
-        &BlockMetadata::default(),
-        indexer_ids::INDEXER_SPACE_ID,
-        "0",
-    )
-    .triples(bootstrap::boostrap_indexer::triples())
-    .send()
-    .await?;
+    bootstrap::bootstrap_indexer(handler.neo4j()).await?;
+
+    // A freshly bootstrapped database is up to date
+    migrations::mark_all_applied(handler, migrations::MIGRATIONS).await?;
+
+    // Record the embedding model so that readers can check they use the same one
+    EmbeddingConfig::new(handler.embedder().model(), handler.embedding_dim())
+        .insert(
+            handler.neo4j(),
+            &BlockMetadata::default(),
+            indexer_ids::INDEXER_SPACE_ID,
+            "0",
+        )
+        .send()
+        .await?;
 
     Ok(())
 }
@@ -221,23 +832,68 @@ async fn migration_check(handler: &EventHandler) -> Result<(), Error> {
     .send()
     .await?;
 
-    if let Some(version) = version {
-        if version.value.value != env!("GIT_TAG") {
+    let Some(version) = version else {
+        tracing::info!("No version found in the database. Resetting the database.");
+        return reset_db(handler).await;
+    };
+
+    match migrations::run(handler).await? {
+        MigrationOutcome::Applied(applied) if applied.is_empty() => {
+            tracing::info!("Database is up to date. No migration needed.");
+        }
+        MigrationOutcome::Applied(applied) => {
+            tracing::info!("Applied migrations: {}", applied.join(", "));
+        }
+        MigrationOutcome::ResetRequired { migration } => {
             tracing::info!(
-                "Version mismatch. Resetting the database. Old version: {}, New version: {}",
-                version.value.value,
-                env!("GIT_TAG")
+                "Migration {} cannot be applied in place. Resetting the database.",
+                migration
             );
-            reset_db(handler).await?;
-        } else {
+            return reset_db(handler).await;
+        }
+    }
+
+    // Insert the indexer entities added since the database was created
+    bootstrap::bootstrap_indexer(handler.neo4j()).await?;
+
+    if version.value.value != env!("GIT_TAG") {
+        tracing::info!(
+            "Updating database version. Old version: {}, New version: {}",
+            version.value.value,
+            env!("GIT_TAG")
+        );
+        triple::insert_one(
+            handler.neo4j(),
+            &BlockMetadata::default(),
+            indexer_ids::INDEXER_SPACE_ID,
+            "0",
+            Triple::new(
+                indexer_ids::CURSOR_ID,
+                indexer_ids::VERSION_ATTRIBUTE,
+                env!("GIT_TAG"),
+            ),
+        )
+        .send()
+        .await?;
+    }
+
+    let model = handler.embedder().model();
+    match EmbeddingConfig::load(handler.neo4j()).await? {
+        Some(config) if config.attributes.matches(model, handler.embedding_dim()) => {}
+        Some(config) => {
             tracing::info!(
-                "Version match: {}. No migration needed.",
-                version.value.value
+                "Embedding model mismatch. Resetting the database. Old model: {} (dim = {}), New model: {} (dim = {})",
+                config.attributes.model,
+                config.attributes.dim,
+                model,
+                handler.embedding_dim()
             );
+            reset_db(handler).await?;
+        }
+        None => {
+            tracing::info!("No embedding model found in the database. Resetting the database.");
+            reset_db(handler).await?;
         }
-    } else {
-        tracing::info!("No version found in the database. Resetting the database.");
-        reset_db(handler).await?;
     }
 
     Ok(())
@@ -278,39 +934,32 @@ fn set_log_level() {
     }
 }
 
-async fn health() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "component": "sink",
-        "status": "ok",
-    }))
-}
-
-async fn start_http_server() {
+async fn start_http_server(
+    config: &Config,
+    neo4j: neo4rs::Graph,
+    health: HealthState,
+) -> anyhow::Result<()> {
     let app = Router::new()
-        .route("/health", get(health))
-        .route("/metrics", get(metrics::metrics_handler));
-
-    let port = env::var("KG_SINK_HTTP_PORT")
-        .ok()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or_else(|| {
-            tracing::info!(
-                "KG_SINK_HTTP_PORT not set, using default port {}",
-                DEFAULT_HTTP_PORT
-            );
-            DEFAULT_HTTP_PORT
-        });
-
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+        .route("/metrics", get(metrics::metrics_handler))
+        .merge(health::routes(
+            health,
+            neo4j,
+            Duration::from_secs(config.http.max_head_lag),
+        ));
+
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.http.port));
     let listener = tokio::net::TcpListener::bind(addr)
         .await
-        .unwrap_or_else(|e| panic!("failed to start health server on {addr}: {e}"));
-    tracing::info!("Health available on {addr}/health");
+        .map_err(|e| anyhow::anyhow!("Failed to start HTTP server on {addr}: {e}"))?;
+    tracing::info!("Liveness available on {addr}/health/live");
+    tracing::info!("Readiness available on {addr}/health/ready");
     tracing::info!("Metrics available on {addr}/metrics");
 
     tokio::spawn(async move {
-        axum::serve(listener, app)
-            .await
-            .unwrap_or_else(|e| panic!("failed to run HTTP server: {e}"));
+        if let Err(e) = axum::serve(listener, app).await {
+            tracing::error!("HTTP server failed: {e}");
+        }
     });
+
+    Ok(())
 }
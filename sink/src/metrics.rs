@@ -1,7 +1,11 @@
 use axum::http::{header, StatusCode};
 use axum::response::Response;
 use lazy_static::lazy_static;
-use prometheus::{register_gauge, register_histogram, Encoder, Gauge, Histogram, TextEncoder};
+use prometheus::{
+    register_gauge, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, Encoder, Gauge, Histogram, HistogramTimer, HistogramVec, IntCounter,
+    IntCounterVec, TextEncoder,
+};
 use std::time::SystemTime;
 
 lazy_static! {
@@ -25,6 +29,61 @@ lazy_static! {
         "Time spent processing each block"
     )
     .expect("Failed to create block_processing_duration_seconds histogram");
+    pub static ref FAILED_EDITS: IntCounter = register_int_counter!(
+        "failed_edits_total",
+        "Number of edits or ops rejected by the sink"
+    )
+    .expect("Failed to create failed_edits_total counter");
+    pub static ref SKIPPED_EDITS: IntCounter = register_int_counter!(
+        "skipped_edits_total",
+        "Number of edits skipped because of the blacklist"
+    )
+    .expect("Failed to create skipped_edits_total counter");
+    pub static ref EVENTS_PROCESSED: IntCounterVec = register_int_counter_vec!(
+        "events_total",
+        "Number of events processed, by event type",
+        &["event_type"]
+    )
+    .expect("Failed to create events_total counter");
+    pub static ref EVENT_PROCESSING_TIME: HistogramVec = register_histogram_vec!(
+        "event_processing_duration_seconds",
+        "Time spent processing all events of a given type in a block",
+        &["event_type"]
+    )
+    .expect("Failed to create event_processing_duration_seconds histogram");
+    pub static ref OPS_PROCESSED: IntCounterVec = register_int_counter_vec!(
+        "ops_total",
+        "Number of edit ops processed, by op type",
+        &["op_type"]
+    )
+    .expect("Failed to create ops_total counter");
+    pub static ref EMBEDDING_TIME: Histogram = register_histogram!(
+        "embedding_duration_seconds",
+        "Time spent computing the embeddings of an edit"
+    )
+    .expect("Failed to create embedding_duration_seconds histogram");
+    pub static ref EMBEDDED_TEXTS: IntCounter =
+        register_int_counter!("embedded_texts_total", "Number of triple values embedded")
+            .expect("Failed to create embedded_texts_total counter");
+}
+
+/// Counts `count` events of type `event_type` and starts timing their processing.
+/// Returns `None` if there are no events so that empty stages are not recorded
+/// in the processing time histogram.
+pub fn start_event_timer(event_type: &str, count: usize) -> Option<HistogramTimer> {
+    if count == 0 {
+        return None;
+    }
+
+    EVENTS_PROCESSED
+        .with_label_values(&[event_type])
+        .inc_by(count as u64);
+
+    Some(
+        EVENT_PROCESSING_TIME
+            .with_label_values(&[event_type])
+            .start_timer(),
+    )
 }
 
 pub async fn metrics_handler() -> Response<String> {
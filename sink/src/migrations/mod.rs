@@ -0,0 +1,174 @@
+//! Ordered, idempotent database migrations.
+//!
+//! Each migration is applied at most once and recorded in the indexer space. Migrations
+//! must be idempotent since a failure between applying a migration and recording it
+//! will cause it to be applied again on the next start.
+//!
+//! New migrations must be appended to [`MIGRATIONS`] unless the migrations preceding
+//! them depend on them; applied migrations must never be modified or reordered.
+mod steps;
+
+use futures::future::BoxFuture;
+use grc20_core::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{query_utils::Query, triple, Triple},
+    neo4rs,
+};
+
+use crate::events::EventHandler;
+
+pub use steps::{BLOCK_INDEXES, MIGRATIONS};
+
+pub type MigrationFn = for<'a> fn(&'a EventHandler) -> BoxFuture<'a, Result<(), DatabaseError>>;
+
+pub struct Migration {
+    /// Unique identifier of the migration (e.g.: `0001_create_indexes`)
+    pub id: &'static str,
+    pub description: &'static str,
+    pub action: MigrationAction,
+}
+
+pub enum MigrationAction {
+    /// Cypher statements executed in order. Each statement must be idempotent.
+    Cypher(&'static [&'static str]),
+    /// Arbitrary migration (e.g.: backfills)
+    Run(MigrationFn),
+    /// The migration cannot be applied in place: the database must be reset and
+    /// everything reindexed.
+    Reset,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// The pending migrations (possibly none) were applied
+    Applied(Vec<&'static str>),
+    /// A pending migration requires the database to be reset. Migrations preceding it
+    /// were applied.
+    ResetRequired { migration: &'static str },
+}
+
+/// Applies the pending migrations of [`MIGRATIONS`]
+pub async fn run(handler: &EventHandler) -> Result<MigrationOutcome, DatabaseError> {
+    run_migrations(handler, MIGRATIONS).await
+}
+
+/// Applies the pending `migrations` in order, stopping at the first one requiring a reset.
+pub async fn run_migrations(
+    handler: &EventHandler,
+    migrations: &[Migration],
+) -> Result<MigrationOutcome, DatabaseError> {
+    let mut applied = vec![];
+
+    for migration in migrations {
+        if is_applied(handler, migration.id).await? {
+            continue;
+        }
+
+        match &migration.action {
+            MigrationAction::Cypher(statements) => {
+                for statement in statements.iter() {
+                    handler.neo4j().run(neo4rs::query(statement)).await?;
+                }
+            }
+            MigrationAction::Run(f) => f(handler).await?,
+            MigrationAction::Reset => {
+                tracing::info!(
+                    "Migration {} ({}) requires a database reset",
+                    migration.id,
+                    migration.description
+                );
+                return Ok(MigrationOutcome::ResetRequired {
+                    migration: migration.id,
+                });
+            }
+        }
+
+        mark_applied(handler, migration.id).await?;
+        tracing::info!(
+            "Applied migration {} ({})",
+            migration.id,
+            migration.description
+        );
+        applied.push(migration.id);
+    }
+
+    Ok(MigrationOutcome::Applied(applied))
+}
+
+/// Records all `migrations` as applied. Used after a reset since a freshly bootstrapped
+/// database is up to date.
+pub async fn mark_all_applied(
+    handler: &EventHandler,
+    migrations: &[Migration],
+) -> Result<(), DatabaseError> {
+    triple::insert_many(
+        handler.neo4j(),
+        &BlockMetadata::default(),
+        indexer_ids::INDEXER_SPACE_ID,
+        "0",
+    )
+    .triples(
+        migrations
+            .iter()
+            .map(|migration| migration_triple(migration.id)),
+    )
+    .send()
+    .await
+}
+
+pub async fn is_applied(handler: &EventHandler, migration_id: &str) -> Result<bool, DatabaseError> {
+    is_applied_in(handler.neo4j(), migration_id).await
+}
+
+/// Returns the IDs of the `migrations` that have not been applied yet
+pub async fn pending(
+    neo4j: &neo4rs::Graph,
+    migrations: &[Migration],
+) -> Result<Vec<&'static str>, DatabaseError> {
+    let mut pending = vec![];
+    for migration in migrations {
+        if !is_applied_in(neo4j, migration.id).await? {
+            pending.push(migration.id);
+        }
+    }
+    Ok(pending)
+}
+
+async fn is_applied_in(neo4j: &neo4rs::Graph, migration_id: &str) -> Result<bool, DatabaseError> {
+    Ok(triple::find_one(
+        neo4j,
+        indexer_ids::MIGRATION_ID_ATTRIBUTE,
+        migration_entity_id(migration_id),
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await?
+    .is_some())
+}
+
+async fn mark_applied(handler: &EventHandler, migration_id: &str) -> Result<(), DatabaseError> {
+    triple::insert_one(
+        handler.neo4j(),
+        &BlockMetadata::default(),
+        indexer_ids::INDEXER_SPACE_ID,
+        "0",
+        migration_triple(migration_id),
+    )
+    .send()
+    .await
+}
+
+fn migration_entity_id(migration_id: &str) -> String {
+    format!("{}:{}", indexer_ids::MIGRATION_TYPE, migration_id)
+}
+
+fn migration_triple(migration_id: &str) -> Triple {
+    Triple::new(
+        migration_entity_id(migration_id),
+        indexer_ids::MIGRATION_ID_ATTRIBUTE,
+        migration_id,
+    )
+}
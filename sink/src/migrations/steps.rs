@@ -0,0 +1,199 @@
+use futures::future::BoxFuture;
+use grc20_core::{
+    block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::query_utils::Query, neo4rs,
+};
+use grc20_sdk::models::{EmbeddingConfig, Proposal};
+
+use super::{Migration, MigrationAction};
+use crate::{events::EventHandler, reindex};
+
+/// Embedding model used to build the indexes before the model was recorded in the indexer space
+const LEGACY_EMBEDDING_MODEL: &str = "Qdrant/all-MiniLM-L6-v2-onnx";
+const LEGACY_EMBEDDING_DIM: usize = 384;
+
+/// Indexes of the block numbers stamped on entities, attributes and relations, used to
+/// find the writes to roll back on block undo signals
+pub const BLOCK_INDEXES: &[&str] = &[
+    const_format::formatcp!(
+        "CREATE INDEX entity_created_at_block_index IF NOT EXISTS FOR (e:Entity) ON (e.`{}`)",
+        indexer_ids::CREATED_AT_BLOCK
+    ),
+    const_format::formatcp!(
+        "CREATE INDEX entity_updated_at_block_index IF NOT EXISTS FOR (e:Entity) ON (e.`{}`)",
+        indexer_ids::UPDATED_AT_BLOCK
+    ),
+    const_format::formatcp!(
+        "CREATE INDEX attribute_created_at_block_index IF NOT EXISTS FOR () -[r:ATTRIBUTE]-> () ON (r.`{}`)",
+        indexer_ids::CREATED_AT_BLOCK
+    ),
+    const_format::formatcp!(
+        "CREATE INDEX attribute_updated_at_block_index IF NOT EXISTS FOR () -[r:ATTRIBUTE]-> () ON (r.`{}`)",
+        indexer_ids::UPDATED_AT_BLOCK
+    ),
+    const_format::formatcp!(
+        "CREATE INDEX relation_created_at_block_index IF NOT EXISTS FOR () -[r:RELATION]-> () ON (r.`{}`)",
+        indexer_ids::CREATED_AT_BLOCK
+    ),
+    const_format::formatcp!(
+        "CREATE INDEX relation_updated_at_block_index IF NOT EXISTS FOR () -[r:RELATION]-> () ON (r.`{}`)",
+        indexer_ids::UPDATED_AT_BLOCK
+    ),
+];
+
+pub const MIGRATIONS: &[Migration] = &[
+    // Must run first: writes close the open edges of a triple by comparing their block
+    // numbers, which edges written before block numbers were recorded lack.
+    Migration {
+        id: "0000_backfill_edge_block_numbers",
+        description: "Record block numbers on the attribute and relation edges lacking them",
+        action: MigrationAction::Cypher(&[
+            const_format::formatcp!(
+                "MATCH () -[r:ATTRIBUTE]-> () WHERE r.`{CREATED_AT_BLOCK}` IS NULL SET r.`{CREATED_AT_BLOCK}` = \"0\", r.`{UPDATED_AT_BLOCK}` = coalesce(r.`{UPDATED_AT_BLOCK}`, \"0\")",
+                CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
+                UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+            ),
+            const_format::formatcp!(
+                "MATCH () -[r:RELATION]-> () WHERE r.`{CREATED_AT_BLOCK}` IS NULL SET r.`{CREATED_AT_BLOCK}` = \"0\", r.`{UPDATED_AT_BLOCK}` = coalesce(r.`{UPDATED_AT_BLOCK}`, \"0\")",
+                CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
+                UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+            ),
+        ]),
+    },
+    Migration {
+        id: "0001_create_indexes",
+        description: "Create the entity and relation indexes",
+        action: MigrationAction::Cypher(&[
+            "CREATE INDEX entity_id_index IF NOT EXISTS FOR (e:Entity) ON (e.id)",
+            "CREATE INDEX relation_id_index IF NOT EXISTS FOR () -[r:RELATION]-> () ON (r.id)",
+            "CREATE INDEX relation_type_index IF NOT EXISTS FOR () -[r:RELATION]-> () ON (r.relation_type)",
+        ]),
+    },
+    Migration {
+        id: "0002_create_vector_index",
+        description: "Create the vector index used for semantic search",
+        action: MigrationAction::Run(create_vector_index),
+    },
+    Migration {
+        id: "0003_record_embedding_config",
+        description: "Record the embedding model the indexes were built with",
+        action: MigrationAction::Run(record_embedding_config),
+    },
+    Migration {
+        id: "0004_create_block_indexes",
+        description: "Index the block numbers of entities, attributes and relations for rollbacks",
+        action: MigrationAction::Cypher(BLOCK_INDEXES),
+    },
+    Migration {
+        id: "0005_create_attribute_value_index",
+        description: "Index the attribute values, e.g.: to find edit proposals by content URI",
+        action: MigrationAction::Cypher(&[
+            "CREATE INDEX attribute_value_index IF NOT EXISTS FOR (a:Attribute) ON (a.id, a.value)",
+        ]),
+    },
+    Migration {
+        id: "0006_migrate_proposal_status",
+        description: "Move the proposal statuses to the proposal status attribute",
+        action: MigrationAction::Run(migrate_proposal_status),
+    },
+    Migration {
+        id: "0007_backfill_vote_tallies",
+        description: "Record the vote tallies of the proposals voted on before they were maintained",
+        action: MigrationAction::Run(backfill_vote_tallies),
+    },
+];
+
+fn create_vector_index(handler: &EventHandler) -> BoxFuture<'_, Result<(), DatabaseError>> {
+    Box::pin(async move {
+        handler.neo4j()
+            .run(neo4rs::query(&format!(
+                "CREATE VECTOR INDEX vector_index IF NOT EXISTS FOR (a:Indexed) ON (a.embedding) OPTIONS {{indexConfig: {{`vector.dimensions`: {}, `vector.similarity_function`: 'COSINE'}}}}",
+                handler.embedding_dim()
+            )))
+            .await?;
+
+        Ok(())
+    })
+}
+
+/// Proposal statuses used to be stored under the `status` attribute, either as
+/// `PROPOSED` (when the proposal was created) or `Executed`. Proposals whose voting
+/// period ended before the last indexed block are then closed, since statuses are only
+/// re-evaluated for the proposals ending after the previous block.
+fn migrate_proposal_status(handler: &EventHandler) -> BoxFuture<'_, Result<(), DatabaseError>> {
+    Box::pin(async move {
+        handler
+            .neo4j()
+            .run(
+                neo4rs::query(
+                    r#"
+                    MATCH (:Entity) -[:ATTRIBUTE {space_id: $space_id}]-> (a:Attribute {id: "status"})
+                    SET a.id = $status_attribute
+                    SET a.value = CASE a.value
+                        WHEN "PROPOSED" THEN "Proposed"
+                        WHEN "ACCEPTED" THEN "Accepted"
+                        WHEN "REJECTED" THEN "Rejected"
+                        WHEN "CANCELED" THEN "Canceled"
+                        WHEN "EXECUTED" THEN "Executed"
+                        ELSE a.value
+                    END
+                    "#,
+                )
+                .param("space_id", indexer_ids::INDEXER_SPACE_ID)
+                .param("status_attribute", indexer_ids::PROPOSAL_STATUS_ATTRIBUTE),
+            )
+            .await?;
+
+        let Some(timestamp) =
+            reindex::cursor_timestamp(handler.neo4j(), indexer_ids::CURSOR_ID).await?
+        else {
+            return Ok(());
+        };
+        let block = BlockMetadata {
+            timestamp,
+            ..Default::default()
+        };
+
+        for proposal in Proposal::find_ended(handler.neo4j(), None, &timestamp).await? {
+            // Vote tallies are only backfilled by a later migration
+            let tally = Proposal::count_votes(handler.neo4j(), &proposal.id).await?;
+            handler.close_proposal(&block, &proposal, &tally).await?;
+        }
+
+        Ok(())
+    })
+}
+
+fn backfill_vote_tallies(handler: &EventHandler) -> BoxFuture<'_, Result<(), DatabaseError>> {
+    Box::pin(async move {
+        for proposal_id in Proposal::find_voted(handler.neo4j()).await? {
+            let tally = Proposal::count_votes(handler.neo4j(), &proposal_id).await?;
+            Proposal::set_vote_tally(
+                handler.neo4j(),
+                &BlockMetadata::default(),
+                &proposal_id,
+                tally,
+            )
+            .await?;
+        }
+
+        Ok(())
+    })
+}
+
+fn record_embedding_config(handler: &EventHandler) -> BoxFuture<'_, Result<(), DatabaseError>> {
+    Box::pin(async move {
+        if EmbeddingConfig::load(handler.neo4j()).await?.is_some() {
+            return Ok(());
+        }
+
+        EmbeddingConfig::new(LEGACY_EMBEDDING_MODEL, LEGACY_EMBEDDING_DIM)
+            .insert(
+                handler.neo4j(),
+                &BlockMetadata::default(),
+                indexer_ids::INDEXER_SPACE_ID,
+                "0",
+            )
+            .send()
+            .await
+    })
+}
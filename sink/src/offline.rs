@@ -0,0 +1,322 @@
+//! Offline ingestion of edits read from local files instead of a substreams endpoint
+//! and an IPFS gateway (e.g.: for air-gapped environments, demos or fixtures).
+//!
+//! Each file contains an encoded `pb::ipfs::Edit` or `pb::ipfs::Import`, exactly as it
+//! would be published on IPFS. The space and block metadata which would normally come
+//! from the chain are synthetic: they are either listed in a TOML manifest, e.g.:
+//! ```toml
+//! [[edit]]
+//! file = "edits/root.pb"
+//! dao_address = "0xd3a0Fa4e7A8a3C8c9a3BB0AdD0E4c3C5fd5d6D3b"
+//! # Optional, defaults to the DAO address
+//! plugin_address = "0x84713663033dC5ba5699280728545df11e76BCC1"
+//! # Optional, defaults to the block of the previous edit + 1
+//! block = 10
+//! # Optional, defaults to `block` seconds after the UNIX epoch
+//! timestamp = "2025-01-01T00:00:00Z"
+//! ```
+//! or derived from a directory of `*.pb` files (one block per file, in file name order)
+//! published by a single DAO.
+//!
+//! The edits of an import (`ipfs://<hash>`) are read from the file `<hash>` (or
+//! `<hash>.pb`) in the directory of the import file. Spaces which do not exist yet are
+//! created before their first edit. The indexer cursor is left untouched.
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use grc20_core::{block::BlockMetadata, ids::create_geo_id, mapping::transaction, pb::geo};
+use grc20_sdk::models::space;
+use serde::Deserialize;
+
+use crate::events::{decode_edit, EventHandler, HandlerError};
+
+/// Edit file along with the synthetic metadata of its `EditPublished` event
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfflineEdit {
+    pub path: PathBuf,
+    pub dao_address: String,
+    pub plugin_address: String,
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OfflineEdit {
+    fn edit_published(&self) -> geo::EditPublished {
+        geo::EditPublished {
+            content_uri: format!("file://{}", self.path.display()),
+            plugin_address: self.plugin_address.clone(),
+            dao_address: self.dao_address.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(default, rename = "edit")]
+    edits: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    file: PathBuf,
+    dao_address: String,
+    plugin_address: Option<String>,
+    block: Option<u64>,
+    timestamp: Option<String>,
+}
+
+/// Loads the edits listed in the manifest at `path`. Relative file paths are resolved
+/// from the directory of the manifest and edits without a block are assigned the block
+/// following the previous edit (or `start_block` for the first one).
+pub fn load_manifest(path: impl AsRef<Path>, start_block: u64) -> anyhow::Result<Vec<OfflineEdit>> {
+    let path = path.as_ref();
+    parse_manifest(
+        &std::fs::read_to_string(path)?,
+        path.parent().unwrap_or(Path::new("")),
+        start_block,
+    )
+}
+
+fn parse_manifest(
+    content: &str,
+    base_dir: &Path,
+    start_block: u64,
+) -> anyhow::Result<Vec<OfflineEdit>> {
+    let manifest: Manifest = toml::from_str(content)?;
+
+    let mut previous_block = None;
+    manifest
+        .edits
+        .into_iter()
+        .map(|entry| {
+            let block_number = match (entry.block, previous_block) {
+                (Some(block), Some(previous)) if block < previous => anyhow::bail!(
+                    "{}: block #{} is before the block of the previous edit (#{})",
+                    entry.file.display(),
+                    block,
+                    previous
+                ),
+                (Some(block), _) => block,
+                (None, Some(previous)) => previous + 1,
+                (None, None) => start_block,
+            };
+            previous_block = Some(block_number);
+
+            let timestamp = match &entry.timestamp {
+                Some(timestamp) => DateTime::parse_from_rfc3339(timestamp)
+                    .map_err(|e| anyhow::anyhow!("Invalid timestamp {timestamp:?}: {e}"))?
+                    .to_utc(),
+                None => default_timestamp(block_number)?,
+            };
+
+            Ok(OfflineEdit {
+                path: base_dir.join(&entry.file),
+                plugin_address: entry
+                    .plugin_address
+                    .unwrap_or_else(|| entry.dao_address.clone()),
+                dao_address: entry.dao_address,
+                block_number,
+                timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Lists the `*.pb` files of `dir` (sorted by name) as edits of the DAO `dao_address`,
+/// one block per file starting at `start_block`
+pub fn read_dir(
+    dir: impl AsRef<Path>,
+    dao_address: &str,
+    plugin_address: Option<&str>,
+    start_block: u64,
+) -> anyhow::Result<Vec<OfflineEdit>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "pb"));
+    paths.sort();
+
+    paths
+        .into_iter()
+        .zip(start_block..)
+        .map(|(path, block_number)| {
+            Ok(OfflineEdit {
+                path,
+                dao_address: dao_address.to_string(),
+                plugin_address: plugin_address.unwrap_or(dao_address).to_string(),
+                block_number,
+                timestamp: default_timestamp(block_number)?,
+            })
+        })
+        .collect()
+}
+
+fn default_timestamp(block_number: u64) -> anyhow::Result<DateTime<Utc>> {
+    i64::try_from(block_number)
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| anyhow::anyhow!("No default timestamp for block #{block_number}"))
+}
+
+/// Processes the `edits` in order, each block in its own transaction. Returns the
+/// number of blocks processed.
+pub async fn ingest(
+    handler: &EventHandler,
+    edits: Vec<OfflineEdit>,
+) -> Result<usize, HandlerError> {
+    let mut blocks: Vec<Vec<OfflineEdit>> = vec![];
+    for edit in edits {
+        match blocks.last_mut() {
+            Some(block) if block[0].block_number == edit.block_number => block.push(edit),
+            _ => blocks.push(vec![edit]),
+        }
+    }
+
+    for edits in &blocks {
+        let block = BlockMetadata {
+            cursor: String::new(),
+            block_number: edits[0].block_number,
+            timestamp: edits[0].timestamp,
+            request_id: create_geo_id(),
+        };
+
+        let edits_published = stream::iter(edits)
+            .then(|edit| async move {
+                let edit_published = edit.edit_published();
+                let bytes = read_file(&edit.path).await?;
+                let base_dir = edit.path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+                let content = decode_edit(&edit_published, &bytes, |edit_uri| {
+                    let base_dir = base_dir.clone();
+                    async move { read_file(&import_edit_path(&base_dir, &edit_uri)).await }
+                })
+                .await?;
+
+                Ok::<_, HandlerError>((edit_published, content))
+            })
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        tracing::info!(
+            "Block #{} ({}): Ingesting {} edit files",
+            block.block_number,
+            block.timestamp,
+            edits_published.len()
+        );
+
+        transaction::transaction(handler.neo4j(), async {
+            for edit in edits {
+                create_space_if_missing(handler, edit, &block).await?;
+            }
+
+            handler
+                .handle_edits_published(edits_published, &block)
+                .await
+        })
+        .await?;
+    }
+
+    Ok(blocks.len())
+}
+
+async fn create_space_if_missing(
+    handler: &EventHandler,
+    edit: &OfflineEdit,
+    block: &BlockMetadata,
+) -> Result<(), HandlerError> {
+    if space::find_entity_by_dao_address(handler.neo4j(), &edit.dao_address)
+        .await?
+        .is_none()
+    {
+        let space_created = geo::GeoSpaceCreated {
+            dao_address: edit.dao_address.clone(),
+            space_address: edit.plugin_address.clone(),
+        };
+        handler
+            .handle_space_created(&space_created, &[], block)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the path of the file containing the import edit `edit_uri`
+fn import_edit_path(base_dir: &Path, edit_uri: &str) -> PathBuf {
+    let hash = edit_uri.trim_start_matches("ipfs://");
+    let path = base_dir.join(hash);
+    if path.exists() {
+        path
+    } else {
+        base_dir.join(format!("{hash}.pb"))
+    }
+}
+
+async fn read_file(path: &Path) -> Result<Vec<u8>, HandlerError> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| HandlerError::Other(format!("Failed to read {}: {e}", path.display()).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let edits = parse_manifest(
+            r#"
+[[edit]]
+file = "root.pb"
+dao_address = "0xdao"
+
+[[edit]]
+file = "other.pb"
+dao_address = "0xdao"
+plugin_address = "0xplugin"
+
+[[edit]]
+file = "late.pb"
+dao_address = "0xdao"
+block = 10
+timestamp = "2025-01-01T00:00:00Z"
+"#,
+            Path::new("fixtures"),
+            5,
+        )
+        .unwrap();
+
+        assert_eq!(
+            edits
+                .iter()
+                .map(|edit| (edit.block_number, edit.plugin_address.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(5, "0xdao"), (6, "0xplugin"), (10, "0xdao")]
+        );
+        assert_eq!(edits[0].path, Path::new("fixtures/root.pb"));
+        assert_eq!(edits[1].timestamp, DateTime::from_timestamp(6, 0).unwrap());
+        assert_eq!(edits[2].timestamp.to_rfc3339(), "2025-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_manifest_blocks_out_of_order() {
+        let result = parse_manifest(
+            r#"
+[[edit]]
+file = "a.pb"
+dao_address = "0xdao"
+block = 3
+
+[[edit]]
+file = "b.pb"
+dao_address = "0xdao"
+block = 2
+"#,
+            Path::new(""),
+            1,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::events::Edit;
+use crate::events::EditContent;
 use grc20_core::{block::BlockMetadata, pb::geo};
 
 pub struct EventData {
@@ -8,7 +8,7 @@ pub struct EventData {
     pub governance_plugins_created: Vec<geo::GeoGovernancePluginCreated>,
     pub initial_editors_added: Vec<geo::InitialEditorAdded>,
     pub votes_cast: Vec<geo::VoteCast>,
-    pub edits_published: Vec<(geo::EditPublished, Vec<Edit>)>,
+    pub edits_published: Vec<(geo::EditPublished, EditContent)>,
     pub successor_spaces_created: Vec<geo::SuccessorSpaceCreated>,
     pub subspaces_added: Vec<geo::SubspaceAdded>,
     pub subspaces_removed: Vec<geo::SubspaceRemoved>,
@@ -0,0 +1,309 @@
+//! Targeted re-indexing of a set of spaces and/or of the blocks from a given block,
+//! without resetting the database.
+//!
+//! The data written by the selected blocks (in the selected spaces) is first deleted,
+//! then rebuilt by streaming the blocks again with a dedicated cursor, which leaves
+//! the main cursor and the other spaces untouched. The re-indexed range always ends
+//! at the block indexed by the main sink: later writes depend on the earlier ones,
+//! so a range in the middle of the history cannot be rebuilt on its own.
+//!
+//! When re-indexing specific spaces, all the events of the spaces are streamed again
+//! but only the data of the spaces themselves (i.e.: written by their edits) is deleted
+//! beforehand. The other events of the spaces (e.g.: membership changes, proposals and
+//! votes) write to the indexer space and are applied again over their existing data,
+//! which is restored to its indexed state once the last indexed block is reached.
+//!
+//! Note: The main sink should not be running while re-indexing.
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use grc20_core::{
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{rollback, triple, Query},
+    neo4rs, network_ids,
+    pb::geo::GeoOutput,
+};
+use grc20_sdk::models::space;
+
+use crate::{
+    events::{EventHandler, HandlerError},
+    preprocess::EventData,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct ReindexScope {
+    /// IDs of the spaces to re-index (all spaces if `None`)
+    pub spaces: Option<HashSet<String>>,
+    /// First block to re-index
+    pub start_block: u64,
+}
+
+impl ReindexScope {
+    pub fn new(start_block: u64) -> Self {
+        Self {
+            spaces: None,
+            start_block,
+        }
+    }
+
+    pub fn spaces(mut self, spaces: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.spaces = Some(spaces.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn includes_space(&self, space_id: &str) -> bool {
+        self.spaces
+            .as_ref()
+            .is_none_or(|spaces| spaces.contains(space_id))
+    }
+
+    /// Whether the space of the DAO `dao_address` is re-indexed
+    pub fn includes_dao(&self, dao_address: &str) -> bool {
+        self.includes_space(&space::new_id(network_ids::GEO, dao_address))
+    }
+
+    /// Removes the events of `output` emitted by DAOs whose space is not re-indexed.
+    /// Votes and executed proposals only reference the plugin of a space and are
+    /// filtered when the block is processed (see `retain_plugin_events`).
+    pub fn retain_dao_events(&self, output: &mut GeoOutput) {
+        if self.spaces.is_none() {
+            return;
+        }
+
+        output
+            .spaces_created
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .governance_plugins_created
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .initial_editors_added
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .edits_published
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .successor_spaces_created
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .subspaces_added
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .subspaces_removed
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .members_added
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .editors_added
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .personal_plugins_created
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .members_removed
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .editors_removed
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .edits
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_added_members
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_removed_members
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_added_editors
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_removed_editors
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_added_subspaces
+            .retain(|event| self.includes_dao(&event.dao_address));
+        output
+            .proposed_removed_subspaces
+            .retain(|event| self.includes_dao(&event.dao_address));
+    }
+}
+
+/// Removes the votes and executed proposals of `data` whose plugin does not belong to
+/// a re-indexed space. Plugins are resolved when the block is processed since they may
+/// have been created by an earlier block of the re-index.
+pub(crate) async fn retain_plugin_events(
+    neo4j: &neo4rs::Graph,
+    scope: &ReindexScope,
+    data: &mut EventData,
+) -> Result<(), DatabaseError> {
+    if scope.spaces.is_none() {
+        return Ok(());
+    }
+
+    let mut included_plugins = HashMap::new();
+    let plugin_addresses = data
+        .votes_cast
+        .iter()
+        .map(|vote| &vote.plugin_address)
+        .chain(
+            data.executed_proposals
+                .iter()
+                .map(|proposal| &proposal.plugin_address),
+        );
+    for plugin_address in plugin_addresses {
+        if !included_plugins.contains_key(plugin_address) {
+            let space_id = plugin_space_id(neo4j, plugin_address).await?;
+            included_plugins.insert(
+                plugin_address.clone(),
+                space_id.is_some_and(|space_id| scope.includes_space(&space_id)),
+            );
+        }
+    }
+
+    data.votes_cast
+        .retain(|vote| included_plugins[&vote.plugin_address]);
+    data.executed_proposals
+        .retain(|proposal| included_plugins[&proposal.plugin_address]);
+
+    Ok(())
+}
+
+/// Returns the ID of the space of the voting or personal admin plugin `plugin_address`
+async fn plugin_space_id(
+    neo4j: &neo4rs::Graph,
+    plugin_address: &str,
+) -> Result<Option<String>, DatabaseError> {
+    if let Some(space) = space::find_by_voting_plugin_address(neo4j, plugin_address).await? {
+        return Ok(Some(space.id().to_string()));
+    }
+
+    Ok(
+        space::find_by_personal_plugin_address(neo4j, plugin_address)
+            .await?
+            .map(|space| space.id().to_string()),
+    )
+}
+
+/// Returns the last block indexed by the main sink
+pub async fn indexed_head(neo4j: &neo4rs::Graph) -> Result<Option<u64>, HandlerError> {
+    cursor_block(neo4j, indexer_ids::CURSOR_ID).await
+}
+
+/// Returns the last block processed with the cursor `cursor_id`
+pub(crate) async fn cursor_block(
+    neo4j: &neo4rs::Graph,
+    cursor_id: &str,
+) -> Result<Option<u64>, HandlerError> {
+    let block_number = triple::find_one(
+        neo4j,
+        indexer_ids::BLOCK_NUMBER_ATTRIBUTE,
+        cursor_id,
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await?;
+
+    block_number
+        .map(|triple| {
+            triple.value.value.parse().map_err(|_| {
+                HandlerError::Other(format!("Invalid block number: {}", triple.value.value).into())
+            })
+        })
+        .transpose()
+}
+
+/// Returns the timestamp of the last block processed with the cursor `cursor_id`, if
+/// known (it is cleared when blocks are undone)
+pub(crate) async fn cursor_timestamp(
+    neo4j: &neo4rs::Graph,
+    cursor_id: &str,
+) -> Result<Option<DateTime<Utc>>, DatabaseError> {
+    let block_timestamp = triple::find_one(
+        neo4j,
+        indexer_ids::BLOCK_TIMESTAMP_ATTRIBUTE,
+        cursor_id,
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await?;
+
+    Ok(block_timestamp
+        .map(|triple| DateTime::try_from(triple.value))
+        .transpose()?)
+}
+
+/// Deletes the data written by the blocks (and spaces) of the re-index scope of
+/// `handler`. Nothing is deleted when resuming an interrupted re-index, i.e.: if
+/// the re-index cursor already exists.
+pub async fn prepare(handler: &EventHandler) -> Result<(), HandlerError> {
+    let Some(scope) = handler.reindex_scope() else {
+        return Err(HandlerError::Other(
+            "Handler is not configured for re-indexing".into(),
+        ));
+    };
+
+    if load_cursor(handler.neo4j()).await?.is_some() {
+        tracing::info!("Resuming interrupted re-index");
+        return Ok(());
+    }
+
+    let mut query = rollback::rollback(handler.neo4j(), scope.start_block.saturating_sub(1));
+    if let Some(head) = indexed_head(handler.neo4j()).await? {
+        query = query.head(head);
+    }
+    match &scope.spaces {
+        Some(spaces) => {
+            tracing::info!(
+                "Deleting data of spaces [{}] from block #{}",
+                spaces.iter().cloned().collect::<Vec<_>>().join(", "),
+                scope.start_block
+            );
+            query.spaces(spaces.iter().cloned()).send().await?;
+        }
+        None => {
+            tracing::info!("Deleting data from block #{}", scope.start_block);
+            query.send().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the re-index cursor once the re-index is complete
+pub async fn finish(handler: &EventHandler) -> Result<(), DatabaseError> {
+    handler
+        .neo4j()
+        .run(
+            neo4rs::query(
+                r#"
+                MATCH (e:Entity {id: $id})
+                OPTIONAL MATCH (e) -[:ATTRIBUTE]-> (a)
+                DETACH DELETE e, a
+                "#,
+            )
+            .param("id", indexer_ids::REINDEX_CURSOR_ID),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn load_cursor(neo4j: &neo4rs::Graph) -> Result<Option<String>, DatabaseError> {
+    let cursor = triple::find_one(
+        neo4j,
+        indexer_ids::CURSOR_ATTRIBUTE,
+        indexer_ids::REINDEX_CURSOR_ID,
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await?;
+
+    Ok(cursor.map(|c| c.value.value))
+}
@@ -0,0 +1,685 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use embedding::{Embedder, EmbeddingError};
+use futures::TryStreamExt;
+use grc20_core::{
+    block::BlockMetadata,
+    entity::EntityNodeRef,
+    indexer_ids,
+    mapping::{prop_filter, relation, triple, Query, QueryStream, RelationEdge, Triple},
+    pb::{self, geo},
+    system_ids,
+};
+use grc20_sdk::models::{
+    self, failed_edit, proposal::ProposalStatus, skipped_edit, space, EditProposal,
+};
+use sink::events::{Edit, EditContent, EventHandler, RejectedEdit};
+
+mod common;
+
+fn block(block_number: u64) -> BlockMetadata {
+    BlockMetadata {
+        block_number,
+        ..common::create_block_metadata()
+    }
+}
+
+fn edit(ops: Vec<pb::ipfs::Op>) -> Edit {
+    Edit {
+        name: "Test edit".to_string(),
+        proposal_id: "proposal".to_string(),
+        space_id: "space".to_string(),
+        space_plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+        dao_address: "0x0000000000000000000000000000000000000001".to_string(),
+        creator: "0x1234567890123456789012345678901234567890".to_string(),
+        authors: vec!["0x1234567890123456789012345678901234567890".to_string()],
+        content_uri: "ipfs://content".to_string(),
+        ops,
+    }
+}
+
+fn text_triple(entity: &str, attribute: &str, value: &str) -> pb::ipfs::Triple {
+    pb::ipfs::Triple {
+        entity: entity.to_string(),
+        attribute: attribute.to_string(),
+        value: Some(pb::ipfs::Value {
+            r#type: pb::ipfs::ValueType::Text as i32,
+            value: value.to_string(),
+        }),
+    }
+}
+
+fn set_triple_op(triple: pb::ipfs::Triple) -> pb::ipfs::Op {
+    pb::ipfs::Op {
+        r#type: pb::ipfs::OpType::SetTriple as i32,
+        triple: Some(triple),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_set_triple_batch() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    handler
+        .process_edit(
+            &block(1),
+            edit(vec![pb::ipfs::Op {
+                r#type: pb::ipfs::OpType::SetTripleBatch as i32,
+                triples: vec![
+                    text_triple("alice", "first_name", "Alice"),
+                    text_triple("alice", "last_name", "Smith"),
+                ],
+                ..Default::default()
+            }]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    let first_name = triple::find_one(&neo4j, "first_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        first_name,
+        Some(Triple::new("alice", "first_name", "Alice"))
+    );
+
+    let last_name = triple::find_one(&neo4j, "last_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(last_name, Some(Triple::new("alice", "last_name", "Smith")));
+}
+
+#[tokio::test]
+async fn test_delete_entity() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    handler
+        .process_edit(
+            &block(1),
+            edit(vec![
+                set_triple_op(text_triple("alice", "first_name", "Alice")),
+                set_triple_op(text_triple("bob", "first_name", "Bob")),
+                pb::ipfs::Op {
+                    r#type: pb::ipfs::OpType::CreateRelation as i32,
+                    relation: Some(pb::ipfs::Relation {
+                        id: "alice_knows_bob".to_string(),
+                        r#type: "knows".to_string(),
+                        from_entity: "alice".to_string(),
+                        to_entity: "bob".to_string(),
+                        index: "a0".to_string(),
+                    }),
+                    ..Default::default()
+                },
+            ]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    handler
+        .process_edit(
+            &block(2),
+            edit(vec![pb::ipfs::Op {
+                r#type: pb::ipfs::OpType::DeleteEntity as i32,
+                entity: Some(pb::ipfs::Entity {
+                    id: "bob".to_string(),
+                    types: vec![],
+                }),
+                ..Default::default()
+            }]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    // Bob's attributes and relations are closed
+    let bob_name = triple::find_one(&neo4j, "first_name", "bob", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bob_name, None);
+
+    let relation =
+        relation::find_one::<RelationEdge<EntityNodeRef>>(&neo4j, "alice_knows_bob", "space", None)
+            .send()
+            .await
+            .unwrap();
+    assert!(relation.is_none());
+
+    // Alice is untouched
+    let alice_name = triple::find_one(&neo4j, "first_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        alice_name,
+        Some(Triple::new("alice", "first_name", "Alice"))
+    );
+}
+
+#[tokio::test]
+async fn test_set_triple_after_delete_entity() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    // Ops are applied in order: bob is deleted, then written again
+    handler
+        .process_edit(
+            &block(1),
+            edit(vec![
+                set_triple_op(text_triple("bob", "first_name", "Bob")),
+                pb::ipfs::Op {
+                    r#type: pb::ipfs::OpType::DeleteEntity as i32,
+                    entity: Some(pb::ipfs::Entity {
+                        id: "bob".to_string(),
+                        types: vec![],
+                    }),
+                    ..Default::default()
+                },
+                set_triple_op(text_triple("bob", "first_name", "Robert")),
+            ]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    let bob_name = triple::find_one(&neo4j, "first_name", "bob", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bob_name, Some(Triple::new("bob", "first_name", "Robert")));
+}
+
+#[tokio::test]
+async fn test_archive_space() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    let space_id = handler
+        .handle_space_created(
+            &geo::GeoSpaceCreated {
+                dao_address: "0x1234567890123456789012345678901234567890".to_string(),
+                space_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+            },
+            &[],
+            &block(1),
+        )
+        .await
+        .unwrap();
+
+    handler
+        .handle_edits_published(
+            vec![(
+                geo::EditPublished {
+                    content_uri: "ipfs://archive".to_string(),
+                    plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                    dao_address: "0x1234567890123456789012345678901234567890".to_string(),
+                },
+                EditContent::ArchiveSpace {
+                    space_id: space_id.clone(),
+                },
+            )],
+            &block(2),
+        )
+        .await
+        .unwrap();
+
+    // Archived spaces are excluded by default
+    let spaces = space::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert!(spaces.is_empty());
+
+    let spaces = space::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .include_archived(true)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(spaces.len(), 1);
+    assert_eq!(spaces[0].id(), space_id);
+    assert_eq!(spaces[0].attributes.archived, Some(true));
+}
+
+#[tokio::test]
+async fn test_invalid_triple_is_recorded_as_failed_edit() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    handler
+        .process_edit(
+            &block(1),
+            edit(vec![pb::ipfs::Op {
+                r#type: pb::ipfs::OpType::SetTripleBatch as i32,
+                triples: vec![
+                    text_triple("alice", "first_name", "Alice"),
+                    pb::ipfs::Triple {
+                        entity: "alice".to_string(),
+                        attribute: "last_name".to_string(),
+                        value: None,
+                    },
+                ],
+                ..Default::default()
+            }]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    // The valid triple is still inserted
+    let first_name = triple::find_one(&neo4j, "first_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        first_name,
+        Some(Triple::new("alice", "first_name", "Alice"))
+    );
+
+    let failed_edits = failed_edit::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .space(prop_filter::value("space"))
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    assert_eq!(failed_edits.len(), 1);
+    assert_eq!(failed_edits[0].attributes.content_uri, "ipfs://content");
+    assert_eq!(failed_edits[0].attributes.block_number, 1);
+    assert_eq!(
+        failed_edits[0].attributes.reason,
+        "Invalid triple alice/last_name: Triple value is required"
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_edits_are_recorded() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    handler
+        .handle_edits_published(
+            vec![(
+                geo::EditPublished {
+                    content_uri: "ipfs://import".to_string(),
+                    plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                    dao_address: "0x1234567890123456789012345678901234567890".to_string(),
+                },
+                EditContent::Edits {
+                    edits: vec![edit(vec![set_triple_op(text_triple(
+                        "alice",
+                        "first_name",
+                        "Alice",
+                    ))])],
+                    rejected: vec![RejectedEdit {
+                        space_id: "space".to_string(),
+                        content_uri: "ipfs://no-authors".to_string(),
+                        reason: "Edit has no authors".to_string(),
+                    }],
+                },
+            )],
+            &block(1),
+        )
+        .await
+        .unwrap();
+
+    // Valid edits of the same event are still processed
+    let first_name = triple::find_one(&neo4j, "first_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        first_name,
+        Some(Triple::new("alice", "first_name", "Alice"))
+    );
+
+    let failed_edits = failed_edit::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    assert_eq!(failed_edits.len(), 1);
+    assert_eq!(failed_edits[0].attributes.space, "space");
+    assert_eq!(failed_edits[0].attributes.content_uri, "ipfs://no-authors");
+    assert_eq!(failed_edits[0].attributes.reason, "Edit has no authors");
+}
+
+#[tokio::test]
+async fn test_edits_of_different_spaces() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client)
+        .unwrap()
+        .versioning(true)
+        .edit_concurrency(2);
+
+    let edits = vec![
+        edit(vec![set_triple_op(text_triple("alice", "name", "Alice"))]),
+        Edit {
+            space_id: "other_space".to_string(),
+            ..edit(vec![set_triple_op(text_triple("bob", "name", "Bob"))])
+        },
+        edit(vec![set_triple_op(text_triple(
+            "alice",
+            "name",
+            "Alice Smith",
+        ))]),
+    ];
+
+    handler
+        .handle_edits_published(
+            vec![(
+                geo::EditPublished {
+                    content_uri: "ipfs://content".to_string(),
+                    plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                    dao_address: "0x0000000000000000000000000000000000000001".to_string(),
+                },
+                EditContent::Edits {
+                    edits,
+                    rejected: vec![],
+                },
+            )],
+            &block(1),
+        )
+        .await
+        .unwrap();
+
+    // Edits of the same space are applied in block order
+    let alice = triple::find_one(&neo4j, "name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(alice, Some(Triple::new("alice", "name", "Alice Smith")));
+
+    let bob = triple::find_one(&neo4j, "name", "bob", "other_space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bob, Some(Triple::new("bob", "name", "Bob")));
+}
+
+/// Embedder recording whether a text was embedded while another one was. Each call
+/// waits (up to a timeout) for a second call to start.
+#[derive(Default)]
+struct OverlapEmbedder {
+    state: Mutex<OverlapState>,
+    started: Condvar,
+    overlapped: AtomicBool,
+}
+
+#[derive(Default)]
+struct OverlapState {
+    in_flight: usize,
+    started: usize,
+}
+
+impl Embedder for OverlapEmbedder {
+    fn model(&self) -> &str {
+        "overlap"
+    }
+
+    fn dim(&self) -> usize {
+        4
+    }
+
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>, EmbeddingError> {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight += 1;
+        state.started += 1;
+        if state.in_flight > 1 {
+            self.overlapped.store(true, Ordering::SeqCst);
+        }
+        self.started.notify_all();
+
+        let (mut state, _) = self
+            .started
+            .wait_timeout_while(state, Duration::from_secs(5), |state| state.started < 2)
+            .unwrap();
+        state.in_flight -= 1;
+
+        Ok(texts.iter().map(|_| vec![1.0; 4]).collect())
+    }
+}
+
+#[tokio::test]
+async fn test_edits_of_different_spaces_are_concurrent() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let embedder = Arc::new(OverlapEmbedder::default());
+    let handler =
+        EventHandler::new_with_embedder(neo4j.clone(), ipfs_client, None, embedder.clone())
+            .edit_concurrency(2);
+
+    // The names are embedded: the edit of the second space can only be embedded while
+    // the edit of the first space is if both spaces are processed concurrently
+    let edits = vec![
+        edit(vec![set_triple_op(text_triple(
+            "alice",
+            system_ids::NAME_ATTRIBUTE,
+            "Alice",
+        ))]),
+        Edit {
+            space_id: "other_space".to_string(),
+            ..edit(vec![set_triple_op(text_triple(
+                "bob",
+                system_ids::NAME_ATTRIBUTE,
+                "Bob",
+            ))])
+        },
+    ];
+
+    handler
+        .handle_edits_published(
+            vec![(
+                geo::EditPublished {
+                    content_uri: "ipfs://content".to_string(),
+                    plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                    dao_address: "0x0000000000000000000000000000000000000001".to_string(),
+                },
+                EditContent::Edits {
+                    edits,
+                    rejected: vec![],
+                },
+            )],
+            &block(1),
+        )
+        .await
+        .unwrap();
+
+    assert!(embedder.overlapped.load(Ordering::SeqCst));
+
+    let bob = triple::find_one(
+        &neo4j,
+        system_ids::NAME_ATTRIBUTE,
+        "bob",
+        "other_space",
+        None,
+    )
+    .send()
+    .await
+    .unwrap();
+    assert_eq!(
+        bob.map(|triple| triple.value.value),
+        Some("Bob".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_blacklisted_edits_are_skipped() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Blacklist the author of the edit (addresses are case-insensitive)
+    let blacklist_file = std::env::temp_dir().join("test_blacklisted_edits_are_skipped.yaml");
+    std::fs::write(
+        &blacklist_file,
+        "authors:\n  - \"0X1234567890123456789012345678901234567890\"\n",
+    )
+    .unwrap();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client)
+        .unwrap()
+        .blacklist_file(&blacklist_file);
+
+    // The blacklisted author is not the creator of the edit
+    let mut blacklisted_edit = edit(vec![set_triple_op(text_triple(
+        "alice",
+        "first_name",
+        "Alice",
+    ))]);
+    blacklisted_edit.creator = "0x0000000000000000000000000000000000000002".to_string();
+    blacklisted_edit
+        .authors
+        .insert(0, blacklisted_edit.creator.clone());
+
+    handler
+        .process_edit(&block(1), blacklisted_edit, 0)
+        .await
+        .unwrap();
+
+    std::fs::remove_file(&blacklist_file).unwrap();
+
+    // The edit is not applied
+    let first_name = triple::find_one(&neo4j, "first_name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(first_name, None);
+
+    let skipped_edits = skipped_edit::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    assert_eq!(skipped_edits.len(), 1);
+    assert_eq!(skipped_edits[0].attributes.space, "space");
+    assert_eq!(skipped_edits[0].attributes.content_uri, "ipfs://content");
+    assert_eq!(
+        skipped_edits[0].attributes.author,
+        "0x1234567890123456789012345678901234567890"
+    );
+    assert_eq!(
+        skipped_edits[0].attributes.dao_address,
+        "0x0000000000000000000000000000000000000001"
+    );
+    assert_eq!(skipped_edits[0].attributes.block_number, 1);
+    assert_eq!(
+        skipped_edits[0].attributes.reason,
+        "Author 0x1234567890123456789012345678901234567890 is blacklisted"
+    );
+}
+
+#[tokio::test]
+async fn test_edit_metadata_and_synthetic_proposal() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler (governance events are not indexed)
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    handler
+        .process_edit(
+            &block(1),
+            edit(vec![
+                set_triple_op(text_triple("alice", "first_name", "Alice")),
+                set_triple_op(text_triple("bob", "first_name", "Bob")),
+                pb::ipfs::Op {
+                    r#type: pb::ipfs::OpType::DeleteTriple as i32,
+                    triple: Some(text_triple("alice", "last_name", "")),
+                    ..Default::default()
+                },
+            ]),
+            0,
+        )
+        .await
+        .unwrap();
+
+    let edit_metadata = models::edit::find_one(
+        &neo4j,
+        models::Edit::gen_id("ipfs://content"),
+        indexer_ids::INDEXER_SPACE_ID,
+    )
+    .send()
+    .await
+    .unwrap()
+    .expect("Edit metadata not found");
+
+    assert_eq!(edit_metadata.attributes.name, "Test edit");
+    assert_eq!(edit_metadata.attributes.block_number, Some(1));
+    assert_eq!(edit_metadata.attributes.set_triple_count, Some(2));
+    assert_eq!(edit_metadata.attributes.delete_triple_count, Some(1));
+    assert_eq!(edit_metadata.attributes.create_relation_count, Some(0));
+
+    let proposal = EditProposal::find_by_content_uri(&neo4j, "ipfs://content")
+        .await
+        .unwrap()
+        .expect("Synthetic proposal not found");
+
+    assert!(proposal
+        .types
+        .contains(&indexer_ids::SYNTHETIC_PROPOSAL.to_string()));
+    assert_eq!(proposal.attributes.proposal.onchain_proposal_id, "proposal");
+    assert_eq!(
+        proposal.attributes.proposal.status.to_string(),
+        ProposalStatus::Executed.to_string()
+    );
+}
@@ -0,0 +1,225 @@
+use futures::future::BoxFuture;
+use grc20_core::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{query_utils::Query, triple, Triple},
+    neo4rs,
+};
+use sink::{
+    events::EventHandler,
+    migrations::{self, Migration, MigrationAction, MigrationOutcome},
+};
+
+mod common;
+
+fn backfill(handler: &EventHandler) -> BoxFuture<'_, Result<(), DatabaseError>> {
+    Box::pin(async move {
+        handler
+            .neo4j()
+            .run(neo4rs::query(
+                "MATCH (e:Entity {id: 'migration-test'}) SET e.backfilled = true",
+            ))
+            .await?;
+        Ok(())
+    })
+}
+
+const TEST_MIGRATIONS: &[Migration] = &[
+    Migration {
+        id: "0001_test_cypher",
+        description: "Create a test entity",
+        action: MigrationAction::Cypher(&["MERGE (:Entity {id: 'migration-test'})"]),
+    },
+    Migration {
+        id: "0002_test_backfill",
+        description: "Backfill the test entity",
+        action: MigrationAction::Run(backfill),
+    },
+];
+
+#[test_log::test(tokio::test)]
+async fn test_run_migrations() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    // Pending migrations are applied in order and recorded
+    let outcome = migrations::run_migrations(&handler, TEST_MIGRATIONS)
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        MigrationOutcome::Applied(vec!["0001_test_cypher", "0002_test_backfill"])
+    );
+
+    for migration in TEST_MIGRATIONS {
+        assert!(migrations::is_applied(&handler, migration.id)
+            .await
+            .unwrap());
+    }
+
+    let mut result = neo4j
+        .execute(neo4rs::query(
+            "MATCH (e:Entity {id: 'migration-test'}) RETURN e.backfilled AS backfilled",
+        ))
+        .await
+        .unwrap();
+    let row = result.next().await.unwrap().expect("Test entity not found");
+    assert!(row.get::<bool>("backfilled").unwrap());
+
+    // Applied migrations are skipped
+    let outcome = migrations::run_migrations(&handler, TEST_MIGRATIONS)
+        .await
+        .unwrap();
+    assert_eq!(outcome, MigrationOutcome::Applied(vec![]));
+}
+
+#[test_log::test(tokio::test)]
+async fn test_run_migrations_reset_required() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    let migrations = [
+        Migration {
+            id: "0001_test_cypher",
+            description: "Create a test entity",
+            action: MigrationAction::Cypher(&["MERGE (:Entity {id: 'migration-test'})"]),
+        },
+        Migration {
+            id: "0002_test_reset",
+            description: "Change that cannot be applied in place",
+            action: MigrationAction::Reset,
+        },
+        Migration {
+            id: "0003_test_backfill",
+            description: "Backfill the test entity",
+            action: MigrationAction::Run(backfill),
+        },
+    ];
+
+    let outcome = migrations::run_migrations(&handler, &migrations)
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        MigrationOutcome::ResetRequired {
+            migration: "0002_test_reset"
+        }
+    );
+
+    // Migrations preceding the reset are recorded, the others are not
+    assert!(migrations::is_applied(&handler, "0001_test_cypher")
+        .await
+        .unwrap());
+    assert!(!migrations::is_applied(&handler, "0002_test_reset")
+        .await
+        .unwrap());
+    assert!(!migrations::is_applied(&handler, "0003_test_backfill")
+        .await
+        .unwrap());
+
+    // After a reset, all migrations are recorded as applied
+    migrations::mark_all_applied(&handler, &migrations)
+        .await
+        .unwrap();
+    let outcome = migrations::run_migrations(&handler, &migrations)
+        .await
+        .unwrap();
+    assert_eq!(outcome, MigrationOutcome::Applied(vec![]));
+
+    // Sanity check that migrations are recorded in the indexer space
+    let recorded = triple::find_one(
+        &neo4j,
+        indexer_ids::MIGRATION_ID_ATTRIBUTE,
+        format!("{}:0002_test_reset", indexer_ids::MIGRATION_TYPE),
+        indexer_ids::INDEXER_SPACE_ID,
+        Some("0".to_string()),
+    )
+    .send()
+    .await
+    .unwrap();
+    assert!(recorded.is_some());
+}
+
+#[test_log::test(tokio::test)]
+async fn test_upgrade_edges_without_block_numbers() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    // Seed an attribute edge shaped like the ones written before block numbers were
+    // recorded on edges
+    triple::insert_one(
+        &neo4j,
+        &common::create_block_metadata(),
+        "space",
+        "0",
+        Triple::new("alice", "name", "Alice"),
+    )
+    .send()
+    .await
+    .unwrap();
+    neo4j
+        .run(neo4rs::query(&format!(
+            "MATCH () -[r:ATTRIBUTE]-> () REMOVE r.`{}`, r.`{}`",
+            indexer_ids::CREATED_AT_BLOCK,
+            indexer_ids::UPDATED_AT_BLOCK
+        )))
+        .await
+        .unwrap();
+
+    migrations::run(&handler).await.unwrap();
+
+    // Updating the triple closes the legacy edge
+    triple::insert_one(
+        &neo4j,
+        &BlockMetadata {
+            block_number: 1,
+            ..common::create_block_metadata()
+        },
+        "space",
+        "0",
+        Triple::new("alice", "name", "Alicia"),
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let mut result = neo4j
+        .execute(neo4rs::query(
+            r#"
+            MATCH (:Entity {id: "alice"}) -[r:ATTRIBUTE {space_id: "space"}]-> (:Attribute {id: "name"})
+            WHERE r.max_version IS NULL
+            RETURN count(r) AS open_edges
+            "#,
+        ))
+        .await
+        .unwrap();
+    let row = result.next().await.unwrap().expect("No result");
+    assert_eq!(row.get::<i64>("open_edges").unwrap(), 1);
+
+    let name = triple::find_one(&neo4j, "name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(name, Some(Triple::new("alice", "name", "Alicia")));
+}
+
+#[test]
+fn test_migration_ids_are_sequential() {
+    for (idx, migration) in migrations::MIGRATIONS.iter().enumerate() {
+        assert!(
+            migration.id.starts_with(&format!("{idx:04}_")),
+            "Migration {} should be numbered {idx:04}",
+            migration.id
+        );
+    }
+}
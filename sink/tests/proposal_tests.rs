@@ -0,0 +1,333 @@
+use chrono::DateTime;
+use grc20_core::{
+    block::BlockMetadata,
+    indexer_ids,
+    mapping::{triple, Query},
+    pb::geo,
+};
+use grc20_sdk::models::{
+    account,
+    proposal::{VoteTally, VotingSettings},
+    vote::VoteType,
+    Proposal,
+};
+
+mod common;
+
+const DAO_ADDRESS: &str = "0x1234567890123456789012345678901234567890";
+const VOTING_PLUGIN_ADDRESS: &str = "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd";
+
+fn block(block_number: u64, timestamp: i64) -> BlockMetadata {
+    BlockMetadata {
+        block_number,
+        timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+        ..common::create_block_metadata()
+    }
+}
+
+fn edit_proposal(proposal_id: &str, end_time: i64) -> geo::PublishEditProposalCreated {
+    geo::PublishEditProposalCreated {
+        proposal_id: proposal_id.to_string(),
+        creator: "0x0000000000000000000000000000000000000001".to_string(),
+        start_time: "0".to_string(),
+        end_time: end_time.to_string(),
+        content_uri: format!("ipfs://{proposal_id}"),
+        dao_address: DAO_ADDRESS.to_string(),
+        plugin_address: VOTING_PLUGIN_ADDRESS.to_string(),
+    }
+}
+
+fn vote(proposal_id: &str, voter: &str, vote_option: u64) -> geo::VoteCast {
+    geo::VoteCast {
+        onchain_proposal_id: proposal_id.to_string(),
+        voter: voter.to_string(),
+        vote_option,
+        plugin_address: VOTING_PLUGIN_ADDRESS.to_string(),
+    }
+}
+
+async fn status(neo4j: &grc20_core::neo4rs::Graph, proposal_id: &str) -> Option<String> {
+    triple::find_one(
+        neo4j,
+        indexer_ids::PROPOSAL_STATUS_ATTRIBUTE,
+        Proposal::gen_id(VOTING_PLUGIN_ADDRESS, proposal_id),
+        indexer_ids::INDEXER_SPACE_ID,
+        None,
+    )
+    .send()
+    .await
+    .unwrap()
+    .map(|triple| triple.value.value)
+}
+
+#[tokio::test]
+async fn test_proposal_status_lifecycle() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    let voters = [
+        "0x0000000000000000000000000000000000000002",
+        "0x0000000000000000000000000000000000000003",
+    ];
+    for voter in voters {
+        account::new(voter.to_string())
+            .insert(&neo4j, &block(1, 0), indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    for proposal_id in ["1", "2", "3", "4"] {
+        handler
+            .handle_publish_edit_proposal_created(&edit_proposal(proposal_id, 100), &block(1, 0))
+            .await
+            .unwrap();
+    }
+    handler
+        .handle_publish_edit_proposal_created(&edit_proposal("5", 50), &block(1, 0))
+        .await
+        .unwrap();
+
+    // Proposal 1 has more accept votes, proposal 2 more reject votes (after a vote
+    // change), proposal 3 receives no votes and proposal 4 is executed
+    handler
+        .handle_vote_cast(&vote("1", voters[0], 2), &block(2, 50))
+        .await
+        .unwrap();
+    handler
+        .handle_vote_cast(&vote("2", voters[0], 2), &block(2, 50))
+        .await
+        .unwrap();
+    handler
+        .handle_vote_cast(&vote("2", voters[1], 3), &block(2, 50))
+        .await
+        .unwrap();
+    handler
+        .handle_vote_cast(&vote("2", voters[0], 3), &block(3, 60))
+        .await
+        .unwrap();
+    handler
+        .handle_proposal_executed(
+            &geo::ProposalExecuted {
+                proposal_id: "4".to_string(),
+                plugin_address: VOTING_PLUGIN_ADDRESS.to_string(),
+            },
+            &block(3, 60),
+            0,
+        )
+        .await
+        .unwrap();
+
+    // Voting is still open. Proposal 5 ended before the previously processed block, so
+    // it is not looked up again.
+    triple::Triple::new(
+        indexer_ids::CURSOR_ID,
+        indexer_ids::BLOCK_TIMESTAMP_ATTRIBUTE,
+        DateTime::from_timestamp(60, 0).unwrap(),
+    )
+    .insert(
+        &neo4j,
+        &BlockMetadata::default(),
+        indexer_ids::INDEXER_SPACE_ID,
+        "0",
+    )
+    .send()
+    .await
+    .unwrap();
+    handler
+        .update_proposal_statuses(&block(4, 99))
+        .await
+        .unwrap();
+    assert_eq!(status(&neo4j, "1").await.as_deref(), Some("Proposed"));
+    assert_eq!(status(&neo4j, "4").await.as_deref(), Some("Executed"));
+    assert_eq!(status(&neo4j, "5").await.as_deref(), Some("Proposed"));
+
+    // Voting ended
+    handler
+        .update_proposal_statuses(&block(5, 100))
+        .await
+        .unwrap();
+    assert_eq!(status(&neo4j, "1").await.as_deref(), Some("Accepted"));
+    assert_eq!(status(&neo4j, "2").await.as_deref(), Some("Rejected"));
+    assert_eq!(status(&neo4j, "3").await.as_deref(), Some("Rejected"));
+    assert_eq!(status(&neo4j, "4").await.as_deref(), Some("Executed"));
+}
+
+#[tokio::test]
+async fn test_proposal_voting_settings() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler requiring half of the editors to vote
+    let handler = common::create_handler(neo4j.clone(), ipfs_client)
+        .unwrap()
+        .voting_settings(VotingSettings {
+            support_threshold: 500_000,
+            min_participation: 500_000,
+        });
+
+    let editors = [
+        "0x0000000000000000000000000000000000000002",
+        "0x0000000000000000000000000000000000000003",
+        "0x0000000000000000000000000000000000000004",
+        "0x0000000000000000000000000000000000000005",
+    ];
+
+    handler
+        .handle_space_created(
+            &geo::GeoSpaceCreated {
+                dao_address: DAO_ADDRESS.to_string(),
+                space_address: DAO_ADDRESS.to_string(),
+            },
+            &[],
+            &block(1, 0),
+        )
+        .await
+        .unwrap();
+    handler
+        .handle_governance_plugin_created(
+            &geo::GeoGovernancePluginCreated {
+                dao_address: DAO_ADDRESS.to_string(),
+                main_voting_address: VOTING_PLUGIN_ADDRESS.to_string(),
+                member_access_address: VOTING_PLUGIN_ADDRESS.to_string(),
+            },
+            &block(1, 0),
+        )
+        .await
+        .unwrap();
+    handler
+        .handle_initial_space_editors_added(
+            &geo::InitialEditorAdded {
+                addresses: editors.iter().map(|editor| editor.to_string()).collect(),
+                plugin_address: VOTING_PLUGIN_ADDRESS.to_string(),
+                dao_address: DAO_ADDRESS.to_string(),
+            },
+            &block(1, 0),
+        )
+        .await
+        .unwrap();
+
+    // Handlers created afterwards use the settings indexed on the space
+    let handler = handler.voting_settings(VotingSettings::default());
+
+    for proposal_id in ["1", "2"] {
+        handler
+            .handle_publish_edit_proposal_created(&edit_proposal(proposal_id, 100), &block(1, 0))
+            .await
+            .unwrap();
+    }
+
+    // Proposal 1 does not reach the minimum participation, proposal 2 does
+    handler
+        .handle_vote_cast(&vote("1", editors[0], 2), &block(2, 50))
+        .await
+        .unwrap();
+    for editor in &editors[..2] {
+        handler
+            .handle_vote_cast(&vote("2", editor, 2), &block(2, 50))
+            .await
+            .unwrap();
+    }
+
+    handler
+        .update_proposal_statuses(&block(3, 100))
+        .await
+        .unwrap();
+    assert_eq!(status(&neo4j, "1").await.as_deref(), Some("Rejected"));
+    assert_eq!(status(&neo4j, "2").await.as_deref(), Some("Accepted"));
+}
+
+#[tokio::test]
+async fn test_vote_tally() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    let voters = [
+        "0x0000000000000000000000000000000000000002",
+        "0x0000000000000000000000000000000000000003",
+        "0x0000000000000000000000000000000000000004",
+    ];
+    for voter in voters {
+        account::new(voter.to_string())
+            .insert(&neo4j, &block(1, 0), indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    handler
+        .handle_publish_edit_proposal_created(&edit_proposal("1", 100), &block(1, 0))
+        .await
+        .unwrap();
+
+    let proposal_id = Proposal::gen_id(VOTING_PLUGIN_ADDRESS, "1");
+    assert_eq!(
+        Proposal::vote_tally(&neo4j, &proposal_id).await.unwrap(),
+        VoteTally::default()
+    );
+
+    for (voter, vote_option) in [(voters[0], 2), (voters[1], 3), (voters[2], 1)] {
+        handler
+            .handle_vote_cast(&vote("1", voter, vote_option), &block(2, 10))
+            .await
+            .unwrap();
+    }
+
+    // Vote changes (including a repeated vote) do not add voters
+    handler
+        .handle_vote_cast(&vote("1", voters[1], 2), &block(3, 20))
+        .await
+        .unwrap();
+    handler
+        .handle_vote_cast(&vote("1", voters[1], 2), &block(4, 30))
+        .await
+        .unwrap();
+
+    let expected = VoteTally {
+        accept: 2,
+        reject: 0,
+        abstain: 1,
+        voters: 3,
+    };
+    assert_eq!(
+        Proposal::vote_tally(&neo4j, &proposal_id).await.unwrap(),
+        expected
+    );
+    assert_eq!(
+        Proposal::count_votes(&neo4j, &proposal_id).await.unwrap(),
+        expected
+    );
+
+    let proposal = Proposal::find_by_id_and_address(&neo4j, "1", VOTING_PLUGIN_ADDRESS)
+        .await
+        .unwrap()
+        .expect("Proposal not found");
+    assert_eq!(proposal.attributes.votes, expected);
+}
+
+#[test]
+fn test_vote_tally_does_not_underflow() {
+    // A tally out of sync with the votes (e.g.: the vote was indexed before the tally
+    // existed) must not underflow when the vote is changed
+    let mut tally = VoteTally::default();
+    tally.apply(Some(VoteType::Reject), VoteType::Accept);
+
+    assert_eq!(
+        tally,
+        VoteTally {
+            accept: 1,
+            reject: 0,
+            abstain: 0,
+            voters: 0,
+        }
+    );
+}
@@ -0,0 +1,140 @@
+use grc20_core::{
+    block::BlockMetadata,
+    mapping::{triple, Query, Triple},
+    network_ids,
+    pb::{self, geo},
+};
+use grc20_sdk::models::space;
+use sink::{
+    events::Edit,
+    reindex::{self, ReindexScope},
+};
+
+mod common;
+
+fn edit(space_id: &str, triple: pb::ipfs::Triple) -> Edit {
+    Edit {
+        name: "Test edit".to_string(),
+        proposal_id: "proposal".to_string(),
+        space_id: space_id.to_string(),
+        space_plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+        dao_address: "0x0000000000000000000000000000000000000001".to_string(),
+        creator: "0x1234567890123456789012345678901234567890".to_string(),
+        authors: vec!["0x1234567890123456789012345678901234567890".to_string()],
+        content_uri: "ipfs://content".to_string(),
+        ops: vec![pb::ipfs::Op {
+            r#type: pb::ipfs::OpType::SetTriple as i32,
+            triple: Some(triple),
+            ..Default::default()
+        }],
+    }
+}
+
+fn name_triple(entity: &str, name: &str) -> pb::ipfs::Triple {
+    pb::ipfs::Triple {
+        entity: entity.to_string(),
+        attribute: "name".to_string(),
+        value: Some(pb::ipfs::Value {
+            r#type: pb::ipfs::ValueType::Text as i32,
+            value: name.to_string(),
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_reindex_spaces() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    let block = |block_number| BlockMetadata {
+        block_number,
+        ..common::create_block_metadata()
+    };
+
+    handler
+        .process_edit(&block(1), edit("space", name_triple("alice", "Alice")), 0)
+        .await
+        .unwrap();
+    handler
+        .process_edit(
+            &block(2),
+            edit("space", name_triple("alice", "Alice Smith")),
+            0,
+        )
+        .await
+        .unwrap();
+    handler
+        .process_edit(&block(2), edit("other_space", name_triple("bob", "Bob")), 1)
+        .await
+        .unwrap();
+
+    let handler = handler.reindex(ReindexScope::new(2).spaces(["space"]));
+    reindex::prepare(&handler).await.unwrap();
+
+    // Writes of the re-indexed space from the start block are rolled back
+    let alice = triple::find_one(&neo4j, "name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(alice, Some(Triple::new("alice", "name", "Alice")));
+
+    // Other spaces are untouched
+    let bob = triple::find_one(&neo4j, "name", "bob", "other_space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bob, Some(Triple::new("bob", "name", "Bob")));
+
+    // Rebuilding the data restores the state of the space
+    handler
+        .process_edit(
+            &block(2),
+            edit("space", name_triple("alice", "Alice Smith")),
+            0,
+        )
+        .await
+        .unwrap();
+
+    let alice = triple::find_one(&neo4j, "name", "alice", "space", None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(alice, Some(Triple::new("alice", "name", "Alice Smith")));
+
+    reindex::finish(&handler).await.unwrap();
+}
+
+#[test]
+fn test_reindex_scope_events() {
+    let dao_address = "0x0000000000000000000000000000000000000001";
+    let other_dao_address = "0x0000000000000000000000000000000000000002";
+    let scope = ReindexScope::new(1).spaces([space::new_id(network_ids::GEO, dao_address)]);
+
+    let mut output = geo::GeoOutput {
+        edits_published: [dao_address, other_dao_address]
+            .map(|dao_address| geo::EditPublished {
+                content_uri: "ipfs://content".to_string(),
+                plugin_address: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                dao_address: dao_address.to_string(),
+            })
+            .to_vec(),
+        members_added: [dao_address, other_dao_address]
+            .map(|dao_address| geo::MemberAdded {
+                member_address: "0x1234567890123456789012345678901234567890".to_string(),
+                dao_address: dao_address.to_string(),
+                ..Default::default()
+            })
+            .to_vec(),
+        ..Default::default()
+    };
+    scope.retain_dao_events(&mut output);
+
+    // Only the events of the re-indexed space are kept
+    assert_eq!(output.edits_published.len(), 1);
+    assert_eq!(output.edits_published[0].dao_address, dao_address);
+    assert_eq!(output.members_added.len(), 1);
+    assert_eq!(output.members_added[0].dao_address, dao_address);
+}
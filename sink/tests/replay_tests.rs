@@ -0,0 +1,111 @@
+use grc20_core::{indexer_ids, mapping::Query, network_ids, pb::geo};
+use grc20_sdk::models::space;
+use prost::Message;
+use substreams_utils::{
+    pb::sf::substreams::{
+        rpc::v2::{BlockScopedData, MapModuleOutput},
+        v1::Clock,
+    },
+    recording::{self, Recorder},
+    substreams_stream::RawBlockResponse,
+    Sink,
+};
+
+mod common;
+
+const DAO_ADDRESSES: [&str; 2] = [
+    "0x1234567890123456789012345678901234567890",
+    "0x2234567890123456789012345678901234567890",
+];
+
+fn raw_block(block_number: u64, output: geo::GeoOutput) -> BlockScopedData {
+    BlockScopedData {
+        output: Some(MapModuleOutput {
+            name: "geo_out".to_string(),
+            map_output: Some(prost_types::Any {
+                type_url: "type.googleapis.com/geo.GeoOutput".to_string(),
+                value: output.encode_to_vec(),
+            }),
+            debug_info: None,
+        }),
+        clock: Some(Clock {
+            id: format!("block-{block_number}"),
+            number: block_number,
+            timestamp: Some(prost_types::Timestamp {
+                seconds: block_number as i64,
+                nanos: 0,
+            }),
+        }),
+        cursor: format!("cursor-{block_number}"),
+        ..Default::default()
+    }
+}
+
+fn space_created(dao_address: &str) -> geo::GeoOutput {
+    geo::GeoOutput {
+        spaces_created: vec![geo::GeoSpaceCreated {
+            dao_address: dao_address.to_string(),
+            space_address: dao_address.to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+
+    // Record two blocks
+    let path = std::env::temp_dir().join(format!(
+        "kg-sink-recording-{}.bin",
+        grc20_core::ids::create_geo_id()
+    ));
+    let mut recorder = Recorder::create(&path).await.unwrap();
+    for (block_number, dao_address) in [(10, DAO_ADDRESSES[0]), (11, DAO_ADDRESSES[1])] {
+        recorder
+            .record(&RawBlockResponse::New(raw_block(
+                block_number,
+                space_created(dao_address),
+            )))
+            .await
+            .unwrap();
+    }
+    assert_eq!(recorder.finish().await.unwrap(), 2);
+
+    let recorded = recording::read_recording(&path).await.unwrap();
+    assert_eq!(recorded.len(), 2);
+    assert!(matches!(
+        &recorded[1],
+        RawBlockResponse::New(block) if block.clock.as_ref().unwrap().number == 11
+    ));
+
+    // Replay them through the handler
+    handler.replay(&path, Some(2)).await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for dao_address in DAO_ADDRESSES {
+        let space_id = space::new_id(network_ids::GEO, dao_address);
+        assert!(
+            space::find_one(&neo4j, &space_id, indexer_ids::INDEXER_SPACE_ID)
+                .send()
+                .await
+                .unwrap()
+                .is_some(),
+            "Space of DAO {dao_address} not created"
+        );
+    }
+
+    assert_eq!(
+        handler.load_persisted_cursor().await.unwrap().as_deref(),
+        Some("cursor-11")
+    );
+    assert_eq!(
+        handler.health().last_block().map(|block| block.number),
+        Some(11)
+    );
+}
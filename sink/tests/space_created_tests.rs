@@ -1,5 +1,5 @@
 use grc20_core::{entity::Entity, indexer_ids, mapping::Query, network_ids, pb::geo};
-use grc20_sdk::models::{space, SpaceGovernanceType};
+use grc20_sdk::models::{proposal::VotingSettings, space, SpaceGovernanceType};
 use web3_utils::checksum_address;
 
 mod common;
@@ -45,6 +45,9 @@ async fn test_handle_space_created() {
             personal_space_admin_plugin: None,
             voting_plugin_address: None,
             member_access_plugin: None,
+            archived: None,
+            support_threshold: None,
+            min_participation: None,
         },
     );
 
@@ -108,6 +111,9 @@ async fn test_handle_personal_space_created() {
             )),
             voting_plugin_address: None,
             member_access_plugin: None,
+            archived: None,
+            support_threshold: None,
+            min_participation: None,
         },
     );
 
@@ -173,6 +179,9 @@ async fn test_handle_governance_plugin_created() {
             member_access_plugin: Some(checksum_address(
                 &governance_plugin_created.member_access_address,
             )),
+            archived: None,
+            support_threshold: Some(VotingSettings::default().support_threshold),
+            min_participation: Some(VotingSettings::default().min_participation),
         },
     );
 
@@ -0,0 +1,84 @@
+use futures::TryStreamExt;
+use grc20_core::{mapping::QueryStream, pb::geo};
+use grc20_sdk::models::space;
+
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn test_handle_successor_space_created() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+    let block = common::create_block_metadata();
+
+    // Create predecessor space
+    let predecessor_dao_address = "0x1234567890123456789012345678901234567890";
+    let predecessor_plugin_address = "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd";
+
+    let predecessor_space_id = handler
+        .handle_space_created(
+            &geo::GeoSpaceCreated {
+                dao_address: predecessor_dao_address.to_string(),
+                space_address: predecessor_plugin_address.to_string(),
+            },
+            &[],
+            &block,
+        )
+        .await
+        .unwrap();
+
+    // Create successor space
+    let successor_dao_address = "0x9876543210987654321098765432109876543210";
+    let successor_plugin_address = "0xfedcbafedcbafedcbafedcbafedcbafedcbafedcba";
+
+    let successor_space_id = handler
+        .handle_space_created(
+            &geo::GeoSpaceCreated {
+                dao_address: successor_dao_address.to_string(),
+                space_address: successor_plugin_address.to_string(),
+            },
+            &[],
+            &block,
+        )
+        .await
+        .unwrap();
+
+    // Call handler
+    handler
+        .handle_successor_space_created(
+            &geo::SuccessorSpaceCreated {
+                predecessor_space: predecessor_dao_address.to_string(),
+                plugin_address: successor_plugin_address.to_string(),
+                dao_address: successor_dao_address.to_string(),
+            },
+            &block,
+        )
+        .await
+        .unwrap();
+
+    // Verify the lineage can be followed in both directions
+    let successors = space::successors(&neo4j, &predecessor_space_id)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to collect successor spaces");
+
+    assert_eq!(successors.len(), 1);
+    assert_eq!(successors[0].space_id, successor_space_id);
+
+    let predecessors = space::predecessors(&neo4j, &successor_space_id)
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to collect predecessor spaces");
+
+    assert_eq!(predecessors.len(), 1);
+    assert_eq!(predecessors[0].space_id, predecessor_space_id);
+}
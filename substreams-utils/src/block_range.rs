@@ -0,0 +1,113 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::Context;
+
+/// Range of blocks to stream, written `<start>:<stop>` where:
+/// - `<start>` is empty (the initial block of the output module), `+N` (N blocks after
+///   the initial block) or a block number (negative numbers are relative to the head
+///   of the chain)
+/// - `<stop>` is empty or `-` (stream indefinitely), `+N` (N blocks after the start
+///   block) or a block number (exclusive)
+///
+/// A range without `:` only sets the stop block, e.g.: `1000:+500`, `:-`, `+10:`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockRange {
+    pub start: StartBlock,
+    pub stop: StopBlock,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StartBlock {
+    /// Initial block of the output module
+    #[default]
+    Initial,
+    /// Number of blocks after the initial block of the output module
+    AfterInitial(u64),
+    Block(i64),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopBlock {
+    #[default]
+    Unbounded,
+    /// Number of blocks after the start block
+    AfterStart(u64),
+    Block(u64),
+}
+
+impl BlockRange {
+    /// Range from `start` to `stop` (exclusive), or unbounded if `stop` is 0
+    pub fn new(start: i64, stop: u64) -> Self {
+        Self {
+            start: StartBlock::Block(start),
+            stop: match stop {
+                0 => StopBlock::Unbounded,
+                stop => StopBlock::Block(stop),
+            },
+        }
+    }
+
+    /// Resolves the range against the initial block of the output module. Returns the
+    /// start block and the stop block (0 if unbounded) of the substreams request.
+    pub fn resolve(&self, initial_block: u64) -> (i64, u64) {
+        let start = match self.start {
+            StartBlock::Initial => initial_block as i64,
+            StartBlock::AfterInitial(count) => (initial_block + count) as i64,
+            StartBlock::Block(block) => block,
+        };
+
+        let stop = match self.stop {
+            StopBlock::Unbounded => 0,
+            StopBlock::AfterStart(count) => start.max(0) as u64 + count,
+            StopBlock::Block(block) => block,
+        };
+
+        (start, stop)
+    }
+}
+
+impl FromStr for BlockRange {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (prefix, suffix) = input.split_once(':').unwrap_or(("", input));
+
+        let start = match prefix {
+            "" => StartBlock::Initial,
+            x if x.starts_with('+') => StartBlock::AfterInitial(
+                x.trim_start_matches('+')
+                    .parse()
+                    .context("<start> is not a valid block count")?,
+            ),
+            x => StartBlock::Block(x.parse().context("<start> is not a valid block number")?),
+        };
+
+        let stop = match suffix {
+            "" | "-" => StopBlock::Unbounded,
+            x if x.starts_with('+') => StopBlock::AfterStart(
+                x.trim_start_matches('+')
+                    .parse()
+                    .context("<stop> is not a valid block count")?,
+            ),
+            x => StopBlock::Block(x.parse().context("<stop> is not a valid block number")?),
+        };
+
+        Ok(Self { start, stop })
+    }
+}
+
+impl Display for BlockRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.start {
+            StartBlock::Initial => {}
+            StartBlock::AfterInitial(count) => write!(f, "+{count}")?,
+            StartBlock::Block(block) => write!(f, "{block}")?,
+        }
+        match self.stop {
+            StopBlock::Unbounded => write!(f, ":-"),
+            StopBlock::AfterStart(count) => write!(f, ":+{count}"),
+            StopBlock::Block(block) => write!(f, ":{block}"),
+        }
+    }
+}
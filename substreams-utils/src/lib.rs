@@ -1,6 +1,12 @@
+pub mod block_range;
+pub mod metrics;
 pub mod pb;
+pub mod recording;
+pub mod retry;
 pub mod sink;
 pub mod substreams;
 pub mod substreams_stream;
 
-pub use sink::Sink;
+pub use block_range::BlockRange;
+pub use retry::RetryPolicy;
+pub use sink::{Sink, SinkError};
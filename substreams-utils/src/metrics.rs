@@ -0,0 +1,28 @@
+//! Metrics of the substreams connection, registered in the default prometheus registry
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter, register_int_gauge_vec, IntCounter, IntGaugeVec};
+
+use crate::substreams::EndpointPool;
+
+lazy_static! {
+    pub static ref ACTIVE_ENDPOINT: IntGaugeVec = register_int_gauge_vec!(
+        "substreams_active_endpoint",
+        "Whether the substreams endpoint is the one blocks are streamed from (1) or not (0)",
+        &["endpoint"]
+    )
+    .expect("Failed to create substreams_active_endpoint gauge");
+    pub static ref ENDPOINT_FAILOVERS: IntCounter = register_int_counter!(
+        "substreams_endpoint_failovers_total",
+        "Number of times the stream switched to another substreams endpoint"
+    )
+    .expect("Failed to create substreams_endpoint_failovers_total counter");
+}
+
+/// Marks the endpoint at `active` as the only active endpoint of `pool`
+pub(crate) fn set_active_endpoint(pool: &EndpointPool, active: usize) {
+    for (index, endpoint) in pool.endpoints().iter().enumerate() {
+        ACTIVE_ENDPOINT
+            .with_label_values(&[&endpoint.uri])
+            .set((index == active) as i64);
+    }
+}
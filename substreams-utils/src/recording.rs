@@ -0,0 +1,141 @@
+//! Recording of block streams to local files, and replay of such recordings (see
+//! [`SubstreamsStream::replay`]), e.g.: to capture the blocks reproducing a bug and
+//! process them again without a substreams endpoint.
+//!
+//! A recording is a sequence of length-delimited `sf.substreams.rpc.v2.Response`
+//! messages, each holding either a `BlockScopedData` or a `BlockUndoSignal`, in the
+//! order they were received.
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+use futures::StreamExt;
+use prost::Message;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+use crate::{
+    pb::sf::substreams::rpc::v2::{response, Response},
+    substreams_stream::{RawBlockResponse, SubstreamsStream},
+};
+
+/// Writes the blocks of a stream to a recording file
+pub struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<tokio::fs::File>,
+    count: usize,
+}
+
+impl Recorder {
+    /// Creates (or truncates) the recording file at `path`
+    pub async fn create(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref().to_path_buf();
+        let file = tokio::fs::File::create(&path)
+            .await
+            .with_context(|| format!("create recording file '{}'", path.display()))?;
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            count: 0,
+        })
+    }
+
+    pub async fn record(&mut self, response: &RawBlockResponse) -> Result<(), anyhow::Error> {
+        let message = match response {
+            RawBlockResponse::New(block) => response::Message::BlockScopedData(block.clone()),
+            RawBlockResponse::Undo(undo) => response::Message::BlockUndoSignal(undo.clone()),
+        };
+        let bytes = Response {
+            message: Some(message),
+        }
+        .encode_length_delimited_to_vec();
+
+        self.writer
+            .write_all(&bytes)
+            .await
+            .with_context(|| format!("write recording file '{}'", self.path.display()))?;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Flushes the recording file. Returns the number of responses recorded.
+    pub async fn finish(mut self) -> Result<usize, anyhow::Error> {
+        self.writer
+            .flush()
+            .await
+            .with_context(|| format!("write recording file '{}'", self.path.display()))?;
+        Ok(self.count)
+    }
+}
+
+/// Records `stream` to the file at `path` until the stream ends or `shutdown` resolves
+/// (e.g.: on a termination signal, as the stream of an unbounded range never ends).
+/// Returns the number of responses recorded. On error or shutdown, the responses
+/// received so far are kept.
+pub async fn record(
+    mut stream: SubstreamsStream,
+    path: impl AsRef<Path>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<usize, anyhow::Error> {
+    let mut recorder = Recorder::create(path).await?;
+
+    tokio::pin!(shutdown);
+
+    loop {
+        let next = tokio::select! {
+            biased;
+            _ = &mut shutdown => break,
+            next = stream.next() => next,
+        };
+
+        match next {
+            Some(Ok(response)) => recorder.record(&response).await?,
+            Some(Err(err)) => {
+                recorder.finish().await?;
+                return Err(err);
+            }
+            None => break,
+        }
+    }
+
+    recorder.finish().await
+}
+
+/// Reads the responses of the recording file at `path`
+pub async fn read_recording(
+    path: impl AsRef<Path>,
+) -> Result<Vec<RawBlockResponse>, anyhow::Error> {
+    let path = path.as_ref();
+    let content = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("read recording file '{}'", path.display()))?;
+
+    let mut buf = content.as_slice();
+    let mut responses = vec![];
+    while !buf.is_empty() {
+        let response = Response::decode_length_delimited(&mut buf).with_context(|| {
+            format!(
+                "decode response #{} of recording file '{}'",
+                responses.len(),
+                path.display()
+            )
+        })?;
+
+        responses.push(match response.message {
+            Some(response::Message::BlockScopedData(block)) => RawBlockResponse::New(block),
+            Some(response::Message::BlockUndoSignal(undo)) => RawBlockResponse::Undo(undo),
+            _ => {
+                return Err(anyhow!(
+                    "response #{} of recording file '{}' is not a block or an undo signal",
+                    responses.len(),
+                    path.display()
+                ))
+            }
+        });
+    }
+
+    Ok(responses)
+}
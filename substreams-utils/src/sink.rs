@@ -1,15 +1,15 @@
-use std::{env, process::exit, sync::Arc};
-
 use anyhow::{format_err, Context};
 use futures::StreamExt;
 use prost::Message;
 
 use crate::{
+    block_range::BlockRange,
     pb::sf::substreams::{
         rpc::v2::{BlockScopedData, BlockUndoSignal},
-        v1::Package,
+        v1::{module, Modules, Package},
     },
-    substreams::SubstreamsEndpoint,
+    retry::RetryPolicy,
+    substreams::EndpointPool,
     substreams_stream::{RawBlockResponse, SubstreamsStream},
 };
 
@@ -18,6 +18,47 @@ pub enum PreprocessedBlockResponse<T> {
     Undo(BlockUndoSignal),
 }
 
+/// Error returned by [`Sink::run`]
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError<E: std::error::Error + Send + Sync + 'static> {
+    #[error("Failed to load the persisted cursor: {0}")]
+    Cursor(E),
+
+    /// The package could not be read, or the output module, the block range or the
+    /// module parameters do not match it
+    #[error("Invalid substreams package or request: {0:#}")]
+    Package(anyhow::Error),
+
+    /// The block stream failed for good (e.g.: every endpoint rejected its API token)
+    #[error("Substreams stream failed: {0:#}")]
+    Stream(anyhow::Error),
+
+    /// A block (or undo signal) could not be processed, even after retrying it if the
+    /// error was retryable
+    #[error("Failed to process block {} after {attempts} attempt(s): {error}", block.map_or("(unknown)".to_string(), |block| format!("#{block}")))]
+    Block {
+        block: Option<u64>,
+        attempts: u32,
+        retryable: bool,
+        error: E,
+    },
+}
+
+impl<E: std::error::Error + Send + Sync + 'static> SinkError<E> {
+    /// Whether running the sink again later may succeed, i.e.: the sink gave up on a
+    /// block because of an error considered retryable (see [`Sink::is_retryable`]).
+    /// Other errors are fatal and require an intervention.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            SinkError::Block {
+                retryable: true,
+                ..
+            }
+        )
+    }
+}
+
 pub trait Sink<T: Send>: Send + Sync {
     type Error: std::error::Error + Send + Sync + 'static;
 
@@ -32,15 +73,16 @@ pub trait Sink<T: Send>: Send + Sync {
         data: T,
     ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
 
-    fn process_block_undo_signal(&self, _undo_signal: &BlockUndoSignal) -> Result<(), Self::Error> {
-        // `BlockUndoSignal` must be treated as "delete every data that has been recorded after
-        // block height specified by block in BlockUndoSignal". In the example above, this means
-        // you must delete changes done by `Block #7b` and `Block #6b`. The exact details depends
-        // on your own logic. If for example all your added record contain a block number, a
-        // simple way is to do `delete all records where block_num > 5` which is the block num
-        // received in the `BlockUndoSignal` (this is true for append only records, so when only `INSERT` are allowed).
-        unimplemented!("you must implement some kind of block undo handling, or request only final blocks (tweak substreams_stream.rs)")
-    }
+    /// Reverts the data recorded after the last valid block of `undo_signal`, i.e.: every
+    /// record added after that block height must be deleted (for append only records, a
+    /// simple way is to delete all records where `block_num > last_valid_block`).
+    ///
+    /// Sinks which only request final blocks never receive undo signals, but must still
+    /// implement this method (e.g.: by returning an error).
+    fn process_block_undo_signal(
+        &self,
+        undo_signal: &BlockUndoSignal,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
 
     fn persist_cursor(
         &self,
@@ -66,70 +108,251 @@ pub trait Sink<T: Send>: Send + Sync {
         async { Ok(None) }
     }
 
+    /// Processes a new block and persists its cursor. Sinks should override this method
+    /// if the block data and the cursor can be persisted atomically.
+    fn apply_block_scoped_data(
+        &self,
+        block_data: BlockScopedData,
+        data: T,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        async move {
+            self.process_block_scoped_data(&block_data, data).await?;
+            self.persist_cursor(block_data.cursor).await
+        }
+    }
+
+    /// Processes a block undo signal and persists the last valid cursor. Sinks should
+    /// override this method if the rollback and the cursor can be persisted atomically.
+    fn apply_block_undo_signal(
+        &self,
+        undo_signal: BlockUndoSignal,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        async move {
+            self.process_block_undo_signal(&undo_signal).await?;
+            self.persist_cursor(undo_signal.last_valid_cursor).await
+        }
+    }
+
+    /// Policy used to retry the blocks whose processing failed with a retryable error.
+    /// Blocks are not retried by default.
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Whether the processing of a block which failed with `error` may succeed if
+    /// retried (e.g.: the database was temporarily unavailable). Retried blocks are
+    /// preprocessed again.
+    fn is_retryable(&self, _error: &Self::Error) -> bool {
+        false
+    }
+
+    /// Preprocesses and applies a new block, retrying according to the retry policy
+    fn apply_block_scoped_data_with_retry(
+        &self,
+        block_data: BlockScopedData,
+        data: Result<T, Self::Error>,
+    ) -> impl std::future::Future<Output = Result<(), SinkError<Self::Error>>> + Send {
+        async move {
+            let policy = self.retry_policy();
+            let block = block_data.clock.as_ref().map(|clock| clock.number);
+
+            let mut data = data;
+            let mut attempt = 1;
+            loop {
+                let result = match data {
+                    Ok(data) => self.apply_block_scoped_data(block_data.clone(), data).await,
+                    Err(err) => Err(err),
+                };
+
+                let err = match result {
+                    Ok(()) => return Ok(()),
+                    Err(err) => err,
+                };
+
+                let retryable = self.is_retryable(&err);
+                if !retryable || attempt >= policy.max_attempts {
+                    return Err(SinkError::Block {
+                        block,
+                        attempts: attempt,
+                        retryable,
+                        error: err,
+                    });
+                }
+
+                let delay = policy.delay(attempt);
+                tracing::warn!(
+                    "Failed to process block #{} (attempt {}/{}), retrying in {:?}: {}",
+                    block.unwrap_or_default(),
+                    attempt,
+                    policy.max_attempts,
+                    delay,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+
+                attempt += 1;
+                data = self.preprocess_block_scoped_data(&block_data).await;
+            }
+        }
+    }
+
+    /// Applies a block undo signal, retrying according to the retry policy
+    fn apply_block_undo_signal_with_retry(
+        &self,
+        undo_signal: BlockUndoSignal,
+    ) -> impl std::future::Future<Output = Result<(), SinkError<Self::Error>>> + Send {
+        async move {
+            let policy = self.retry_policy();
+            let block = undo_signal
+                .last_valid_block
+                .as_ref()
+                .map(|block| block.number);
+
+            let mut attempt = 1;
+            loop {
+                let err = match self.apply_block_undo_signal(undo_signal.clone()).await {
+                    Ok(()) => return Ok(()),
+                    Err(err) => err,
+                };
+
+                let retryable = self.is_retryable(&err);
+                if !retryable || attempt >= policy.max_attempts {
+                    return Err(SinkError::Block {
+                        block,
+                        attempts: attempt,
+                        retryable,
+                        error: err,
+                    });
+                }
+
+                let delay = policy.delay(attempt);
+                tracing::warn!(
+                    "Failed to undo blocks after #{} (attempt {}/{}), retrying in {:?}: {}",
+                    block.unwrap_or_default(),
+                    attempt,
+                    policy.max_attempts,
+                    delay,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+
+                attempt += 1;
+            }
+        }
+    }
+
+    /// Resolves when the sink should stop pulling blocks. The block being applied at
+    /// that time is completed (along with its cursor) before [`Sink::run`] returns.
+    /// Never resolves by default.
+    fn shutdown_signal(&self) -> impl std::future::Future<Output = ()> + Send {
+        std::future::pending()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn run(
         &self,
-        endpoint_url: &str,
+        endpoints: EndpointPool,
         spkg_file: &str,
         module_name: &str,
-        start_block: i64,
-        end_block: u64,
+        block_range: BlockRange,
+        params: &[(String, String)],
+        final_blocks_only: bool,
         preprocess_buffer: Option<usize>,
-    ) -> impl std::future::Future<Output = Result<(), anyhow::Error>> + Send {
+    ) -> impl std::future::Future<Output = Result<(), SinkError<Self::Error>>> + Send {
         async move {
-            let token_env = env::var("SUBSTREAMS_API_TOKEN").unwrap_or("".to_string());
-            let mut token: Option<String> = None;
-            if !token_env.is_empty() {
-                token = Some(token_env);
-            }
-
-            let cursor: Option<String> = self.load_persisted_cursor().await?;
-
-            let package = read_package(spkg_file).await?;
+            let cursor: Option<String> = self
+                .load_persisted_cursor()
+                .await
+                .map_err(SinkError::Cursor)?;
 
-            let endpoint = Arc::new(SubstreamsEndpoint::new(&endpoint_url, token).await?);
+            let package = read_package(spkg_file).await.map_err(SinkError::Package)?;
 
-            let mut stream = SubstreamsStream::new(
-                endpoint.clone(),
+            let stream = SubstreamsStream::from_package(
+                endpoints,
                 cursor,
-                package.modules.clone(),
-                module_name.to_string(),
-                start_block,
-                end_block,
+                package,
+                module_name,
+                &block_range,
+                params,
+                final_blocks_only,
             )
-            .map(|raw_block_response| async move {
-                match raw_block_response {
-                    Ok(RawBlockResponse::New(raw_block)) => {
-                        let data = self.preprocess_block_scoped_data(&raw_block).await?;
-                        Ok(PreprocessedBlockResponse::New(raw_block, data))
-                    }
-                    Ok(RawBlockResponse::Undo(undo_signal)) => {
-                        self.process_block_undo_signal(&undo_signal)?;
-                        Ok(PreprocessedBlockResponse::Undo(undo_signal))
+            .map_err(SinkError::Package)?;
+
+            self.process_stream(stream, preprocess_buffer).await
+        }
+    }
+
+    /// Replays the blocks of the recording file at `path` (see [`crate::recording`])
+    /// through the sink. The persisted cursor is ignored: every recorded block is
+    /// processed, in order.
+    fn replay(
+        &self,
+        path: &std::path::Path,
+        preprocess_buffer: Option<usize>,
+    ) -> impl std::future::Future<Output = Result<(), SinkError<Self::Error>>> + Send {
+        async move {
+            let stream = SubstreamsStream::replay(path)
+                .await
+                .map_err(SinkError::Stream)?;
+
+            self.process_stream(stream, preprocess_buffer).await
+        }
+    }
+
+    /// Processes the blocks of `stream` until it ends, fails or a shutdown is requested.
+    /// Up to `preprocess_buffer` blocks are preprocessed ahead of the block being
+    /// applied.
+    fn process_stream(
+        &self,
+        stream: SubstreamsStream,
+        preprocess_buffer: Option<usize>,
+    ) -> impl std::future::Future<Output = Result<(), SinkError<Self::Error>>> + Send {
+        async move {
+            let mut stream = stream
+                .map(|raw_block_response| async move {
+                    match raw_block_response {
+                        // Preprocessing errors are handled (and possibly retried) when the
+                        // block is applied
+                        Ok(RawBlockResponse::New(raw_block)) => {
+                            let data = self.preprocess_block_scoped_data(&raw_block).await;
+                            Ok(PreprocessedBlockResponse::New(raw_block, data))
+                        }
+                        Ok(RawBlockResponse::Undo(undo_signal)) => {
+                            Ok(PreprocessedBlockResponse::Undo(undo_signal))
+                        }
+                        Err(err) => Err(err),
                     }
-                    Err(err) => Err(err),
-                }
-            })
-            .buffered(preprocess_buffer.unwrap_or(1));
+                })
+                .buffered(preprocess_buffer.unwrap_or(1));
+
+            let shutdown = self.shutdown_signal();
+            tokio::pin!(shutdown);
 
             loop {
-                match stream.next().await {
+                let next = tokio::select! {
+                    biased;
+                    _ = &mut shutdown => {
+                        tracing::info!("Shutdown requested, stream stopped");
+                        break;
+                    }
+                    next = stream.next() => next,
+                };
+
+                match next {
                     None => {
-                        println!("Stream consumed");
+                        tracing::info!("Stream consumed");
                         break;
                     }
                     Some(Ok(PreprocessedBlockResponse::New(raw_block, data))) => {
-                        self.process_block_scoped_data(&raw_block, data).await?;
-                        self.persist_cursor(raw_block.cursor).await?;
+                        self.apply_block_scoped_data_with_retry(raw_block, data)
+                            .await?;
                     }
                     Some(Ok(PreprocessedBlockResponse::Undo(undo_signal))) => {
-                        self.process_block_undo_signal(&undo_signal)?;
-                        self.persist_cursor(undo_signal.last_valid_cursor).await?;
+                        self.apply_block_undo_signal_with_retry(undo_signal).await?;
                     }
                     Some(Err(err)) => {
-                        println!();
-                        println!("Stream terminated with error");
-                        println!("{:?}", err);
-                        exit(1);
+                        tracing::error!("Stream terminated with error: {:#}", err);
+                        return Err(SinkError::Stream(err));
                     }
                 }
             }
@@ -139,7 +362,8 @@ pub trait Sink<T: Send>: Send + Sync {
     }
 }
 
-async fn read_package(input: &str) -> Result<Package, anyhow::Error> {
+/// Reads a substreams package from a local file or an HTTP(S) URL
+pub async fn read_package(input: &str) -> Result<Package, anyhow::Error> {
     if input.starts_with("http") {
         return read_http_package(input).await;
     }
@@ -156,57 +380,47 @@ async fn read_http_package(input: &str) -> Result<Package, anyhow::Error> {
     Package::decode(body).context("decode command")
 }
 
-#[allow(dead_code)]
-fn read_block_range(pkg: &Package, module_name: &str) -> Result<(i64, u64), anyhow::Error> {
+/// Resolves `range` against the initial block of the module `module_name` of `pkg`.
+/// Returns the start block and the stop block (0 if unbounded).
+pub fn read_block_range(
+    pkg: &Package,
+    module_name: &str,
+    range: &BlockRange,
+) -> Result<(i64, u64), anyhow::Error> {
     let module = pkg
-        .modules
-        .as_ref()
-        .unwrap()
         .modules
         .iter()
+        .flat_map(|modules| &modules.modules)
         .find(|m| m.name == module_name)
         .ok_or_else(|| format_err!("module '{}' not found in package", module_name))?;
 
-    let mut input: String = "".to_string();
-    if let Some(range) = env::args().nth(4) {
-        input = range;
-    };
-
-    let (prefix, suffix) = match input.split_once(":") {
-        Some((prefix, suffix)) => (prefix.to_string(), suffix.to_string()),
-        None => ("".to_string(), input),
-    };
-
-    let start: i64 = match prefix.as_str() {
-        "" => module.initial_block as i64,
-        x if x.starts_with("+") => {
-            let block_count = x
-                .trim_start_matches("+")
-                .parse::<u64>()
-                .context("argument <stop> is not a valid integer")?;
-
-            (module.initial_block + block_count) as i64
-        }
-        x => x
-            .parse::<i64>()
-            .context("argument <start> is not a valid integer")?,
-    };
-
-    let stop: u64 = match suffix.as_str() {
-        "" => 0,
-        "-" => 0,
-        x if x.starts_with("+") => {
-            let block_count = x
-                .trim_start_matches("+")
-                .parse::<u64>()
-                .context("argument <stop> is not a valid integer")?;
-
-            start as u64 + block_count
-        }
-        x => x
-            .parse::<u64>()
-            .context("argument <stop> is not a valid integer")?,
-    };
+    Ok(range.resolve(module.initial_block))
+}
+
+/// Sets the parameters of the modules, given as `(module name, value)` pairs. Fails if
+/// a module does not exist or does not take parameters.
+pub fn set_module_params(
+    modules: &mut Modules,
+    params: &[(String, String)],
+) -> Result<(), anyhow::Error> {
+    for (module_name, value) in params {
+        let module = modules
+            .modules
+            .iter_mut()
+            .find(|m| &m.name == module_name)
+            .ok_or_else(|| format_err!("module '{}' not found in package", module_name))?;
+
+        let params = module
+            .inputs
+            .iter_mut()
+            .find_map(|input| match &mut input.input {
+                Some(module::input::Input::Params(params)) => Some(params),
+                _ => None,
+            })
+            .ok_or_else(|| format_err!("module '{}' does not take parameters", module_name))?;
+
+        params.value = value.clone();
+    }
 
-    Ok((start, stop))
+    Ok(())
 }
@@ -1,5 +1,6 @@
 use std::{fmt::Display, sync::Arc, time::Duration};
 
+use anyhow::{anyhow, Context};
 use http::Uri;
 use tonic::{
     codec::CompressionEncoding,
@@ -28,15 +29,23 @@ impl SubstreamsEndpoint {
         let uri = url
             .as_ref()
             .parse::<Uri>()
-            .expect("the url should have been validated by now, so it is a valid Uri");
+            .with_context(|| format!("invalid substreams endpoint url {:?}", url.as_ref()))?;
 
         let endpoint = match uri.scheme_str() {
             Some("http") => Channel::builder(uri),
             Some("https") => Channel::builder(uri)
                 .tls_config(ClientTlsConfig::new().with_native_roots())
-                .expect("TLS config on this host is invalid"),
-            None => panic!("substreams endpoint uri must have a scheme (http or https)"),
-            _ => panic!("invalid uri scheme for substreams endpoint"),
+                .context("TLS config on this host is invalid")?,
+            None => {
+                return Err(anyhow!(
+                    "substreams endpoint {uri} must have a scheme (http or https)"
+                ))
+            }
+            Some(scheme) => {
+                return Err(anyhow!(
+                    "invalid uri scheme {scheme} for substreams endpoint {uri}"
+                ))
+            }
         }
         .connect_timeout(Duration::from_secs(10))
         .tcp_keepalive(Some(Duration::from_secs(30)));
@@ -81,3 +90,65 @@ impl SubstreamsEndpoint {
         Ok(block_stream)
     }
 }
+
+/// URL and API token of a substreams endpoint
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointConfig {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+/// Number of consecutive failures of an endpoint after which the stream switches to
+/// the next one by default
+pub const DEFAULT_FAILURES_BEFORE_FAILOVER: u32 = 3;
+
+/// Substreams endpoints a stream rotates through (in order) when the active one keeps
+/// failing. The stream resumes from its latest cursor on the new endpoint.
+#[derive(Clone, Debug)]
+pub struct EndpointPool {
+    endpoints: Vec<Arc<SubstreamsEndpoint>>,
+    pub(crate) failures_before_failover: u32,
+}
+
+impl EndpointPool {
+    pub async fn new(endpoints: &[EndpointConfig]) -> Result<Self, anyhow::Error> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("at least one substreams endpoint is required"));
+        }
+
+        let mut pool = Vec::with_capacity(endpoints.len());
+        for endpoint in endpoints {
+            pool.push(Arc::new(
+                SubstreamsEndpoint::new(&endpoint.url, endpoint.token.clone()).await?,
+            ));
+        }
+
+        Ok(Self {
+            endpoints: pool,
+            failures_before_failover: DEFAULT_FAILURES_BEFORE_FAILOVER,
+        })
+    }
+
+    /// Sets the number of consecutive failures (connection or stream errors) of the
+    /// active endpoint after which the stream switches to the next endpoint
+    pub fn failures_before_failover(mut self, failures: u32) -> Self {
+        self.failures_before_failover = failures.max(1);
+        self
+    }
+
+    pub fn endpoints(&self) -> &[Arc<SubstreamsEndpoint>] {
+        &self.endpoints
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Arc<SubstreamsEndpoint> {
+        self.endpoints[index % self.endpoints.len()].clone()
+    }
+}
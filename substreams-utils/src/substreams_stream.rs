@@ -2,8 +2,8 @@ use anyhow::{anyhow, Error};
 use async_stream::try_stream;
 use futures::{Stream, StreamExt};
 use std::{
+    collections::HashSet,
     pin::Pin,
-    sync::Arc,
     task::{Context, Poll},
     time::{Duration, Instant},
 };
@@ -13,9 +13,13 @@ use tokio_retry::strategy::ExponentialBackoff;
 use crate::pb::sf::substreams::rpc::v2::{
     response::Message, BlockScopedData, BlockUndoSignal, Request, Response,
 };
-use crate::pb::sf::substreams::v1::Modules;
+use crate::pb::sf::substreams::v1::{Modules, Package};
 
-use crate::substreams::SubstreamsEndpoint;
+use crate::block_range::BlockRange;
+use crate::metrics;
+use crate::recording;
+use crate::sink::{read_block_range, set_module_params};
+use crate::substreams::EndpointPool;
 
 pub enum RawBlockResponse {
     New(BlockScopedData),
@@ -28,45 +32,99 @@ pub struct SubstreamsStream {
 
 impl SubstreamsStream {
     pub fn new(
-        endpoint: Arc<SubstreamsEndpoint>,
+        endpoints: EndpointPool,
         cursor: Option<String>,
         modules: Option<Modules>,
         output_module_name: String,
         start_block: i64,
         end_block: u64,
+        final_blocks_only: bool,
     ) -> Self {
         SubstreamsStream {
             stream: Box::pin(stream_blocks(
-                endpoint,
+                endpoints,
                 cursor,
                 modules,
                 output_module_name,
                 start_block,
                 end_block,
+                final_blocks_only,
             )),
         }
     }
+
+    /// Streams the output module `module_name` of `package` over `block_range`, after
+    /// setting the module parameters `params` (see [`set_module_params`])
+    pub fn from_package(
+        endpoints: EndpointPool,
+        cursor: Option<String>,
+        package: Package,
+        module_name: &str,
+        block_range: &BlockRange,
+        params: &[(String, String)],
+        final_blocks_only: bool,
+    ) -> Result<Self, Error> {
+        let (start_block, end_block) = read_block_range(&package, module_name, block_range)?;
+
+        let mut modules = package.modules;
+        if let Some(modules) = modules.as_mut() {
+            set_module_params(modules, params)?;
+        }
+
+        Ok(Self::new(
+            endpoints,
+            cursor,
+            modules,
+            module_name.to_string(),
+            start_block,
+            end_block,
+            final_blocks_only,
+        ))
+    }
+
+    /// Streams the given responses, in order
+    pub fn from_responses(responses: Vec<RawBlockResponse>) -> Self {
+        SubstreamsStream {
+            stream: Box::pin(futures::stream::iter(responses).map(Ok)),
+        }
+    }
+
+    /// Streams the responses of the recording file at `path` (see [`crate::recording`])
+    pub async fn replay(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        Ok(Self::from_responses(recording::read_recording(path).await?))
+    }
 }
 
-// Create the Stream implementation that streams blocks with auto-reconnection.
+// Create the Stream implementation that streams blocks with auto-reconnection. After
+// repeated failures of the active endpoint, the stream fails over to the next endpoint
+// of the pool and resumes from the latest cursor.
 fn stream_blocks(
-    endpoint: Arc<SubstreamsEndpoint>,
+    endpoints: EndpointPool,
     cursor: Option<String>,
     modules: Option<Modules>,
     output_module_name: String,
     start_block_num: i64,
     stop_block_num: u64,
+    final_blocks_only: bool,
 ) -> impl Stream<Item = Result<RawBlockResponse, Error>> {
     let mut latest_cursor = cursor.unwrap_or_default();
     let mut backoff = ExponentialBackoff::from_millis(500).max_delay(Duration::from_secs(45));
     let mut last_progress_report = Instant::now();
 
+    let mut active = 0;
+    let mut failures = 0;
+    let mut failovers = 0;
+    let mut unauthenticated = HashSet::new();
+    metrics::set_active_endpoint(&endpoints, active);
+
     try_stream! {
         loop {
-            println!("Blockstreams disconnected, connecting (endpoint {}, start block {}, stop block {}, cursor {})",
+            let endpoint = endpoints.get(active);
+            println!("Blockstreams disconnected, connecting (endpoint {}, start block {}, stop block {}, final blocks only {}, cursor {})",
                 &endpoint,
                 start_block_num,
                 stop_block_num,
+                final_blocks_only,
                 &latest_cursor
             );
 
@@ -74,7 +132,9 @@ fn stream_blocks(
                 start_block_num,
                 start_cursor: latest_cursor.clone(),
                 stop_block_num,
-                final_blocks_only: false,
+                // When `true`, only irreversible blocks are streamed, meaning no `BlockUndoSignal`
+                // will ever be received at the cost of trailing the chain head.
+                final_blocks_only,
                 modules: modules.clone(),
                 output_module: output_module_name.clone(),
                 // There is usually no good reason for you to consume the stream development mode (so switching `true`
@@ -96,6 +156,8 @@ fn stream_blocks(
                             BlockProcessedResult::BlockScopedData(block_scoped_data) => {
                                 // Reset backoff because we got a good value from the stream
                                 backoff = ExponentialBackoff::from_millis(500).max_delay(Duration::from_secs(45));
+                                failures = 0;
+                                unauthenticated.clear();
 
                                 let cursor = block_scoped_data.cursor.clone();
                                 yield RawBlockResponse::New(block_scoped_data);
@@ -105,6 +167,8 @@ fn stream_blocks(
                             BlockProcessedResult::BlockUndoSignal(block_undo_signal) => {
                                 // Reset backoff because we got a good value from the stream
                                 backoff = ExponentialBackoff::from_millis(500).max_delay(Duration::from_secs(45));
+                                failures = 0;
+                                unauthenticated.clear();
 
                                 let cursor = block_undo_signal.last_valid_cursor.clone();
                                 yield RawBlockResponse::Undo(block_undo_signal);
@@ -113,13 +177,22 @@ fn stream_blocks(
                             },
                             BlockProcessedResult::Skip() => {},
                             BlockProcessedResult::TonicError(status) => {
-                                // Unauthenticated errors are not retried, we forward the error back to the
-                                // stream consumer which handles it
+                                // Unauthenticated errors are not retried against the same endpoint:
+                                // the stream fails over right away, and the error is forwarded back
+                                // to the stream consumer once every endpoint rejected its token
                                 if status.code() == tonic::Code::Unauthenticated {
-                                    return Err(anyhow::Error::new(status.clone()))?;
+                                    unauthenticated.insert(active);
+                                    if unauthenticated.len() == endpoints.len() {
+                                        return Err(anyhow::Error::new(status.clone()))?;
+                                    }
+
+                                    println!("Endpoint {} rejected the API token: {:#}", &endpoint, status);
+                                    failures = endpoints.failures_before_failover;
+                                } else {
+                                    println!("Received tonic error {:#}", status);
+                                    failures += 1;
                                 }
 
-                                println!("Received tonic error {:#}", status);
                                 encountered_error = true;
                                 break;
                             },
@@ -137,9 +210,27 @@ fn stream_blocks(
                     // having connection errors.
 
                     println!("Unable to connect to endpoint: {:#}", e);
+                    failures += 1;
                 }
             }
 
+            if endpoints.len() > 1 && failures >= endpoints.failures_before_failover {
+                active = (active + 1) % endpoints.len();
+                failures = 0;
+                failovers += 1;
+                metrics::ENDPOINT_FAILOVERS.inc();
+                metrics::set_active_endpoint(&endpoints, active);
+                println!("Failing over from endpoint {} to endpoint {} ({} failovers so far), resuming from cursor {}",
+                    &endpoint,
+                    endpoints.get(active),
+                    failovers,
+                    &latest_cursor
+                );
+
+                // The new endpoint gets a fresh backoff
+                backoff = ExponentialBackoff::from_millis(500).max_delay(Duration::from_secs(45));
+            }
+
             // If we reach this point, we must wait a bit before retrying
             if let Some(duration) = backoff.next() {
                 sleep(duration).await